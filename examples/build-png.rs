@@ -1,37 +1,183 @@
-use std::{fs::File, path::Path};
+//! Build an atlas from a font and write it next to this file as
+//! `demo-sdf.png`.
+//!
+//! ```text
+//! cargo run --example build-png -- [FONT] [--config SETTINGS] [--watch]
+//! ```
+//!
+//! The font is `roboto/Roboto-Regular.ttf` unless another path is given.
+//! The settings file holds lines of `key = value`, any of:
+//!
+//! ```text
+//! # the width and height of the image
+//! size = 255
+//! # the padding ratio of each glyph
+//! padding = 0.1
+//! # the chars to build, or all of Latin 1 if left out
+//! chars = abcdefghijklmnopqrstuvwxyz
+//! ```
+//!
+//! With `--watch`, keep running and build the atlas again whenever the font
+//! or the settings file changes, to see the effect of edits to either as
+//! the font is tuned.
 
-use blurry::{FontAssetBuilder, Glyph, GlyphRequest};
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
 
-static FONT_DATA: &[u8] = include_bytes!("roboto/Roboto-Regular.ttf");
+use blurry::{FontAssetBuilder, Glyph, GlyphRequest, SdfFontAsset};
+
+/// How often to check whether the inputs have changed
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 fn main() {
-    let face = ttf_parser::Face::parse(FONT_DATA, 0).unwrap();
-    let asset = FontAssetBuilder::with_texture_size(255, 255)
-        .build(blurry::latin1().map(|codepoint| GlyphRequest {
-            user_data: (),
-            face: &face,
-            codepoint,
-        }))
-        .unwrap();
-    let mut output_path = Path::new(file!()).parent().unwrap().to_path_buf();
-    output_path.push("demo-sdf.png");
-    let file = File::create(&output_path).unwrap();
-    let mut encoder = png::Encoder::new(file, 255, 255);
+    let mut watch = false;
+    let mut font_path = None;
+    let mut config_path = None;
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--watch" {
+            watch = true;
+        } else if arg == "--config" {
+            let path = args
+                .next()
+                .expect("--config needs the path of a settings file");
+            config_path = Some(PathBuf::from(path));
+        } else {
+            font_path = Some(PathBuf::from(arg));
+        }
+    }
+    let example_dir = Path::new(file!()).parent().unwrap();
+    let font_path = font_path.unwrap_or_else(|| example_dir.join("roboto/Roboto-Regular.ttf"));
+    let output_path = example_dir.join("demo-sdf.png");
+    let config_path = config_path.as_deref();
+    if !watch {
+        let asset = build(&font_path, config_path, &output_path).unwrap();
+        for Glyph {
+            key,
+            tex_left,
+            tex_bottom,
+            ..
+        } in asset.metadata
+        {
+            println!("glyph {key:?} @ {tex_left} , {tex_bottom}");
+        }
+        println!("checkout the image at '{}'", output_path.to_string_lossy());
+        return;
+    }
+    let inputs: Vec<&Path> = [Some(font_path.as_path()), config_path]
+        .into_iter()
+        .flatten()
+        .collect();
+    for input in &inputs {
+        println!("watching '{}' for changes", input.to_string_lossy());
+    }
+    let mut built = None;
+    loop {
+        // compare modification times rather than rely on a file watching
+        // service, so this works the same everywhere
+        let modified: Option<Vec<SystemTime>> = inputs.iter().map(|path| modified(path)).collect();
+        if modified.is_some() && modified != built {
+            built = modified;
+            // an input may be caught half written, so report failures and
+            // try again on the next change rather than stop
+            match build(&font_path, config_path, &output_path) {
+                Ok(asset) => println!(
+                    "built {} glyphs into '{}'",
+                    asset.metadata.len(),
+                    output_path.to_string_lossy()
+                ),
+                Err(err) => eprintln!("failed to build: {err}"),
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// When a file was last modified, or `None` if it can't be read
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// The settings read from a settings file
+struct Config {
+    size: u16,
+    padding: f32,
+    chars: Option<Vec<char>>,
+}
+
+impl Config {
+    /// Read the settings from the file at `path`, or the defaults if there
+    /// is none
+    fn read(path: Option<&Path>) -> Result<Self, String> {
+        let mut config = Self {
+            size: 255,
+            padding: 0.1,
+            chars: None,
+        };
+        let Some(path) = path else {
+            return Ok(config);
+        };
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key = value`, found `{line}`"))?;
+            let value = value.trim();
+            match key.trim() {
+                "size" => {
+                    config.size = value
+                        .parse()
+                        .ok()
+                        .filter(|&size| size >= 2)
+                        .ok_or_else(|| format!("bad size `{value}`"))?
+                }
+                "padding" => {
+                    config.padding = value
+                        .parse()
+                        .map_err(|_| format!("bad padding `{value}`"))?
+                }
+                "chars" => config.chars = Some(value.chars().collect()),
+                key => return Err(format!("unknown setting `{key}`")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Build the atlas from the font at `font_path` with the settings at
+/// `config_path`, and write its image to `output_path`
+fn build(
+    font_path: &Path,
+    config_path: Option<&Path>,
+    output_path: &Path,
+) -> Result<SdfFontAsset<()>, String> {
+    let config = Config::read(config_path)?;
+    let font_data = fs::read(font_path).map_err(|err| err.to_string())?;
+    let face = ttf_parser::Face::parse(&font_data, 0).map_err(|err| err.to_string())?;
+    let chars = config.chars.unwrap_or_else(|| blurry::latin1().collect());
+    let asset = FontAssetBuilder::with_texture_size(config.size, config.size)
+        .with_padding_ratio(config.padding)
+        .build(
+            chars
+                .into_iter()
+                .map(|codepoint| GlyphRequest::new((), &face, codepoint)),
+        )
+        .map_err(|err| format!("{err:?}"))?;
+    let file = File::create(output_path).map_err(|err| err.to_string())?;
+    let mut encoder = png::Encoder::new(file, asset.width.into(), asset.height.into());
     encoder.set_color(png::ColorType::Grayscale);
     encoder.set_depth(png::BitDepth::Eight);
     encoder
         .write_header()
-        .unwrap()
-        .write_image_data(&asset.data)
-        .unwrap();
-    for Glyph {
-        codepoint,
-        tex_left,
-        tex_bottom,
-        ..
-    } in asset.metadata
-    {
-        println!("glyph '{codepoint}' @ {tex_left} , {tex_bottom}");
-    }
-    println!("checkout the image at '{}'", output_path.to_string_lossy());
+        .and_then(|mut writer| writer.write_image_data(&asset.data))
+        .map_err(|err| err.to_string())?;
+    Ok(asset)
 }
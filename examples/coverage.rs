@@ -0,0 +1,88 @@
+use std::process::ExitCode;
+
+static USAGE: &str =
+    "usage: coverage FONT [--charset hexdigits|ascii|latin1|latin1-french|cjk] [--json]";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    let mut charset = String::from("latin1");
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--charset" => match args.next() {
+                Some(name) => charset = name,
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            "--json" => json = true,
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let Some(chars) = blurry::Charset::named(&charset) else {
+        eprintln!("unknown charset '{charset}'\n{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("couldn't read '{path}': {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let face = match ttf_parser::Face::parse(&data, 0) {
+        Ok(face) => face,
+        Err(err) => {
+            eprintln!("couldn't parse '{path}': {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let missing: Vec<char> = blurry::missing_chars(&face, &chars).collect();
+    if json {
+        let codepoints: Vec<String> = missing
+            .iter()
+            .map(|&ch| u32::from(ch).to_string())
+            .collect();
+        println!(
+            "{{\"font\":{},\"charset\":\"{charset}\",\"total\":{},\"missing\":[{}]}}",
+            json_string(&path),
+            chars.len(),
+            codepoints.join(",")
+        );
+    } else {
+        for ch in &missing {
+            println!("U+{:04X} {ch}", u32::from(*ch));
+        }
+        println!(
+            "{} of {} characters in {charset} missing",
+            missing.len(),
+            chars.len()
+        );
+    }
+    if missing.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Quote a string for JSON
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            ch if u32::from(ch) < 0x20 => quoted.push_str(&format!("\\u{:04x}", u32::from(ch))),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
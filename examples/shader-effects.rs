@@ -1,36 +1,30 @@
-use std::fmt::Write;
+use std::{borrow::Cow, fmt::Write};
 
 use glow::HasContext;
 
-use blurry::{latin1, ttf_parser::Face, FontAssetBuilder, Glyph, GlyphRequest};
+use blurry::{
+    effects::{Color, Config, Effect, EffectPreset, Offset},
+    latin1,
+    ttf_parser::Face,
+    FontAssetBuilder, Glyph, GlyphKey, GlyphRequest,
+};
 
 static DISPLAY_FONT_SIZE: f32 = 30.0;
 const PADDING_RATIO: f32 = 0.3;
 
-#[derive(Clone, Copy, Debug)]
-struct AdvanceWidth(f32);
-
 fn update_font(
     gl: &glow::Context,
     texture: glow::Texture,
     ttf_data: &[u8],
-) -> Result<Vec<Glyph<AdvanceWidth>>, &'static str> {
+) -> Result<Vec<Glyph<()>>, &'static str> {
     let face = Face::parse(ttf_data, 0).map_err(|_| "failed to parse font file")?;
-    let height = f32::from(face.units_per_em());
-    let mut asset = FontAssetBuilder::with_font_size(30.0)
+    let asset = FontAssetBuilder::with_font_size(30.0)
         .with_padding_ratio(PADDING_RATIO)
-        .build(latin1().map_while(|codepoint| {
-            let advance_width: f32 = face
-                .glyph_index(codepoint)
-                .and_then(|glyph_id| face.glyph_hor_advance(glyph_id))
-                .unwrap_or(0)
-                .into();
-            Some(GlyphRequest {
-                user_data: AdvanceWidth(advance_width / height),
-                face: &face,
-                codepoint,
-            })
-        }))
+        .build(
+            std::iter::once(' ')
+                .chain(latin1())
+                .map(|codepoint| GlyphRequest::new((), &face, codepoint)),
+        )
         .map_err(|err| match err {
             blurry::Error::MissingGlyph(_) => "the font file didn't contain all the characters",
             blurry::Error::PackingAtlasFailed => {
@@ -38,14 +32,6 @@ fn update_font(
             }
             _ => "an unspecified error occurred",
         })?;
-    let space_width = face
-        .glyph_index(' ')
-        .and_then(|idx| face.glyph_hor_advance(idx))
-        .map(|w| f32::from(w) / height)
-        .unwrap_or(0.25);
-    let mut space_glyph = asset.metadata[0];
-    space_glyph.user_data = AdvanceWidth(space_width);
-    space_glyph.codepoint = ' ';
     unsafe {
         gl.bind_texture(glow::TEXTURE_2D, Some(texture));
         gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
@@ -81,85 +67,96 @@ fn update_font(
             glow::CLAMP_TO_EDGE as _,
         );
     }
-    asset.metadata.push(space_glyph);
     Ok(asset.metadata)
 }
 
 static FIRST_FONT: &[u8] = include_bytes!("roboto/Roboto-Regular.ttf");
 
-#[derive(Clone, Copy, Debug)]
-struct Offset(f32, f32);
-
-#[derive(Clone, Copy, Debug)]
-struct Color(f32, f32, f32, f32);
-
-#[derive(Clone, Copy, Debug)]
-struct Config(f32, f32, f32);
-
-#[derive(Clone, Copy, Debug)]
-struct EffectPreset {
-    name: &'static str,
-    background_color: Color,
-    effects: &'static [(Offset, Color, Config)],
-}
-
 static EFFECTS: &[EffectPreset] = &[
     EffectPreset {
-        name: "white on grey",
+        name: Cow::Borrowed("white on grey"),
         background_color: Color(0.314, 0.314, 0.314, 1.0),
-        effects: &[(
-            Offset(0.0, 0.0),
-            Color(0.867, 0.867, 0.867, 1.0),
-            Config(0.5, 2.0, 0.0),
-        )],
+        effects: Cow::Borrowed(&[Effect {
+            offset: Offset(0.0, 0.0),
+            color: Color(0.867, 0.867, 0.867, 1.0),
+            config: Config {
+                start: 0.5,
+                end: 2.0,
+                smoothing: 0.0,
+            },
+        }]),
     },
     EffectPreset {
-        name: "basic drop shadow",
+        name: Cow::Borrowed("basic drop shadow"),
         background_color: Color(0.6, 0.5, 0.7, 1.0),
-        effects: &[
-            (
-                Offset(0.06, -0.06),
-                Color(0.0, 0.0, 0.0, 0.7),
-                Config(0.5, 2.0, 0.075),
-            ),
-            (
-                Offset(0.0, 0.0),
-                Color(1.0, 1.0, 1.0, 1.0),
-                Config(0.5, 2.0, 0.0),
-            ),
-        ],
+        effects: Cow::Borrowed(&[
+            Effect {
+                offset: Offset(0.06, -0.06),
+                color: Color(0.0, 0.0, 0.0, 0.7),
+                config: Config {
+                    start: 0.5,
+                    end: 2.0,
+                    smoothing: 0.075,
+                },
+            },
+            Effect {
+                offset: Offset(0.0, 0.0),
+                color: Color(1.0, 1.0, 1.0, 1.0),
+                config: Config {
+                    start: 0.5,
+                    end: 2.0,
+                    smoothing: 0.0,
+                },
+            },
+        ]),
     },
     EffectPreset {
-        name: "clouds",
+        name: Cow::Borrowed("clouds"),
         background_color: Color(0.0, 0.656, 1.0, 1.0),
-        effects: &[
-            (
-                Offset(0.0, 0.0),
-                Color(1.0, 1.0, 1.0, 1.0),
-                Config(0.1, 2.0, 0.0),
-            ),
-            (
-                Offset(0.0, 0.0),
-                Color(0.0, 0.656, 1.0, 1.0),
-                Config(0.45, 2.0, 0.0),
-            ),
-        ],
+        effects: Cow::Borrowed(&[
+            Effect {
+                offset: Offset(0.0, 0.0),
+                color: Color(1.0, 1.0, 1.0, 1.0),
+                config: Config {
+                    start: 0.1,
+                    end: 2.0,
+                    smoothing: 0.0,
+                },
+            },
+            Effect {
+                offset: Offset(0.0, 0.0),
+                color: Color(0.0, 0.656, 1.0, 1.0),
+                config: Config {
+                    start: 0.45,
+                    end: 2.0,
+                    smoothing: 0.0,
+                },
+            },
+        ]),
     },
     EffectPreset {
-        name: "over desktop",
+        name: Cow::Borrowed("over desktop"),
         background_color: Color(0.0, 0.0, 0.0, 0.0),
-        effects: &[
-            (
-                Offset(0.0, 0.0),
-                Color(0.0, 0.0, 0.0, 1.0),
-                Config(0.4, 2.0, 0.0),
-            ),
-            (
-                Offset(0.0, 0.0),
-                Color(1.0, 1.0, 1.0, 1.0),
-                Config(0.5, 2.0, 0.0),
-            ),
-        ],
+        effects: Cow::Borrowed(&[
+            Effect {
+                offset: Offset(0.0, 0.0),
+                color: Color(0.0, 0.0, 0.0, 1.0),
+                config: Config {
+                    start: 0.4,
+                    end: 2.0,
+                    smoothing: 0.0,
+                },
+            },
+            Effect {
+                offset: Offset(0.0, 0.0),
+                color: Color(1.0, 1.0, 1.0, 1.0),
+                config: Config {
+                    start: 0.5,
+                    end: 2.0,
+                    smoothing: 0.0,
+                },
+            },
+        ]),
     },
 ];
 
@@ -206,7 +203,7 @@ fn main() {
             *control_flow = ControlFlow::Wait;
             match event {
                 Event::RedrawRequested(_) => {
-                    let effect_preset = EFFECTS[current_effect];
+                    let effect_preset = &EFFECTS[current_effect];
                     let Color(r, g, b, a) = effect_preset.background_color;
                     gl.clear_color(r, g, b, a);
                     gl.clear(glow::COLOR_BUFFER_BIT);
@@ -226,9 +223,12 @@ fn main() {
                     let mut cursor_x = -1.0;
                     let mut cursor_y = 1.0 - font_mul_y;
                     for ch in text.chars() {
-                        if let Some(glyph) = glyphs.iter().find(|glyph| glyph.codepoint == ch) {
-                            let AdvanceWidth(advance) = glyph.user_data;
-                            if !ch.is_whitespace() {
+                        if let Some(glyph) = glyphs
+                            .iter()
+                            .find(|glyph| glyph.key == GlyphKey::Codepoint(ch))
+                        {
+                            let advance = glyph.advance;
+                            if !glyph.is_empty() {
                                 if (cursor_x + (advance * font_mul_x)) > 1.0 {
                                     cursor_x = -1.0;
                                     cursor_y -= font_mul_y;
@@ -254,8 +254,16 @@ fn main() {
                     gl.uniform_1_i32(sdf_uniform.as_ref(), 0);
                     gl.active_texture(glow::TEXTURE0);
                     gl.bind_texture(glow::TEXTURE_2D, Some(texture));
-                    for (Offset(x, y), Color(r, g, b, a), Config(start, end, smoothing)) in
-                        effect_preset.effects.iter().copied()
+                    for Effect {
+                        offset: Offset(x, y),
+                        color: Color(r, g, b, a),
+                        config:
+                            Config {
+                                start,
+                                end,
+                                smoothing,
+                            },
+                    } in effect_preset.effects.iter().copied()
                     {
                         let smoothing = if smoothing == 0.0 {
                             let distance_range_in_px = DISPLAY_FONT_SIZE * (2.0 * PADDING_RATIO);
@@ -389,7 +397,7 @@ fn push_glyph(
     offset_y: f32,
     font_mul_x: f32,
     font_mul_y: f32,
-    glyph: &Glyph<AdvanceWidth>,
+    glyph: &Glyph<()>,
 ) {
     // first triangle
     data.push([
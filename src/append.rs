@@ -0,0 +1,278 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::{cmp::Reverse, ops::Range};
+
+use crate::{
+    channel_pack, face, kerning, overlap,
+    packer::{PackerItem, PackerPlacement},
+    rows, CustomLayout, EmptyGlyphsPolicy, Error, FaceHandle, FontAssetBuilder, Glyph,
+    GlyphRequest, SdfFontAsset, UvOrigin,
+};
+
+impl<T: Clone> SdfFontAsset<T> {
+    /// Add glyphs to an asset which has already been built, such as
+    /// characters found to be missing at run time, without rendering the
+    /// rest again.  The glyphs are rendered with the same settings and font
+    /// size as the asset, and packed into the space its glyphs and reserved
+    /// rects leave free.  They are never rotated.
+    ///
+    /// The image keeps its size, so if the glyphs don't fit this fails with
+    /// [`Error::PackingAtlasFailed`], leaving the asset unchanged.  Edge
+    /// bleed and mip levels are redone for the whole image.
+    ///
+    /// Returns the metadata of the new glyphs, in the order they were
+    /// requested, which is also added to the end of
+    /// [`metadata`](Self::metadata).  If every glyph is left out, such as
+    /// for being [skipped](crate::MissingGlyphPolicy::Skip), this is empty
+    /// whatever the [`EmptyGlyphsPolicy`].
+    pub fn append<'a, I>(&mut self, glyphs: I) -> Result<&[Glyph<T>], Error>
+    where
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        let gutter = usize::from(self.gutter);
+        // the space taken, including the gutter below and left of each rect,
+        // in the coordinates of the area packers fill
+        let occupied = self
+            .rects
+            .iter()
+            .copied()
+            .chain(self.reserved_rects())
+            .map(|rect| crunch::Rect {
+                x: rect.x.saturating_sub(gutter),
+                y: rect.y.saturating_sub(gutter),
+                w: rect.w + gutter,
+                h: rect.h + gutter,
+            })
+            .collect();
+        let area = FreeArea {
+            width: self.width.saturating_sub(self.gutter).into(),
+            height: self.height.saturating_sub(self.gutter).into(),
+            stride: self.height.into(),
+            pages: self.pages().into(),
+        };
+        let builder = FontAssetBuilder {
+            reserved: Vec::new(),
+            trim: None,
+            bleed: false,
+            mipmaps: false,
+            kerning: false,
+            // appending nothing leaves the asset as it is
+            empty: EmptyGlyphsPolicy::Minimal,
+            ..self.builder.clone()
+        };
+        let layout = CustomLayout {
+            width: self.width,
+            height: self.height,
+            pages: self.pages(),
+            font_size: self.font_size,
+            place: Box::new(|items: &[PackerItem]| place_in_free_space(area, occupied, items)),
+        };
+        let added = builder.pack(&glyphs, Some(layout))?.raster()?;
+
+        for rect in &added.rects {
+            for texel in self.texel_bytes(*rect) {
+                self.data[texel.clone()].copy_from_slice(&added.data[texel]);
+            }
+        }
+        let start = self.metadata.len();
+        for mut glyph in added.metadata {
+            let face = added.faces[glyph.face.index()];
+            let index = match self.faces.iter().position(|known| *known == face) {
+                Some(index) => index,
+                None => {
+                    self.faces.push(face);
+                    self.metrics.push(added.metrics[glyph.face.index()]);
+                    self.faces.len() - 1
+                }
+            };
+            glyph.face = FaceHandle(index as u16);
+            self.metadata.push(glyph);
+        }
+        self.rects.extend(added.rects);
+        self.skipped.extend(added.skipped);
+        if self.builder.kerning {
+            // kern the new glyphs with each other and with the glyphs
+            // already in the asset from the same faces
+            for face in face::distinct_faces(glyphs.clone().map(|request| request.face)) {
+                let Some(handle) = self.face_handle(face) else {
+                    continue;
+                };
+                let face = self.builder.variations.apply(face);
+                let pairs = kerning::face_pairs(&face, handle, &self.metadata, start);
+                self.kerning.extend(pairs);
+            }
+            self.kerning
+                .sort_unstable_by_key(|pair| (pair.left, pair.right));
+        }
+        if cfg!(debug_assertions) {
+            let rects: Vec<crunch::Rect> = self
+                .rects
+                .iter()
+                .copied()
+                .chain(self.reserved_rects())
+                .collect();
+            overlap::check_rects(self.width, self.height, self.gutter, &rects);
+        }
+        self.refinish();
+        Ok(&self.metadata[start..])
+    }
+}
+
+impl<T> SdfFontAsset<T> {
+    /// Remove a glyph from the metadata, clearing the texels it was rendered
+    /// to and the gutter below and left of them.  The last glyph takes its
+    /// place in the metadata.  Edge bleed and mip levels are left as they
+    /// were, to be redone by [`refinish`](Self::refinish) once every glyph
+    /// to remove is gone.
+    pub(crate) fn evict(&mut self, index: usize) {
+        self.metadata.swap_remove(index);
+        if !self.kerning.is_empty() {
+            let (index, last) = (index as u32, self.metadata.len() as u32);
+            self.kerning
+                .retain(|pair| pair.left != index && pair.right != index);
+            for pair in &mut self.kerning {
+                for glyph in [&mut pair.left, &mut pair.right] {
+                    if *glyph == last {
+                        *glyph = index;
+                    }
+                }
+            }
+            self.kerning
+                .sort_unstable_by_key(|pair| (pair.left, pair.right));
+        }
+        let rect = self.rects.swap_remove(index);
+        let gutter = usize::from(self.gutter);
+        let with_gutter = crunch::Rect {
+            x: rect.x - gutter,
+            y: rect.y - gutter,
+            w: rect.w + gutter,
+            h: rect.h + gutter,
+        };
+        for texel in self.texel_bytes(with_gutter) {
+            self.data[texel].fill(0);
+        }
+    }
+
+    /// Bleed the edges of the glyphs and build the mip levels again, if
+    /// enabled, after glyphs are added or removed
+    pub(crate) fn refinish(&mut self) {
+        if self.builder.bleed || self.builder.mipmaps {
+            let (width, height) = (self.width, self.height);
+            let page_len = usize::from(width) * usize::from(height);
+            let row_len = usize::from(width) * usize::from(self.channels);
+            let mut data = rows::unpad(std::mem::take(&mut self.data), row_len, self.row_stride);
+            if self.builder.origin == UvOrigin::TopLeft {
+                // finishing the image flips the rows again
+                rows::flip(&mut data, row_len, height.into());
+            }
+            let (channels, pages) = if self.builder.channel_pack {
+                (1, channel_pack::deinterleave(&data, page_len))
+            } else {
+                (self.channels, data)
+            };
+            let reserved: Vec<crunch::Rect> = self.reserved_rects().collect();
+            (self.data, self.mip_levels) =
+                self.builder
+                    .finish_image(width, height, channels, pages, &self.rects, &reserved);
+        }
+    }
+
+    /// The number of layers, times the pages of glyphs packed into each
+    fn pages(&self) -> u16 {
+        self.layers * self.builder.pages_per_layer()
+    }
+
+    /// The reserved rects, in the pages of glyphs stacked from the bottom up
+    fn reserved_rects(&self) -> impl Iterator<Item = crunch::Rect> + '_ {
+        let pages_per_layer = self.builder.pages_per_layer();
+        self.reserved.iter().map(move |rect| {
+            let rect = match self.builder.origin {
+                UvOrigin::BottomLeft => *rect,
+                UvOrigin::TopLeft => rect.flipped(self.height),
+            };
+            rect.as_rect(self.height, pages_per_layer)
+        })
+    }
+
+    /// The bytes of [`data`](Self::data) holding each texel of a rect, given
+    /// in the pages of glyphs stacked from the bottom up
+    fn texel_bytes(&self, rect: crunch::Rect) -> impl Iterator<Item = Range<usize>> {
+        let (height, row_stride) = (usize::from(self.height), self.row_stride);
+        let pages_per_layer = usize::from(self.builder.pages_per_layer());
+        let channels = usize::from(self.channels);
+        let top_left = self.builder.origin == UvOrigin::TopLeft;
+        let (page, bottom) = (rect.y / height, rect.y % height);
+        let (layer, channel) = (page / pages_per_layer, page % pages_per_layer);
+        // each page has its share of the bytes of a texel
+        let bytes = channels / pages_per_layer;
+        (bottom..bottom + rect.h).flat_map(move |y| {
+            (rect.x..rect.x + rect.w).map(move |x| {
+                let row = if top_left { height - 1 - y } else { y };
+                let start = (layer * height + row) * row_stride + x * channels + channel * bytes;
+                start..start + bytes
+            })
+        })
+    }
+}
+
+/// The space to place appended glyphs in: `pages` areas of `width` by
+/// `height`, stacked `stride` apart
+#[derive(Clone, Copy)]
+struct FreeArea {
+    width: usize,
+    height: usize,
+    stride: usize,
+    pages: usize,
+}
+
+/// Place each item, tallest first, at the lowest and then leftmost spot in
+/// the area where it overlaps neither the `occupied` rects nor the items
+/// placed before it.  Spots are tried along the top and right edges of what
+/// is already placed, and at the bottom of each page.
+fn place_in_free_space(
+    area: FreeArea,
+    mut occupied: Vec<crunch::Rect>,
+    items: &[PackerItem],
+) -> Option<Vec<PackerPlacement>> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&index| Reverse(items[index].height));
+    let mut placements = Vec::with_capacity(items.len());
+    for index in order {
+        let (w, h) = (items[index].width as usize, items[index].height as usize);
+        let sorted = |mut edges: Vec<usize>| {
+            edges.sort_unstable();
+            edges.dedup();
+            edges
+        };
+        let xs = sorted(
+            std::iter::once(0)
+                .chain(occupied.iter().map(|rect| rect.x + rect.w))
+                .collect(),
+        );
+        let ys = sorted(
+            (0..area.pages)
+                .map(|page| page * area.stride)
+                .chain(occupied.iter().map(|rect| rect.y + rect.h))
+                .collect(),
+        );
+        let overlaps = |x: usize, y: usize| {
+            occupied.iter().any(|rect| {
+                x < rect.x + rect.w && rect.x < x + w && y < rect.y + rect.h && rect.y < y + h
+            })
+        };
+        let (x, y) = ys
+            .iter()
+            .filter(|&&y| y / area.stride < area.pages && y % area.stride + h <= area.height)
+            .flat_map(|&y| xs.iter().map(move |&x| (x, y)))
+            .find(|&(x, y)| x + w <= area.width && !overlaps(x, y))?;
+        occupied.push(crunch::Rect { x, y, w, h });
+        placements.push(PackerPlacement {
+            item: index,
+            x: x as u32,
+            y: y as u32,
+            rotated: false,
+        });
+    }
+    Some(placements)
+}
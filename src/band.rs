@@ -0,0 +1,138 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::{DistanceMode, Glyph, SdfFontAsset};
+
+/// Which texels of a glyph's tile lie near its outline, see
+/// [`SdfFontAsset::band_mask`].  Texels are numbered row by row, with rows
+/// in the order of [`data`](SdfFontAsset::data) and `(0, 0)` at the
+/// tile's texel at [`x`](Glyph::x) and [`y`](Glyph::y).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BandMask {
+    /// The width of the tile in texels
+    pub width: u16,
+
+    /// The height of the tile in texels
+    pub height: u16,
+
+    /// One bit per texel, set for texels in the band, numbered row by row
+    /// and packed lowest bit first
+    bits: Vec<u8>,
+}
+
+impl BandMask {
+    /// Whether a texel of the tile is in the band
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let bit = usize::from(y) * usize::from(self.width) + usize::from(x);
+        self.bits[bit / 8] & (1 << (bit % 8)) != 0
+    }
+
+    /// The number of texels in the band
+    pub fn count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    /// The mask, one bit per texel, numbered row by row and packed lowest
+    /// bit first
+    pub fn bits(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// The band as rects of `(x, y, width, height)` in the tile, which
+    /// cover it exactly without overlapping.  Each run of texels along a
+    /// row is a rect, merged with the runs in the rows after it which
+    /// start and end at the same columns.
+    pub fn rects(&self) -> Vec<(u16, u16, u16, u16)> {
+        let mut done = Vec::new();
+        // the rects which reach the row before, by their start and end
+        let mut open: Vec<(u16, u16, u16, u16)> = Vec::new();
+        for y in 0..self.height {
+            let mut runs = Vec::new();
+            let mut x = 0;
+            while x < self.width {
+                if !self.contains(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < self.width && self.contains(x, y) {
+                    x += 1;
+                }
+                runs.push((start, x - start));
+            }
+            let mut next = Vec::with_capacity(runs.len());
+            for (x, width) in runs {
+                match open.iter().position(|rect| rect.0 == x && rect.2 == width) {
+                    Some(index) => {
+                        let (x, top, width, height) = open.swap_remove(index);
+                        next.push((x, top, width, height + 1));
+                    }
+                    None => next.push((x, y, width, 1)),
+                }
+            }
+            done.append(&mut open);
+            open = next;
+        }
+        done.append(&mut open);
+        done
+    }
+}
+
+impl<T> SdfFontAsset<T> {
+    /// Find the texels of a glyph's tile within `texels` of its outline, as
+    /// it was rendered, measured in texels of the atlas.  Texels farther
+    /// than [`spread_px`](Self::spread_px) all hold the farthest value, so
+    /// those outside a band of that width or more hold nothing else.  This
+    /// suits compressing the rest of the tile more coarsely, or uploading
+    /// only the band.
+    ///
+    /// The distances are read back from [`data`](Self::data), so the mask
+    /// is only as precise as the texel values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the glyph's tile lies outside the data, such as for a
+    /// glyph of another asset.
+    pub fn band_mask(&self, glyph: &Glyph<T>, texels: f32) -> BandMask {
+        let (width, height) = (glyph.width, glyph.height);
+        let len = usize::from(width) * usize::from(height);
+        let mut bits = vec![0; len.div_ceil(8)];
+        // channel packed assets keep each page of glyphs in its own byte,
+        // and others keep the distance before any coverage
+        let byte = if self.builder.channel_pack {
+            usize::from(glyph.channel)
+        } else {
+            0
+        };
+        let channels = usize::from(self.channels);
+        let first_row = usize::from(glyph.layer) * usize::from(self.height) + usize::from(glyph.y);
+        for y in 0..usize::from(height) {
+            let row = &self.data[(first_row + y) * self.row_stride..];
+            for x in 0..usize::from(width) {
+                let value = row[(usize::from(glyph.x) + x) * channels + byte];
+                let value = f32::from(value) / f32::from(u8::MAX);
+                // undo the encoding, as a distance from the rendered outline
+                let distance = match self.mode {
+                    DistanceMode::Signed => (0.5 - value).abs() * 2.0 * self.spread_px,
+                    DistanceMode::Unsigned => (1.0 - value) * self.spread_px,
+                };
+                if distance <= texels {
+                    let bit = y * usize::from(width) + x;
+                    bits[bit / 8] |= 1 << (bit % 8);
+                }
+            }
+        }
+        BandMask {
+            width,
+            height,
+            bits,
+        }
+    }
+}
@@ -1,7 +1,257 @@
 /* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
 /* Copyright © 2023 Violet Leonard */
 
-use crate::{GlyphRequest, PackResult};
+use std::{cmp::Reverse, fmt::Display};
+
+use crate::{
+    packer::{Packer, PackerItem, PackerPlacement},
+    raster::RasteredSize,
+    reserved::ReservedRect,
+    AtlasShape, Error, GlyphRequest, PackResult, PackedGlyph,
+};
+
+/// How glyphs are laid out, whichever size is being searched for
+#[derive(Clone, Copy)]
+pub struct PackArgs<'p> {
+    pub allow_rotate: bool,
+    pub packer: &'p dyn Packer,
+    /// Empty texels between glyphs, and before the first row and column
+    pub gutter: u16,
+    /// The width, height, and key of each rect to set aside
+    pub reserved: &'p [(u16, u16, u32)],
+    /// The number of areas to fill one after another, each a gutter apart
+    pub layers: u16,
+}
+
+/// Glyphs and reserved rects, placed in the atlas
+pub struct Packing<'a, T> {
+    pub glyphs: PackResult<'a, T>,
+    pub reserved: Vec<ReservedRect>,
+}
+
+impl PackArgs<'_> {
+    fn item<T>(&self, req: &GlyphRequest<'_, T>, size: RasteredSize) -> PackerItem {
+        PackerItem {
+            width: u32::from(size.pixel_width) + u32::from(self.gutter),
+            height: u32::from(size.pixel_height) + u32::from(self.gutter),
+            allow_rotation: req.rotation.unwrap_or(self.allow_rotate),
+        }
+    }
+
+    /// The area in texels covered by the glyphs measured at a font size and
+    /// by the reserved rects, including their gutters
+    fn covered_area<'a, T, I>(
+        &self,
+        font_size: f32,
+        measure: &impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+        glyphs: &I,
+    ) -> Result<f32, Error>
+    where
+        I: Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        let gutter = f32::from(self.gutter);
+        let measured = measure_all(font_size, measure, glyphs)?;
+        let glyphs = measured
+            .iter()
+            .map(|(size, _)| (f32::from(size.pixel_width), f32::from(size.pixel_height)));
+        let reserved = self
+            .reserved
+            .iter()
+            .map(|&(w, h, _)| (f32::from(w), f32::from(h)));
+        Ok(glyphs
+            .chain(reserved)
+            .map(|(w, h)| (w + gutter) * (h + gutter))
+            .sum())
+    }
+
+    /// Measure the glyphs at a font size, and pack them along with the
+    /// reserved rects into a `width` by `height` area which starts after a
+    /// gutter.  Returns `None` if they don't fit.
+    fn pack<'a, T, I>(
+        &self,
+        width: u16,
+        height: u16,
+        font_size: f32,
+        measure: &impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+        glyphs: &I,
+    ) -> Result<Option<Packing<'a, T>>, Error>
+    where
+        I: Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        let measured = measure_all(font_size, measure, glyphs)?;
+        let (width, height) = (u32::from(width), u32::from(height));
+        let stride = height + u32::from(self.gutter);
+        let place = |items: &[PackerItem]| {
+            if self.layers > 1 {
+                self.pack_layers(width, height, stride, items)
+            } else {
+                self.packer.pack(width, height, items)
+            }
+        };
+        let packer = format_args!("{:?}", self.packer);
+        let total_height = u32::from(self.layers - 1) * stride + height;
+        Ok(self.place(width, total_height, stride, measured, &packer, place))
+    }
+
+    /// Pack items with the packer into as many as `self.layers` areas of
+    /// `width` by `height`, stacked `stride` apart.  Each area takes as many
+    /// of the remaining items, in order, as it can fit.
+    fn pack_layers(
+        &self,
+        width: u32,
+        height: u32,
+        stride: u32,
+        items: &[PackerItem],
+    ) -> Option<Vec<PackerPlacement>> {
+        let mut remaining: Vec<usize> = (0..items.len()).collect();
+        let mut placements = Vec::with_capacity(items.len());
+        for layer in 0..u32::from(self.layers) {
+            if remaining.is_empty() {
+                break;
+            }
+            let pack_first = |count: usize| {
+                let subset: Vec<PackerItem> =
+                    remaining[..count].iter().map(|&item| items[item]).collect();
+                self.packer.pack(width, height, &subset)
+            };
+            let (mut fits, mut too_many) = (0, remaining.len() + 1);
+            let mut packed = Vec::new();
+            while fits + 1 < too_many {
+                // try everything left first, since it often fits
+                let count = if fits == 0 && too_many > remaining.len() {
+                    remaining.len()
+                } else {
+                    (fits + too_many) / 2
+                };
+                match pack_first(count) {
+                    Some(placed) => {
+                        fits = count;
+                        packed = placed;
+                    }
+                    None => too_many = count,
+                }
+            }
+            if fits == 0 {
+                return None;
+            }
+            placements.extend(packed.into_iter().map(|placement| PackerPlacement {
+                item: remaining[placement.item],
+                y: placement.y + layer * stride,
+                ..placement
+            }));
+            remaining.drain(..fits);
+        }
+        remaining.is_empty().then_some(placements)
+    }
+
+    /// Place measured glyphs and the reserved rects in a `width` by `height`
+    /// area which starts after a gutter, using `place`, which is named by
+    /// `who` if it breaks the rules of [`Packer::pack`].  Layers of the atlas
+    /// are stacked `stride` apart in the area.  Returns `None` if they don't
+    /// fit.
+    pub fn place<'a, T>(
+        &self,
+        width: u32,
+        height: u32,
+        stride: u32,
+        measured: Vec<(RasteredSize, GlyphRequest<'a, T>)>,
+        who: &dyn Display,
+        place: impl FnOnce(&[PackerItem]) -> Option<Vec<PackerPlacement>>,
+    ) -> Option<Packing<'a, T>> {
+        let gutter = u32::from(self.gutter);
+        let reserved_items = self.reserved.iter().map(|&(w, h, _)| PackerItem {
+            width: u32::from(w) + gutter,
+            height: u32::from(h) + gutter,
+            allow_rotation: false,
+        });
+        let items: Vec<PackerItem> = measured
+            .iter()
+            .map(|(size, req)| self.item(req, *size))
+            .chain(reserved_items)
+            .collect();
+        let placements = place(&items)?;
+        assert_eq!(
+            placements.len(),
+            items.len(),
+            "{who} didn't place every glyph once"
+        );
+        let glyph_count = measured.len();
+        let mut measured: Vec<_> = measured.into_iter().map(Some).collect();
+        let mut placed = vec![false; items.len()];
+        let mut glyphs = Vec::with_capacity(glyph_count);
+        let mut reserved = Vec::with_capacity(self.reserved.len());
+        for placement in placements {
+            let first_time = placed
+                .get_mut(placement.item)
+                .is_some_and(|placed| !std::mem::replace(placed, true));
+            assert!(first_time, "{who} didn't place every glyph once");
+            let item = items[placement.item];
+            let (w, h) = if placement.rotated {
+                (item.height, item.width)
+            } else {
+                (item.width, item.height)
+            };
+            assert!(
+                u64::from(placement.x) + u64::from(w) <= u64::from(width)
+                    && u64::from(placement.y) + u64::from(h) <= u64::from(height),
+                "{who} placed a glyph out of bounds"
+            );
+            // strip the gutter, which is past the glyph's right and top
+            let rect = crunch::Rect {
+                x: (placement.x + gutter) as usize,
+                y: (placement.y + gutter) as usize,
+                w: (w - gutter) as usize,
+                h: (h - gutter) as usize,
+            };
+            if let Some(index) = placement.item.checked_sub(glyph_count) {
+                assert!(!placement.rotated, "{who} rotated a reserved rect");
+                let (_, _, key) = self.reserved[index];
+                // the layer is the page, until pages are put in channels
+                let rect = ReservedRect {
+                    key,
+                    layer: (rect.y as u32 / stride) as u16,
+                    channel: 0,
+                    x: rect.x as u16,
+                    y: (rect.y as u32 % stride) as u16,
+                    width: rect.w as u16,
+                    height: rect.h as u16,
+                };
+                reserved.push((index, rect));
+                continue;
+            }
+            let (size, request) = measured[placement.item].take().expect("placed once");
+            let glyph = PackedGlyph {
+                request,
+                size,
+                rect,
+                rotated: placement.rotated,
+            };
+            glyphs.push((placement.item, glyph));
+        }
+        // report glyphs in the order they were requested, and reserved rects
+        // in the order they were given, whatever order they were placed in
+        glyphs.sort_by_key(|&(index, _)| index);
+        let glyphs = glyphs.into_iter().map(|(_, glyph)| glyph).collect();
+        reserved.sort_by_key(|&(index, _)| index);
+        let reserved = reserved.into_iter().map(|(_, rect)| rect).collect();
+        Some(Packing { glyphs, reserved })
+    }
+}
+
+/// Measure every glyph at a font size
+pub fn measure_all<'a, T, I>(
+    font_size: f32,
+    measure: &impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    glyphs: &I,
+) -> Result<Vec<(RasteredSize, GlyphRequest<'a, T>)>, Error>
+where
+    I: Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    glyphs
+        .clone()
+        .map(|req| Ok((measure(font_size, &req)?, req)))
+        .collect()
+}
 
 pub struct BisectArgs<T> {
     pub lower_bound: T,
@@ -9,151 +259,444 @@ pub struct BisectArgs<T> {
     pub attempts: u32,
 }
 
+/// The fraction of the atlas packers are assumed to fill, when estimating
+/// the size at which glyphs fit
+const ESTIMATED_FILL: f32 = 0.8;
+
+/// The factor between the sizes tried when looking for a narrow range
+/// around an estimated size
+const BRACKET_STEP: f32 = 1.0625;
+
+/// Estimate the font size at which the glyphs cover `area` texels, starting
+/// from a guess, and assuming the area they cover grows with the square of
+/// the font size
+fn estimate_font_size<'a, T, I>(
+    area: f32,
+    guess: f32,
+    measure: &impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
+    glyphs: &I,
+) -> Result<f32, Error>
+where
+    I: Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    let mut font_size = guess;
+    // estimate twice, since the gutter and the rounding of glyphs to whole
+    // texels don't scale with the font size
+    for _ in 0..2 {
+        let covered = pack_args.covered_area(font_size, measure, glyphs)?;
+        if covered <= 0.0 {
+            break;
+        }
+        font_size *= (area / covered).sqrt();
+    }
+    Ok(font_size)
+}
+
+/// Search for the largest font size at which the glyphs fit in the atlas,
+/// by halving the range between the bounds `attempts` times, and then until
+/// a size fits.  If `estimate` is set, the search instead starts from a
+/// narrow range around a size estimated from the area the glyphs cover,
+/// and narrows it as far as the full search would.
 pub fn bisect_font_size<'a, T, I>(
     asset_width: u16,
     asset_height: u16,
-    padding_ratio: f32,
-    allow_rotate: bool,
+    measure: impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
     args: BisectArgs<f32>,
+    estimate: bool,
     glyphs: &I,
-) -> Result<(f32, PackResult<'a, T>), crate::Error>
+) -> Result<(f32, Packing<'a, T>), Error>
 where
     T: Clone,
     I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
 {
-    let rot = if allow_rotate {
-        crunch::Rotation::Allowed
-    } else {
-        crunch::Rotation::None
-    };
-    let mut attempts_remaining = args.attempts;
+    let (width, height) = (
+        asset_width.saturating_sub(pack_args.gutter),
+        asset_height.saturating_sub(pack_args.gutter),
+    );
+    let pack = |font_size| pack_args.pack(width, height, font_size, &measure, glyphs);
     let BisectArgs {
         mut lower_bound,
         mut too_big,
-        ..
+        mut attempts,
     } = args;
-    loop {
-        attempts_remaining = attempts_remaining.saturating_sub(1);
-
-        let check_size = (lower_bound + too_big) / 2.0;
-        let mut missing_glyph = Ok(());
-        let rects = glyphs.clone().map_while(|req| {
-            let rastered_size = match crate::raster::get_rastered_size(
-                padding_ratio,
-                check_size,
-                req.face,
-                req.codepoint,
-            ) {
-                Ok(sz) => sz,
-                Err(ch) => {
-                    missing_glyph = Err(crate::Error::MissingGlyph(ch));
-                    return None;
+    let mut best = None;
+    if estimate {
+        let mut precision = too_big - lower_bound;
+        for _ in 0..attempts {
+            precision /= 2.0;
+        }
+        let area = f32::from(width) * f32::from(height) * f32::from(pack_args.layers);
+        let guess = estimate_font_size(
+            area * ESTIMATED_FILL,
+            (lower_bound + too_big) / 2.0,
+            &measure,
+            pack_args,
+            glyphs,
+        )?
+        .clamp(lower_bound, too_big);
+        match pack(guess)? {
+            Some(packing) => {
+                (lower_bound, best) = (guess, Some(packing));
+                loop {
+                    let next = lower_bound * BRACKET_STEP;
+                    if next >= too_big {
+                        break;
+                    }
+                    match pack(next)? {
+                        Some(packing) => (lower_bound, best) = (next, Some(packing)),
+                        None => {
+                            too_big = next;
+                            break;
+                        }
+                    }
                 }
-            };
-            Some(crunch::Item {
-                data: Box::new((req, rastered_size)),
-                w: (rastered_size.pixel_width + 1).into(),
-                h: (rastered_size.pixel_height + 1).into(),
-                rot,
-            })
-        });
-        let pack_width = (asset_width - 1).into();
-        let pack_height = (asset_height - 1).into();
-        match crunch::Packer::with_items(rects).pack(crunch::Rect {
-            x: 1,
-            y: 1,
-            w: pack_width,
-            h: pack_height,
-        }) {
-            Ok(result) => {
-                missing_glyph?;
-                lower_bound = check_size;
-                if attempts_remaining == 0 {
-                    return Ok((lower_bound, result));
+            }
+            None => {
+                too_big = guess;
+                loop {
+                    let next = too_big / BRACKET_STEP;
+                    if next <= lower_bound {
+                        break;
+                    }
+                    match pack(next)? {
+                        Some(packing) => {
+                            (lower_bound, best) = (next, Some(packing));
+                            break;
+                        }
+                        None => too_big = next,
+                    }
                 }
             }
-            Err(_) => {
-                missing_glyph?;
-                too_big = check_size;
+        }
+        let mut range = too_big - lower_bound;
+        attempts = 0;
+        while range > precision {
+            range /= 2.0;
+            attempts += 1;
+        }
+    }
+    loop {
+        if attempts == 0 {
+            if let Some(packing) = best.take() {
+                return Ok((lower_bound, packing));
             }
         }
+        attempts = attempts.saturating_sub(1);
+
+        let check_size = (lower_bound + too_big) / 2.0;
+        if check_size <= lower_bound || check_size >= too_big {
+            // the bounds have met without a size that fits
+            return Err(Error::PackingAtlasFailed);
+        }
+        match pack(check_size)? {
+            Some(packing) => (lower_bound, best) = (check_size, Some(packing)),
+            None => too_big = check_size,
+        }
     }
 }
 
-pub fn bisect_asset_size<'a, T, I>(
+/// Pack the glyphs at a fixed font size into a fixed size atlas, leaving
+/// out those of the lowest priority, and then those requested last, until
+/// the rest fit.  Returns the packing and the glyphs left out, in the order
+/// they were requested.
+pub fn pack_by_priority<'a, T, I>(
+    asset_width: u16,
+    asset_height: u16,
     font_size: f32,
-    padding_ratio: f32,
-    allow_rotate: bool,
+    measure: impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
     glyphs: &I,
-) -> Result<(u16, PackResult<'a, T>), crate::Error>
+) -> Result<(Packing<'a, T>, Vec<GlyphRequest<'a, T>>), Error>
 where
     T: Clone,
     I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
 {
-    let rot = if allow_rotate {
-        crunch::Rotation::Allowed
-    } else {
-        crunch::Rotation::None
+    let mut by_priority: Vec<(usize, i32)> = glyphs
+        .clone()
+        .map(|request| request.priority)
+        .enumerate()
+        .collect();
+    // stable, so glyphs requested first stay ahead of those of equal priority
+    by_priority.sort_by_key(|&(_, priority)| Reverse(priority));
+    let kept = |count: usize| {
+        let mut kept = vec![false; by_priority.len()];
+        for &(index, _) in &by_priority[..count] {
+            kept[index] = true;
+        }
+        kept
     };
-    let mut too_small = (font_size.floor().clamp(2.0, u16::MAX.into()) as u16) - 1;
-    let missing_glyph = std::cell::Cell::new(Ok(()));
-    let mut map_glyphs = |req: GlyphRequest<'a, T>| {
-        let rastered_size = match crate::raster::get_rastered_size(
-            padding_ratio,
+    let pack_first = |count: usize| {
+        let subset = glyphs
+            .clone()
+            .zip(kept(count))
+            .filter_map(|(request, kept)| kept.then_some(request));
+        pack_args.pack(
+            asset_width.saturating_sub(pack_args.gutter),
+            asset_height.saturating_sub(pack_args.gutter),
             font_size,
-            req.face,
-            req.codepoint,
-        ) {
-            Ok(sz) => sz,
-            Err(ch) => {
-                missing_glyph.set(Err(crate::Error::MissingGlyph(ch)));
-                return None;
+            &measure,
+            &subset,
+        )
+    };
+    if let Some(packing) = pack_first(by_priority.len())? {
+        return Ok((packing, Vec::new()));
+    }
+    let (mut fits, mut too_many) = (0, by_priority.len());
+    let mut packed = pack_first(0)?.ok_or(Error::PackingAtlasFailed)?;
+    while fits + 1 < too_many {
+        let count = (fits + too_many) / 2;
+        match pack_first(count)? {
+            Some(packing) => {
+                fits = count;
+                packed = packing;
             }
-        };
-        Some(crunch::Item {
-            data: Box::new((req, rastered_size)),
-            w: (rastered_size.pixel_width + 1).into(),
-            h: (rastered_size.pixel_height + 1).into(),
-            rot,
-        })
+            None => too_many = count,
+        }
+    }
+    let left_out = glyphs
+        .clone()
+        .zip(kept(fits))
+        .filter_map(|(request, kept)| (!kept).then_some(request))
+        .collect();
+    Ok((packed, left_out))
+}
+
+/// Aspect ratios tried by [`AtlasShape::Free`], squarest first so it wins
+/// ties
+const FREE_RATIOS: [f32; 9] = [
+    1.0,
+    std::f32::consts::SQRT_2,
+    std::f32::consts::FRAC_1_SQRT_2,
+    2.0,
+    0.5,
+    2.0 * std::f32::consts::SQRT_2,
+    0.5 * std::f32::consts::FRAC_1_SQRT_2,
+    4.0,
+    0.25,
+];
+
+/// The dimensions of an atlas whose longer side is `side`
+fn with_aspect(side: u16, ratio: f32) -> (u16, u16) {
+    let shorter = |side: u16, ratio: f32| {
+        (f32::from(side) * ratio)
+            .round()
+            .clamp(2.0, u16::MAX.into()) as u16
     };
-    let mut result = match crunch::Packer::with_items(glyphs.clone().map_while(&mut map_glyphs))
-        .pack(crunch::Rect {
-            x: 1,
-            y: 1,
-            w: u16::MAX.into(),
-            h: u16::MAX.into(),
-        }) {
-        Ok(res) => {
-            missing_glyph.get()?;
-            res
-        }
-        Err(_) => {
-            missing_glyph.get()?;
-            return Err(crate::Error::PackingAtlasFailed);
+    if ratio >= 1.0 {
+        (side, shorter(side, 1.0 / ratio))
+    } else {
+        (shorter(side, ratio), side)
+    }
+}
+
+pub fn bisect_asset_size<'a, T, I>(
+    font_size: f32,
+    max_side: u16,
+    measure: impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
+    shape: AtlasShape,
+    glyphs: &I,
+) -> Result<((u16, u16), Packing<'a, T>), Error>
+where
+    T: Clone,
+    I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    let smallest =
+        |ratio| smallest_with_aspect(font_size, max_side, &measure, pack_args, ratio, glyphs);
+    match shape {
+        AtlasShape::Square => smallest(1.0),
+        AtlasShape::AspectRatio(ratio) => smallest(ratio),
+        AtlasShape::Free => {
+            let mut best: Option<((u16, u16), Packing<'a, T>)> = None;
+            for ratio in FREE_RATIOS {
+                let ((width, height), packing) = match smallest(ratio) {
+                    Ok(found) => found,
+                    Err(Error::PackingAtlasFailed) => continue,
+                    Err(err) => return Err(err),
+                };
+                let area = u32::from(width) * u32::from(height);
+                if best
+                    .as_ref()
+                    .is_none_or(|((w, h), _)| area < u32::from(*w) * u32::from(*h))
+                {
+                    best = Some(((width, height), packing));
+                }
+            }
+            best.ok_or(Error::PackingAtlasFailed)
         }
+    }
+}
+
+/// Find the smallest atlas of a fixed aspect ratio, with no side longer than
+/// `max_side`, which fits every glyph
+fn smallest_with_aspect<'a, T, I>(
+    font_size: f32,
+    max_side: u16,
+    measure: &impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
+    ratio: f32,
+    glyphs: &I,
+) -> Result<((u16, u16), Packing<'a, T>), Error>
+where
+    T: Clone,
+    I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    let gutter = pack_args.gutter;
+    let pack = |side| {
+        let (width, height) = with_aspect(side, ratio);
+        let (width, height) = (width.saturating_sub(gutter), height.saturating_sub(gutter));
+        pack_args.pack(width, height, font_size, measure, glyphs)
+    };
+    // the longer side of an atlas of this aspect ratio, with each layer
+    // large enough for the packer to fill its share of the area
+    let area = pack_args.covered_area(font_size, measure, glyphs)? / ESTIMATED_FILL;
+    let estimate = (area / f32::from(pack_args.layers) * ratio.max(1.0 / ratio)).sqrt()
+        + f32::from(pack_args.gutter);
+    let (side, result) = smallest_side(font_size, estimate, max_side, pack)?;
+    Ok((with_aspect(side, ratio), result))
+}
+
+/// Find the shortest atlas `width` texels wide, no taller than
+/// `max_height`, which fits every glyph
+pub fn smallest_height<'a, T, I>(
+    width: u16,
+    font_size: f32,
+    max_height: u16,
+    measure: impl Fn(f32, &GlyphRequest<'a, T>) -> Result<RasteredSize, Error>,
+    pack_args: PackArgs<'_>,
+    glyphs: &I,
+) -> Result<(u16, Packing<'a, T>), Error>
+where
+    T: Clone,
+    I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    let gutter = pack_args.gutter;
+    let pack = |height: u16| {
+        let (width, height) = (width.saturating_sub(gutter), height.saturating_sub(gutter));
+        pack_args.pack(width, height, font_size, &measure, glyphs)
     };
-    let mut upper_bound = u16::MAX;
-    while (too_small + 1) < upper_bound {
-        let check_size = too_small + ((upper_bound - too_small) / 2);
-        match crunch::Packer::with_items(glyphs.clone().map_while(&mut map_glyphs)).pack(
-            crunch::Rect {
-                x: 1,
-                y: 1,
-                w: check_size.into(),
-                h: check_size.into(),
-            },
-        ) {
-            Ok(res) => {
-                missing_glyph.get()?;
-                result = res;
-                upper_bound = check_size;
+    // the height at which each layer is large enough for the packer to fill
+    // its share of the area
+    let gutter = f32::from(gutter);
+    let area = pack_args.covered_area(font_size, &measure, glyphs)? / ESTIMATED_FILL;
+    let estimate =
+        area / f32::from(pack_args.layers) / (f32::from(width) - gutter).max(1.0) + gutter;
+    smallest_side(font_size, estimate, max_height, pack)
+}
+
+/// Search for the smallest side, no longer than `max_side`, of an atlas
+/// which `pack` fits every glyph into, starting around `estimate`
+fn smallest_side<'a, T>(
+    font_size: f32,
+    estimate: f32,
+    max_side: u16,
+    pack: impl Fn(u16) -> Result<Option<Packing<'a, T>>, Error>,
+) -> Result<(u16, Packing<'a, T>), Error> {
+    let smallest_fit = (font_size.floor().clamp(2.0, u16::MAX.into()) as u16) - 1;
+    let smallest_below = |upper_bound: u16| -> Result<Option<(u16, Packing<'a, T>)>, Error> {
+        // glyphs which don't fit at the largest size never will, so fail
+        // after a single pack rather than searching up to it
+        let Some(mut result) = pack(upper_bound)? else {
+            return Ok(None);
+        };
+        let mut too_small = smallest_fit;
+        let mut upper_bound = upper_bound;
+        if too_small + 1 >= upper_bound {
+            return Ok(Some((upper_bound, result)));
+        }
+        // start from a narrow range around the estimate
+        let guess = (estimate.ceil() as u16).clamp(too_small + 1, upper_bound);
+        if guess < upper_bound {
+            match pack(guess)? {
+                Some(res) => {
+                    (upper_bound, result) = (guess, res);
+                    loop {
+                        let next = (f32::from(upper_bound) / BRACKET_STEP) as u16;
+                        if next <= too_small {
+                            break;
+                        }
+                        match pack(next)? {
+                            Some(res) => (upper_bound, result) = (next, res),
+                            None => {
+                                too_small = next;
+                                break;
+                            }
+                        }
+                    }
+                }
+                None => {
+                    too_small = guess;
+                    loop {
+                        let next = (f32::from(too_small) * BRACKET_STEP).ceil() as u16;
+                        let next = next.max(too_small + 1);
+                        if next >= upper_bound {
+                            break;
+                        }
+                        match pack(next)? {
+                            Some(res) => {
+                                (upper_bound, result) = (next, res);
+                                break;
+                            }
+                            None => too_small = next,
+                        }
+                    }
+                }
             }
-            Err(_) => {
-                missing_glyph.get()?;
-                too_small = check_size;
+        }
+        while (too_small + 1) < upper_bound {
+            let check_size = too_small + ((upper_bound - too_small) / 2);
+            match pack(check_size)? {
+                Some(res) => {
+                    result = res;
+                    upper_bound = check_size;
+                }
+                None => too_small = check_size,
             }
         }
+        Ok(Some((upper_bound, result)))
+    };
+    // packers don't fit glyphs into every size larger than one they fit, so
+    // only search below the cap when the glyphs are found to need more room,
+    // to keep the same result as without it otherwise
+    let found = match smallest_below(u16::MAX)? {
+        Some((side, _)) if side > max_side => smallest_below(max_side)?,
+        found => found,
+    };
+    found.ok_or(Error::PackingAtlasFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn impossible_fails_after_one_pack() {
+        let packs = Cell::new(0);
+        let pack = |_side: u16| -> Result<Option<Packing<'static, ()>>, Error> {
+            packs.set(packs.get() + 1);
+            Ok(None)
+        };
+        let found = smallest_side(16.0, 200.0, 4096, pack);
+        assert!(matches!(found, Err(Error::PackingAtlasFailed)));
+        assert_eq!(packs.get(), 1);
+    }
+
+    #[test]
+    fn finds_smallest_side() {
+        let packs = Cell::new(0);
+        let pack = |side: u16| -> Result<Option<Packing<'static, ()>>, Error> {
+            packs.set(packs.get() + 1);
+            Ok((side >= 300).then(|| Packing {
+                glyphs: Vec::new(),
+                reserved: Vec::new(),
+            }))
+        };
+        let (side, _) = smallest_side(16.0, 200.0, 4096, pack).unwrap();
+        assert_eq!(side, 300);
+        assert!(packs.get() < 32);
     }
-    Ok((upper_bound, result))
 }
@@ -0,0 +1,132 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::{Face, GlyphId, RasterImageFormat};
+
+use crate::{
+    edt::Mask,
+    raster::{Buffer, RasterConfig, RasteredSize},
+    GlyphRequest, RasterBackend,
+};
+
+/// Texels with at least this alpha are considered inside the glyph
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// The oversampling used for bitmap glyphs when the exact backend is
+/// selected, since there is no outline to search
+const DEFAULT_OVERSAMPLE: u8 = 4;
+
+/// The coverage of a glyph's embedded bitmap, from the `CBDT` or `sbix`
+/// table
+pub struct Bitmap {
+    left: f32,
+    bottom: f32,
+    /// The size of a bitmap pixel as a ratio of the font size
+    scale: f32,
+    width: usize,
+    height: usize,
+    /// Whether each pixel is opaque, from the top row down
+    coverage: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Decode the largest embedded bitmap of a glyph
+    pub fn new(face: &Face<'_>, glyph_id: GlyphId) -> Option<Self> {
+        let image = face.glyph_raster_image(glyph_id, u16::MAX)?;
+        let RasterImageFormat::PNG = image.format;
+        let mut decoder = png::Decoder::new(image.data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().ok()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).ok()?;
+        let channels = info.color_type.samples();
+        let alpha = match info.color_type {
+            png::ColorType::Rgba | png::ColorType::GrayscaleAlpha => Some(channels - 1),
+            _ => None,
+        };
+        let width = info.width as usize;
+        let height = info.height as usize;
+        let coverage = (0..width * height)
+            .map(|i| {
+                let row = i / width;
+                let pixel = row * info.line_size + (i % width) * channels;
+                alpha.is_none_or(|alpha| data[pixel + alpha] >= ALPHA_THRESHOLD)
+            })
+            .collect();
+        let scale = 1.0 / f32::from(image.pixels_per_em.max(1));
+        Some(Self {
+            left: f32::from(image.x) * scale,
+            bottom: f32::from(image.y) * scale,
+            scale,
+            width,
+            height,
+            coverage,
+        })
+    }
+
+    /// Decode the largest embedded bitmap of a requested glyph
+    pub fn from_request<T>(request: &GlyphRequest<'_, T>) -> Option<Self> {
+        Self::new(request.face, request.glyph_id(request.face)?)
+    }
+
+    /// The left, right, bottom, and top of the image as a ratio of the font
+    /// size
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        (
+            self.left,
+            self.left + self.width as f32 * self.scale,
+            self.bottom,
+            self.bottom + self.height as f32 * self.scale,
+        )
+    }
+
+    /// Sample the bitmap at the center of each cell of a `width` by `height`
+    /// grid covering the rastered bounding box.
+    pub fn mask(&self, rastered_size: &RasteredSize, width: usize, height: usize) -> Mask {
+        let cell_w = (rastered_size.right - rastered_size.left) / (width as f32);
+        let cell_h = (rastered_size.top - rastered_size.bottom) / (height as f32);
+        let mut inside = vec![false; width * height];
+        for (i, cell) in inside.iter_mut().enumerate() {
+            let x = rastered_size.left + ((i % width) as f32 + 0.5) * cell_w;
+            let y = rastered_size.bottom + ((i / width) as f32 + 0.5) * cell_h;
+            let col = ((x - self.left) / self.scale).floor();
+            let row_from_bottom = ((y - self.bottom) / self.scale).floor();
+            if col < 0.0 || row_from_bottom < 0.0 {
+                continue;
+            }
+            let (col, row_from_bottom) = (col as usize, row_from_bottom as usize);
+            if col < self.width && row_from_bottom < self.height {
+                let row = self.height - 1 - row_from_bottom;
+                *cell = self.coverage[row * self.width + col];
+            }
+        }
+        Mask {
+            width,
+            height,
+            inside,
+        }
+    }
+
+    /// Derive the glyph's distance field from the bitmap with a distance
+    /// transform, regardless of the selected backend
+    pub fn raster(
+        &self,
+        buffer: &mut Buffer<'_>,
+        config: RasterConfig,
+        rastered_size: &RasteredSize,
+        rect: &crunch::Rect,
+        rotate: bool,
+    ) {
+        let oversample = match config.backend {
+            RasterBackend::Approximate { oversample } => oversample.max(1),
+            RasterBackend::Exact => DEFAULT_OVERSAMPLE,
+        };
+        let oversample = usize::from(oversample);
+        let mask = self.mask(
+            rastered_size,
+            usize::from(rastered_size.pixel_width) * oversample,
+            usize::from(rastered_size.pixel_height) * oversample,
+        );
+        crate::edt::raster_mask(buffer, config, &mask, rastered_size, rect, rotate);
+    }
+}
@@ -0,0 +1,87 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::collections::VecDeque;
+
+use crate::reserved;
+
+/// Find, for each texel of a `width` by `height` image, the index of the
+/// nearest texel inside one of `rects`, measured in steps to any of the 8
+/// neighbors.  Texels inside a rect are their own nearest; ties go to the
+/// glyph packed first.  Returns an empty list if there are no such texels.
+pub fn nearest_glyph_texels<'r>(
+    width: u16,
+    height: u16,
+    rects: impl IntoIterator<Item = &'r crunch::Rect>,
+) -> Vec<usize> {
+    let (width, height) = (usize::from(width), usize::from(height));
+    let mut nearest = vec![usize::MAX; width * height];
+    let mut queue = VecDeque::new();
+    for rect in rects {
+        for y in rect.y..rect.y + rect.h {
+            for x in rect.x..rect.x + rect.w {
+                let i = y * width + x;
+                nearest[i] = i;
+                queue.push_back(i);
+            }
+        }
+    }
+    if queue.is_empty() {
+        return Vec::new();
+    }
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = (i % width, i / width);
+        for ny in y.saturating_sub(1)..(y + 2).min(height) {
+            for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                let n = ny * width + nx;
+                if nearest[n] == usize::MAX {
+                    nearest[n] = nearest[i];
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+    nearest
+}
+
+/// Copy each texel's nearest glyph texel into it, for an image with
+/// `channels` bytes per texel
+pub fn bleed(data: &mut [u8], channels: u8, nearest: &[usize]) {
+    let channels = usize::from(channels);
+    for (i, &source) in nearest.iter().enumerate() {
+        data.copy_within(source * channels..(source + 1) * channels, i * channels);
+    }
+}
+
+/// Fill the space between the glyphs of each `width` by `height` layer of
+/// an atlas with their edges, leaving the reserved rects clear
+pub fn bleed_atlas(
+    width: u16,
+    height: u16,
+    channels: u8,
+    data: &mut [u8],
+    glyphs: &[crunch::Rect],
+    reserved: &[crunch::Rect],
+) {
+    let layer_len = usize::from(width) * usize::from(height) * usize::from(channels);
+    let layer_height = usize::from(height);
+    for (layer, data) in data.chunks_exact_mut(layer_len).enumerate() {
+        let bottom = layer * layer_height;
+        let rects: Vec<crunch::Rect> = glyphs
+            .iter()
+            .filter(|rect| (bottom..bottom + layer_height).contains(&rect.y))
+            .map(|&rect| crunch::Rect {
+                y: rect.y - bottom,
+                ..rect
+            })
+            .collect();
+        let nearest = nearest_glyph_texels(width, height, &rects);
+        if nearest.is_empty() {
+            // a layer whose glyphs were all evicted is cleared
+            data.fill(0);
+        } else {
+            bleed(data, channels, &nearest);
+        }
+    }
+    reserved::clear(data, width, channels, reserved);
+}
@@ -0,0 +1,53 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::raster::{self, Buffer, RasterConfig, RasteredSize, Segments};
+
+/// Shrink the box a glyph will be rendered into by the rows and columns
+/// along its edges whose texels are all at the far outside value, found by
+/// rendering the glyph.  The texels which remain are sampled at the same
+/// points as before, so they render the same, up to rounding.
+pub fn trim_empty_borders(
+    config: RasterConfig,
+    segments: &Segments,
+    size: RasteredSize,
+) -> RasteredSize {
+    let (width, height) = (
+        usize::from(size.pixel_width),
+        usize::from(size.pixel_height),
+    );
+    if width == 0 || height == 0 || segments.edge_count() == 0 {
+        return size;
+    }
+    let mut data = vec![0; width * height];
+    let rect = crunch::Rect {
+        x: 0,
+        y: 0,
+        w: width,
+        h: height,
+    };
+    let mut buffer = Buffer {
+        data: &mut data,
+        width: size.pixel_width,
+    };
+    raster::raster_segments(&mut buffer, config, segments, &size, &rect, false);
+    let row_empty = |y: usize| data[y * width..][..width].iter().all(|&value| value == 0);
+    let column_empty = |x: usize| (0..height).all(|y| data[y * width + x] == 0);
+    let Some(bottom) = (0..height).find(|&y| !row_empty(y)) else {
+        // keep glyphs which render nothing at all as they are
+        return size;
+    };
+    let top = (0..height).rev().find(|&y| !row_empty(y)).unwrap_or(bottom);
+    let left = (0..width).find(|&x| !column_empty(x)).unwrap_or(0);
+    let right = (0..width).rev().find(|&x| !column_empty(x)).unwrap_or(left);
+    let cell_w = (size.right - size.left) / (width as f32);
+    let cell_h = (size.top - size.bottom) / (height as f32);
+    RasteredSize {
+        pixel_width: (right + 1 - left) as u16,
+        pixel_height: (top + 1 - bottom) as u16,
+        left: size.left + left as f32 * cell_w,
+        right: size.right - (width - 1 - right) as f32 * cell_w,
+        bottom: size.bottom + bottom as f32 * cell_h,
+        top: size.top - (height - 1 - top) as f32 * cell_h,
+    }
+}
@@ -0,0 +1,45 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// Which optional features of the crate were compiled in, see
+/// [`capabilities`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Distance fields can be derived from embedded bitmaps, for glyphs
+    /// without outlines (the `bitmap` feature)
+    pub bitmap: bool,
+
+    /// The [`effects`](crate::effects) module is available (the `effects`
+    /// feature)
+    pub effects: bool,
+
+    /// The [`fuzzing`](crate::fuzzing) module is available (the `fuzzing`
+    /// feature)
+    pub fuzzing: bool,
+
+    /// Distance fields can be computed on the GPU (the `gpu` feature)
+    pub gpu: bool,
+
+    /// The [`reference`](crate::reference) module is available (the
+    /// `reference` feature)
+    pub reference: bool,
+
+    /// Glyphs can be rendered from the `SVG ` table when they have no
+    /// outline (the `svg` feature)
+    pub svg: bool,
+}
+
+/// Report which optional features were compiled in, so applications which
+/// load the crate through a plugin or another layer of indirection can adapt
+/// to it at run time.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        bitmap: cfg!(feature = "bitmap"),
+        effects: cfg!(feature = "effects"),
+        fuzzing: cfg!(feature = "fuzzing"),
+        gpu: cfg!(feature = "gpu"),
+        reference: cfg!(feature = "reference"),
+        svg: cfg!(feature = "svg"),
+    }
+}
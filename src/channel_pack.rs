@@ -0,0 +1,33 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// The pages of glyphs packed into each texel, one in each RGBA channel,
+/// see [`FontAssetBuilder::with_channel_packing`](crate::FontAssetBuilder::with_channel_packing)
+pub const PAGES_PER_TEXEL: u16 = 4;
+
+/// Interleave each group of four single channel pages of `page_len` texels
+/// into one layer of RGBA texels, the first page in red
+pub fn interleave(data: &[u8], page_len: usize) -> Vec<u8> {
+    let per_texel = usize::from(PAGES_PER_TEXEL);
+    let mut out = vec![0; data.len()];
+    for (page, texels) in data.chunks_exact(page_len).enumerate() {
+        let layer = &mut out[(page / per_texel) * page_len * per_texel..][..page_len * per_texel];
+        for (texel, &value) in layer.chunks_exact_mut(per_texel).zip(texels) {
+            texel[page % per_texel] = value;
+        }
+    }
+    out
+}
+
+/// Split layers of RGBA texels into single channel pages of `page_len`
+/// texels, undoing [`interleave`]
+pub fn deinterleave(data: &[u8], page_len: usize) -> Vec<u8> {
+    let per_texel = usize::from(PAGES_PER_TEXEL);
+    let mut out = Vec::with_capacity(data.len());
+    for layer in data.chunks_exact(page_len * per_texel) {
+        for channel in 0..per_texel {
+            out.extend(layer.chunks_exact(per_texel).map(|texel| texel[channel]));
+        }
+    }
+    out
+}
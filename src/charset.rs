@@ -0,0 +1,220 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::ops::RangeInclusive;
+
+/// The codepoints UTF-16 uses for surrogate pairs, which are not chars
+const SURROGATES: (u32, u32) = (0xd800, 0xdfff);
+
+/// A set of chars, such as the characters an atlas should hold.  Sets are
+/// built from ranges, chars, and named presets, and combined with
+/// [`union`](Self::union) and [`difference`](Self::difference).
+/// Iterating a set gives its chars in ascending order, so it can be passed
+/// to [`build_fallback`](crate::FontAssetBuilder::build_fallback), or
+/// mapped to [`GlyphRequest`](crate::GlyphRequest)s for
+/// [`build`](crate::FontAssetBuilder::build).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Charset {
+    /// Ranges of codepoints, inclusive, in ascending order, with none
+    /// overlapping or adjacent, and none including surrogates
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Charset {
+    /// The names accepted by [`named`](Self::named)
+    pub const NAMES: [&'static str; 5] = ["hexdigits", "ascii", "latin1", "latin1-french", "cjk"];
+
+    /// An empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The chars in a range
+    pub fn range(range: RangeInclusive<char>) -> Self {
+        let (start, end) = (u32::from(*range.start()), u32::from(*range.end()));
+        let (surrogates_start, surrogates_end) = SURROGATES;
+        let ranges = if start < surrogates_start && end > surrogates_end {
+            vec![(start, surrogates_start - 1), (surrogates_end + 1, end)]
+        } else {
+            vec![(start, end)]
+        };
+        Self::from_ranges(ranges)
+    }
+
+    /// The chars for displaying hexadecimal values: digits, letters `a` to
+    /// `f` in either case, and `x` in either case
+    pub fn hexdigits() -> Self {
+        "0123456789abcdefABCDEFxX".chars().collect()
+    }
+
+    /// The printable ascii chars, not including the space
+    pub fn ascii() -> Self {
+        Self::range('!'..='~')
+    }
+
+    /// The printable ISO-8859-1 ("Latin 1") chars, not including the spaces
+    pub fn latin1() -> Self {
+        Self::ascii().union(&Self::range('\u{a1}'..='\u{ff}'))
+    }
+
+    /// The [Latin 1](Self::latin1) chars, with the three more that French
+    /// needs: `Œ`, `œ`, and `Ÿ`
+    pub fn latin1_french() -> Self {
+        Self::latin1().union(&['\u{0152}', '\u{0153}', '\u{0178}'].into_iter().collect())
+    }
+
+    /// The chars in the CJK Unified Ideographs block, the common Chinese,
+    /// Japanese, and Korean ideographs.  This is over 20,000 chars, so most
+    /// atlases will want a smaller subset.
+    pub fn cjk() -> Self {
+        Self::range('\u{4e00}'..='\u{9fff}')
+    }
+
+    /// A preset by name, one of [`NAMES`](Self::NAMES), for tools which
+    /// take a charset as an argument.  Returns `None` for other names.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "hexdigits" => Some(Self::hexdigits()),
+            "ascii" => Some(Self::ascii()),
+            "latin1" => Some(Self::latin1()),
+            "latin1-french" => Some(Self::latin1_french()),
+            "cjk" => Some(Self::cjk()),
+            _ => None,
+        }
+    }
+
+    /// The chars in either set
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.ranges.iter().chain(&other.ranges).copied().collect())
+    }
+
+    /// The chars in this set but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        for &(start, end) in &self.ranges {
+            let mut start = start;
+            for &(cut_start, cut_end) in &other.ranges {
+                if cut_end < start {
+                    continue;
+                }
+                if cut_start > end {
+                    break;
+                }
+                if cut_start > start {
+                    ranges.push((start, cut_start - 1));
+                }
+                start = cut_end + 1;
+                if start > end {
+                    break;
+                }
+            }
+            if start <= end {
+                ranges.push((start, end));
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Whether the set holds a char
+    pub fn contains(&self, ch: char) -> bool {
+        let ch = u32::from(ch);
+        let index = self.ranges.partition_point(|&(_, end)| end < ch);
+        self.ranges
+            .get(index)
+            .is_some_and(|&(start, _)| start <= ch)
+    }
+
+    /// The number of chars in the set
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| (end - start) as usize + 1)
+            .sum()
+    }
+
+    /// Whether the set holds no chars
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The chars in the set, in ascending order
+    pub fn iter(&self) -> Chars {
+        self.clone().into_iter()
+    }
+
+    /// Sort and merge ranges which overlap or touch
+    fn from_ranges(mut ranges: Vec<(u32, u32)>) -> Self {
+        ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= *last_end + 1 => *last_end = end.max(*last_end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Self { ranges: merged }
+    }
+}
+
+impl From<RangeInclusive<char>> for Charset {
+    fn from(range: RangeInclusive<char>) -> Self {
+        Self::range(range)
+    }
+}
+
+impl FromIterator<char> for Charset {
+    fn from_iter<I: IntoIterator<Item = char>>(chars: I) -> Self {
+        Self::from_ranges(
+            chars
+                .into_iter()
+                .map(|ch| (u32::from(ch), u32::from(ch)))
+                .collect(),
+        )
+    }
+}
+
+impl IntoIterator for Charset {
+    type Item = char;
+    type IntoIter = Chars;
+
+    fn into_iter(self) -> Chars {
+        Chars {
+            ranges: self.ranges.into_iter(),
+            current: None,
+        }
+    }
+}
+
+impl IntoIterator for &Charset {
+    type Item = char;
+    type IntoIter = Chars;
+
+    fn into_iter(self) -> Chars {
+        self.iter()
+    }
+}
+
+/// The chars of a [`Charset`], in ascending order
+#[derive(Clone, Debug)]
+pub struct Chars {
+    ranges: std::vec::IntoIter<(u32, u32)>,
+    /// The rest of the range being iterated
+    current: Option<(u32, u32)>,
+}
+
+impl Iterator for Chars {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some((start, end)) = &mut self.current {
+                if *start <= *end {
+                    let ch = char::from_u32(*start);
+                    *start += 1;
+                    return ch;
+                }
+            }
+            self.current = Some(self.ranges.next()?);
+        }
+    }
+}
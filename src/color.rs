@@ -0,0 +1,67 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::{Face, GlyphId, Tag};
+
+use crate::GlyphKey;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Look up the palette index of the first layer of a color glyph
+fn first_layer_palette_index(colr: &[u8], glyph_id: GlyphId) -> Option<u16> {
+    let num_base_glyphs = read_u16(colr, 2)?;
+    let base_glyphs_offset = read_u32(colr, 4)? as usize;
+    let layers_offset = read_u32(colr, 8)? as usize;
+    let num_layers = read_u16(colr, 12)?;
+    let (mut low, mut high) = (0, usize::from(num_base_glyphs));
+    while low < high {
+        let mid = (low + high) / 2;
+        let record = base_glyphs_offset + mid * 6;
+        let record_glyph = read_u16(colr, record)?;
+        match record_glyph.cmp(&glyph_id.0) {
+            std::cmp::Ordering::Less => low = mid + 1,
+            std::cmp::Ordering::Greater => high = mid,
+            std::cmp::Ordering::Equal => {
+                let first_layer = read_u16(colr, record + 2)?;
+                if read_u16(colr, record + 4)? == 0 || first_layer >= num_layers {
+                    return None;
+                }
+                let layer = layers_offset + usize::from(first_layer) * 4;
+                return read_u16(colr, layer + 2);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the color the font assigns to a glyph, for use with
+/// [`GlyphRequest::with_color`](crate::GlyphRequest::with_color).
+///
+/// For glyphs in the font's `COLR` table, this is the color of the first
+/// layer in the given `CPAL` palette, as non-premultiplied sRGB RGBA.  Returns
+/// `None` if the glyph has no color, or if the layer uses the text foreground
+/// color.
+pub fn palette_color(face: &Face<'_>, glyph: impl Into<GlyphKey>, palette: u16) -> Option<[u8; 4]> {
+    let glyph_id = glyph.into().glyph_id(face)?;
+    let colr = face.raw_face().table(Tag::from_bytes(b"COLR"))?;
+    let cpal = face.raw_face().table(Tag::from_bytes(b"CPAL"))?;
+    let entry = first_layer_palette_index(colr, glyph_id)?;
+    let num_entries = read_u16(cpal, 2)?;
+    let num_palettes = read_u16(cpal, 4)?;
+    if entry >= num_entries || palette >= num_palettes {
+        return None;
+    }
+    let records_offset = read_u32(cpal, 8)? as usize;
+    let first_record = read_u16(cpal, 12 + usize::from(palette) * 2)?;
+    let record = records_offset + (usize::from(first_record) + usize::from(entry)) * 4;
+    let [blue, green, red, alpha]: [u8; 4] = cpal.get(record..record + 4)?.try_into().ok()?;
+    Some([red, green, blue, alpha])
+}
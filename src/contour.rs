@@ -0,0 +1,119 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::OutlineBuilder;
+
+use crate::edge::{CubicCurve, Edge, Line, QuadCurve};
+
+/// The direction a contour winds, with the y axis pointing up as in font
+/// units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Outer contours of `glyf` outlines wind this way, and holes the other
+    Clockwise,
+    /// Outer contours of `CFF` outlines wind this way, and holes the other
+    CounterClockwise,
+}
+
+impl Orientation {
+    /// The orientation of a contour with a signed area, as returned by
+    /// [`ContourAreas::areas`].  Returns `None` for a contour with no area.
+    pub fn of_area(area: f32) -> Option<Self> {
+        if area > 0.0 {
+            Some(Self::CounterClockwise)
+        } else if area < 0.0 {
+            Some(Self::Clockwise)
+        } else {
+            None
+        }
+    }
+}
+
+/// Measure the signed area of each contour of an outline, by passing it to
+/// [`Face::outline_glyph`](ttf_parser::Face::outline_glyph) or calling the
+/// [`OutlineBuilder`] methods directly.
+///
+/// Areas are exact for lines and curves, in the units of the outline, and
+/// positive for contours which wind counter-clockwise.  Contours are closed
+/// with a line if they end away from where they started, whether or not
+/// [`close`](OutlineBuilder::close) is called.
+#[derive(Clone, Debug, Default)]
+pub struct ContourAreas {
+    areas: Vec<f32>,
+    /// The area of the contour being built, if one has been started
+    current: Option<f32>,
+    start: (f32, f32),
+    cursor: (f32, f32),
+}
+
+impl ContourAreas {
+    /// Start measuring an outline with no contours
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The signed area of each contour, in the order they were drawn
+    pub fn areas(&self) -> &[f32] {
+        &self.areas
+    }
+
+    /// The sum of the signed areas of every contour.  The outer contours of
+    /// an outline normally dominate, so its sign gives their orientation.
+    pub fn total_area(&self) -> f32 {
+        self.areas.iter().sum()
+    }
+
+    /// Whether a contour is a hole, winding against the outline as a whole.
+    /// This is how the renderer decides which way contours face, and it
+    /// holds for outlines which follow either the `glyf` or `CFF` convention.
+    pub fn is_hole(&self, contour: usize) -> bool {
+        match (
+            Orientation::of_area(self.areas[contour]),
+            Orientation::of_area(self.total_area()),
+        ) {
+            (Some(contour), Some(outline)) => contour != outline,
+            _ => false,
+        }
+    }
+
+    fn add(&mut self, area: f32, end: (f32, f32)) {
+        *self.current.get_or_insert(0.0) += area;
+        self.cursor = end;
+    }
+
+    fn finish(&mut self) {
+        if self.cursor != self.start {
+            self.add(Line::new(self.cursor, self.start).area(), self.start);
+        }
+        if let Some(area) = self.current.take() {
+            self.areas.push(area);
+        }
+    }
+}
+
+impl OutlineBuilder for ContourAreas {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.finish();
+        self.start = (x, y);
+        self.cursor = (x, y);
+        self.current = Some(0.0);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.add(Line::new(self.cursor, (x, y)).area(), (x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let curve = QuadCurve::new(self.cursor, (x1, y1), (x, y));
+        self.add(curve.area(), (x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let curve = CubicCurve::new(self.cursor, (x1, y1), (x2, y2), (x, y));
+        self.add(curve.area(), (x, y));
+    }
+
+    fn close(&mut self) {
+        self.finish();
+    }
+}
@@ -0,0 +1,151 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::collections::HashMap;
+
+use crate::{
+    EmptyGlyphsPolicy, Error, FaceHandle, FontAssetBuilder, Glyph, GlyphKey, GlyphRequest,
+    SdfFontAsset, SkipReason,
+};
+
+/// What tells glyphs of the atlas apart: the face, key, color, and the bits
+/// of the transform, since floats can't be hashed
+type CacheKey = (FaceHandle, GlyphKey, Option<[u8; 4]>, Option<[u32; 6]>);
+
+/// A glyph cache: an atlas of a fixed size which renders glyphs the first
+/// time they are asked for, and makes room for new glyphs by evicting the
+/// ones used least recently.
+///
+/// Glyphs may move when others are evicted and rendered, so look them up
+/// with [`glyph`](Self::glyph) each time they are drawn, and upload the
+/// image from [`asset`](Self::asset) again after any glyph is added.
+#[derive(Clone, Debug)]
+pub struct DynamicSdfAtlas<T> {
+    asset: SdfFontAsset<T>,
+    /// When each glyph in the asset's metadata was last asked for
+    last_used: Vec<u64>,
+    /// What each glyph in the asset's metadata was asked for by
+    keys: Vec<CacheKey>,
+    /// The index in the asset's metadata of each glyph
+    index: HashMap<CacheKey, usize>,
+    clock: u64,
+}
+
+impl<T: Clone> DynamicSdfAtlas<T> {
+    /// Create an empty `width` by `height` atlas, which will render glyphs
+    /// at `font_size` with the settings of `builder`.  The size of the atlas
+    /// and font set by the builder are ignored, as is its
+    /// [`EmptyGlyphsPolicy`], since the atlas starts with no glyphs.
+    pub fn new(
+        builder: FontAssetBuilder,
+        width: u16,
+        height: u16,
+        font_size: f32,
+    ) -> Result<Self, Error> {
+        let no_glyphs = std::iter::empty::<GlyphRequest<'_, T>>();
+        let builder = FontAssetBuilder {
+            empty: EmptyGlyphsPolicy::Minimal,
+            ..builder
+        };
+        let asset = builder
+            .plan_with_layout(width, height, font_size, no_glyphs, |_| Some(Vec::new()))?
+            .raster()?;
+        Ok(Self {
+            asset,
+            last_used: Vec::new(),
+            keys: Vec::new(),
+            index: HashMap::new(),
+            clock: 0,
+        })
+    }
+
+    /// The atlas, with every glyph currently rendered in it
+    pub fn asset(&self) -> &SdfFontAsset<T> {
+        &self.asset
+    }
+
+    /// Find a glyph in the atlas, rendering it if it isn't there.  If there
+    /// is no room for it, the glyphs used least recently are evicted until
+    /// it fits.  Fails with [`Error::PackingAtlasFailed`] if it doesn't fit
+    /// even in the empty atlas, with [`Error::MissingGlyph`] if the font has
+    /// no such glyph, unless the builder renders `.notdef` instead, and with
+    /// [`Error::FilteredGlyph`] if the builder's filter leaves it out.
+    pub fn glyph<'a>(&mut self, request: GlyphRequest<'a, T>) -> Result<&Glyph<T>, Error> {
+        self.clock += 1;
+        let found = self
+            .asset
+            .face_handle(request.face)
+            .and_then(|handle| self.index.get(&cache_key(handle, &request)));
+        if let Some(&index) = found {
+            self.last_used[index] = self.clock;
+            return Ok(&self.asset.metadata[index]);
+        }
+        let key = request.key;
+        let mut evicted = false;
+        loop {
+            match self.asset.append(std::iter::once(request.clone())) {
+                Ok([]) => {
+                    // skipped, which is only worth reporting once
+                    let reason = self.asset.skipped.pop().map(|skipped| skipped.reason);
+                    return Err(match reason {
+                        Some(SkipReason::Filtered) => Error::FilteredGlyph(key),
+                        Some(SkipReason::DidNotFit) => Error::PackingAtlasFailed,
+                        _ => Error::MissingGlyph(key),
+                    });
+                }
+                Ok(_) => break,
+                Err(Error::PackingAtlasFailed) if !self.last_used.is_empty() => {
+                    self.evict_least_recent();
+                    evicted = true;
+                }
+                Err(err) => {
+                    if evicted {
+                        self.asset.refinish();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        self.last_used.push(self.clock);
+        let last = self.asset.metadata.len() - 1;
+        // the face has a handle now, even if it was new to the atlas
+        let handle = self.asset.metadata[last].face;
+        let cache_key = cache_key(handle, &request);
+        self.index.insert(cache_key, last);
+        self.keys.push(cache_key);
+        Ok(&self.asset.metadata[last])
+    }
+
+    /// Remove every glyph from the atlas
+    pub fn clear(&mut self) {
+        while !self.last_used.is_empty() {
+            self.evict_least_recent();
+        }
+        self.asset.refinish();
+    }
+
+    fn evict_least_recent(&mut self) {
+        let Some((index, _)) = self
+            .last_used
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &last_used)| last_used)
+        else {
+            return;
+        };
+        self.last_used.swap_remove(index);
+        self.index.remove(&self.keys.swap_remove(index));
+        // the last glyph takes the place of the evicted one
+        if let Some(moved) = self.keys.get(index) {
+            self.index.insert(*moved, index);
+        }
+        self.asset.evict(index);
+    }
+}
+
+fn cache_key<T>(face: FaceHandle, request: &GlyphRequest<'_, T>) -> CacheKey {
+    let transform = request
+        .transform
+        .map(|transform| transform.map(f32::to_bits));
+    (face, request.key, request.color, transform)
+}
@@ -5,6 +5,17 @@ use crate::math::Polynomial;
 
 const NEWTONS_ITERS: u8 = 4;
 
+/// The guesses Newton's method starts from along a curve
+const NEWTONS_SEEDS: u8 = 5;
+
+/// The iterations and guesses used, in `f64`, for glyphs whose distances
+/// came out broken the first time, see [`Segment::careful_nearest_t`]
+const CAREFUL_ITERS: u8 = 16;
+const CAREFUL_SEEDS: u8 = 33;
+
+/// The most quadratic curves [`cubic_to_quads`] will split a cubic into
+const MAX_QUADS: usize = 64;
+
 pub enum Segment {
     LoopPoint(f32, f32),
     Line(Line),
@@ -31,6 +42,18 @@ impl Segment {
         }
     }
 
+    /// Find the nearest point like [`nearest_t`](Self::nearest_t), but with
+    /// many more guesses and iterations, in `f64`, for curves where the
+    /// usual search goes wrong
+    pub fn careful_nearest_t(&self, point: (f32, f32)) -> f32 {
+        match self {
+            Self::LoopPoint(_, _) => unreachable!(),
+            Self::Line(line) => line.nearest_t(point),
+            Self::Quad(quad) => careful_search(&quad.x_poly, &quad.y_poly, point),
+            Self::Cubic(curve) => careful_search(&curve.x_poly, &curve.y_poly, point),
+        }
+    }
+
     pub fn direction(&self, t: f32) -> (f32, f32) {
         match self {
             Self::LoopPoint(x, y) => (*x, *y),
@@ -48,6 +71,15 @@ impl Segment {
             Self::Cubic(curve) => curve.bbox(),
         }
     }
+
+    pub fn area(&self) -> f32 {
+        match self {
+            Self::LoopPoint(_, _) => 0.0,
+            Self::Line(line) => line.area(),
+            Self::Quad(quad) => quad.area(),
+            Self::Cubic(curve) => curve.area(),
+        }
+    }
 }
 
 impl From<Line> for Segment {
@@ -75,11 +107,99 @@ pub struct EdgeBoundingBox {
     pub bottom: f32,
 }
 
+impl EdgeBoundingBox {
+    /// The squared distance from a point to the nearest point in the box.
+    /// Unlike `f32::clamp`, this does not panic if the box is degenerate.
+    pub fn dist2(&self, (x, y): (f32, f32)) -> f32 {
+        let near_x = x.max(self.left).min(self.right);
+        let near_y = y.max(self.bottom).min(self.top);
+        (near_x - x).powi(2) + (near_y - y).powi(2)
+    }
+}
+
 pub trait Edge {
     fn point(&self, t: f32) -> (f32, f32);
     fn nearest_t(&self, point: (f32, f32)) -> f32;
     fn direction(&self, t: f32) -> (f32, f32);
     fn bbox(&self) -> EdgeBoundingBox;
+
+    /// The signed area swept by a line from the origin to a point moving
+    /// along the edge, positive when it turns counter-clockwise.  The sum
+    /// over the edges of a closed contour is the area of the contour.
+    fn area(&self) -> f32;
+}
+
+/// Find the nearest point to `point` on a parametric curve from t = 0 to 1
+/// by Newton's method, like the curves' usual search, but from
+/// [`CAREFUL_SEEDS`] guesses and evaluated in `f64`, so rounding can't
+/// send it astray where the distance barely changes along the curve
+fn careful_search<const N: usize>(
+    x_poly: &Polynomial<N>,
+    y_poly: &Polynomial<N>,
+    point: (f32, f32),
+) -> f32 {
+    let (px, py) = (f64::from(point.0), f64::from(point.1));
+    // the squared distance at t, and its first two derivatives, halved
+    let distance_sq = |t: f64| {
+        let (x, dx, ddx) = value_f64(&x_poly.coeffs, t);
+        let (y, dy, ddy) = value_f64(&y_poly.coeffs, t);
+        let (x, y) = (x - px, y - py);
+        (
+            x * x + y * y,
+            x * dx + y * dy,
+            dx * dx + x * ddx + dy * dy + y * ddy,
+        )
+    };
+    let (start, end) = (distance_sq(0.0).0, distance_sq(1.0).0);
+    let (mut best_dist_sq, mut best_t) = if start < end {
+        (start, 0.0)
+    } else {
+        (end, 1.0)
+    };
+    for seed in 0..CAREFUL_SEEDS {
+        let mut t = f64::from(seed) / f64::from(CAREFUL_SEEDS - 1);
+        for _ in 0..CAREFUL_ITERS {
+            let (_, slope, curvature) = distance_sq(t);
+            t -= slope / curvature;
+        }
+        // guesses which diverged are not a number, and so not in range
+        if (0.0..=1.0).contains(&t) {
+            let dist_sq = distance_sq(t).0;
+            if dist_sq < best_dist_sq {
+                best_dist_sq = dist_sq;
+                best_t = t;
+            }
+        }
+    }
+    best_t as f32
+}
+
+/// The value of a polynomial, with coefficients ordered from the highest
+/// power, and of its first two derivatives, at `t` in `f64`
+fn value_f64(coeffs: &[f32], t: f64) -> (f64, f64, f64) {
+    let (mut value, mut slope, mut half_curvature) = (0.0, 0.0, 0.0);
+    for &coeff in coeffs {
+        half_curvature = half_curvature * t + slope;
+        slope = slope * t + value;
+        value = value * t + f64::from(coeff);
+    }
+    (value, slope, 2.0 * half_curvature)
+}
+
+/// The area swept by a parametric curve from t = 0 to 1, by Green's
+/// theorem: half the integral of `x dy - y dx`
+fn swept_area<const N: usize>(x_poly: &Polynomial<N>, y_poly: &Polynomial<N>) -> f32 {
+    let mut twice_area = 0.0;
+    // coefficients are ordered from the highest power
+    for (i, (x_i, y_i)) in x_poly.coeffs.iter().zip(&y_poly.coeffs).enumerate() {
+        for (j, (x_j, y_j)) in x_poly.coeffs.iter().zip(&y_poly.coeffs).enumerate() {
+            let (power_i, power_j) = ((N - 1 - i) as f32, (N - 1 - j) as f32);
+            if power_j > 0.0 {
+                twice_area += (x_i * y_j - y_i * x_j) * power_j / (power_i + power_j);
+            }
+        }
+    }
+    twice_area / 2.0
 }
 
 pub struct Line {
@@ -133,6 +253,10 @@ impl Edge for Line {
             bottom: self.start.1.min(self.end.1),
         }
     }
+
+    fn area(&self) -> f32 {
+        (self.start.0 * self.end.1 - self.end.0 * self.start.1) / 2.0
+    }
 }
 
 pub struct QuadCurve {
@@ -158,16 +282,10 @@ impl QuadCurve {
         };
         Self { x_poly, y_poly }
     }
-}
-
-impl Edge for QuadCurve {
-    fn point(&self, t: f32) -> (f32, f32) {
-        let x = self.x_poly.value(t);
-        let y = self.y_poly.value(t);
-        (x, y)
-    }
 
-    fn nearest_t(&self, point: (f32, f32)) -> f32 {
+    /// Find the nearest point by Newton's method, starting from `seeds`
+    /// guesses spread evenly along the curve and its ends
+    fn search_nearest_t(&self, point: (f32, f32), seeds: u8, iters: u8) -> f32 {
         let x_point = Polynomial {
             coeffs: [0.0, 0.0, point.0],
         };
@@ -183,9 +301,10 @@ impl Edge for QuadCurve {
         } else {
             (end_dist_sq, 1.0)
         };
+        let step = f32::from(seeds - 1).recip();
         let mut test = 0.0;
         while test <= 1.0 {
-            let root = dd.newtons_root(test, NEWTONS_ITERS);
+            let root = dd.newtons_root(test, iters);
             if (0.0..=1.0).contains(&root) {
                 let dist_sq = distance_sq.value(root);
                 if dist_sq < best_dist_sq {
@@ -193,10 +312,22 @@ impl Edge for QuadCurve {
                     best_t = root;
                 }
             }
-            test += 0.25;
+            test += step;
         }
         best_t
     }
+}
+
+impl Edge for QuadCurve {
+    fn point(&self, t: f32) -> (f32, f32) {
+        let x = self.x_poly.value(t);
+        let y = self.y_poly.value(t);
+        (x, y)
+    }
+
+    fn nearest_t(&self, point: (f32, f32)) -> f32 {
+        self.search_nearest_t(point, NEWTONS_SEEDS, NEWTONS_ITERS)
+    }
 
     fn direction(&self, t: f32) -> (f32, f32) {
         let x = self.x_poly.derivative().value(t);
@@ -204,6 +335,10 @@ impl Edge for QuadCurve {
         (x, y)
     }
 
+    fn area(&self) -> f32 {
+        swept_area(&self.x_poly, &self.y_poly)
+    }
+
     fn bbox(&self) -> EdgeBoundingBox {
         let tx = self.x_poly.derivative().root().clamp(0.0, 1.0);
         let ty = self.y_poly.derivative().root().clamp(0.0, 1.0);
@@ -252,16 +387,10 @@ impl CubicCurve {
         };
         Self { x_poly, y_poly }
     }
-}
 
-impl Edge for CubicCurve {
-    fn point(&self, t: f32) -> (f32, f32) {
-        let x = self.x_poly.value(t);
-        let y = self.y_poly.value(t);
-        (x, y)
-    }
-
-    fn nearest_t(&self, point: (f32, f32)) -> f32 {
+    /// Find the nearest point by Newton's method, starting from `seeds`
+    /// guesses spread evenly along the curve and its ends
+    fn search_nearest_t(&self, point: (f32, f32), seeds: u8, iters: u8) -> f32 {
         let x_point = Polynomial {
             coeffs: [0.0, 0.0, 0.0, point.0],
         };
@@ -277,9 +406,10 @@ impl Edge for CubicCurve {
         } else {
             (end_dist_sq, 1.0)
         };
+        let step = f32::from(seeds - 1).recip();
         let mut test = 0.0;
         while test <= 1.0 {
-            let root = dd.newtons_root(test, NEWTONS_ITERS);
+            let root = dd.newtons_root(test, iters);
             if (0.0..=1.0).contains(&root) {
                 let dist_sq = distance_sq.value(root);
                 if dist_sq < best_dist_sq {
@@ -287,10 +417,22 @@ impl Edge for CubicCurve {
                     best_t = root;
                 }
             }
-            test += 0.25;
+            test += step;
         }
         best_t
     }
+}
+
+impl Edge for CubicCurve {
+    fn point(&self, t: f32) -> (f32, f32) {
+        let x = self.x_poly.value(t);
+        let y = self.y_poly.value(t);
+        (x, y)
+    }
+
+    fn nearest_t(&self, point: (f32, f32)) -> f32 {
+        self.search_nearest_t(point, NEWTONS_SEEDS, NEWTONS_ITERS)
+    }
 
     fn direction(&self, t: f32) -> (f32, f32) {
         let x = self.x_poly.derivative().value(t);
@@ -298,6 +440,10 @@ impl Edge for CubicCurve {
         (x, y)
     }
 
+    fn area(&self) -> f32 {
+        swept_area(&self.x_poly, &self.y_poly)
+    }
+
     fn bbox(&self) -> EdgeBoundingBox {
         let [tx_a, tx_b] = self.x_poly.derivative().roots();
         let [ty_a, ty_b] = self.y_poly.derivative().roots();
@@ -315,3 +461,109 @@ impl Edge for CubicCurve {
         }
     }
 }
+
+fn lerp(a: (f32, f32), b: (f32, f32), t: f32) -> (f32, f32) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+/// Convert a quadratic curve to a cubic curve with the same shape, returning
+/// the two control points of the cubic.  The conversion is exact.
+pub fn quad_to_cubic(start: (f32, f32), control: (f32, f32), end: (f32, f32)) -> [(f32, f32); 2] {
+    [
+        lerp(start, control, 2.0 / 3.0),
+        lerp(end, control, 2.0 / 3.0),
+    ]
+}
+
+/// The largest distance between a cubic curve and the quadratic curve
+/// through its endpoints sharing its midpoint
+fn cubic_quad_error(
+    start: (f32, f32),
+    control_s: (f32, f32),
+    control_e: (f32, f32),
+    end: (f32, f32),
+) -> f32 {
+    let dx = end.0 - 3.0 * control_e.0 + 3.0 * control_s.0 - start.0;
+    let dy = end.1 - 3.0 * control_e.1 + 3.0 * control_s.1 - start.1;
+    (dx * dx + dy * dy).sqrt() * 3f32.sqrt() / 36.0
+}
+
+/// Approximate a cubic curve with quadratic curves, so no point on them is
+/// further than `tolerance` from the cubic.  Returns the control point and
+/// end point of each quadratic curve; the first starts at `start`.
+///
+/// The cubic is split into equal pieces, at most 64, so a very small
+/// tolerance may not be met.
+pub fn cubic_to_quads(
+    start: (f32, f32),
+    control_s: (f32, f32),
+    control_e: (f32, f32),
+    end: (f32, f32),
+    tolerance: f32,
+) -> Vec<((f32, f32), (f32, f32))> {
+    // the error falls with the cube of the number of pieces; count up to
+    // the cube root rather than taking it, since platforms round `cbrt`
+    // differently, and the pieces should be the same everywhere
+    let ratio = cubic_quad_error(start, control_s, control_e, end) / tolerance;
+    let pieces = (1..MAX_QUADS)
+        .find(|&pieces| ratio.is_nan() || (pieces * pieces * pieces) as f32 >= ratio)
+        .unwrap_or(MAX_QUADS);
+    let curve = CubicCurve::new(start, control_s, control_e, end);
+    let step = 1.0 / pieces as f32;
+    (0..pieces)
+        .map(|i| {
+            let t0 = i as f32 * step;
+            let t1 = if i + 1 == pieces { 1.0 } else { t0 + step };
+            let p0 = curve.point(t0);
+            let p3 = if i + 1 == pieces {
+                end
+            } else {
+                curve.point(t1)
+            };
+            // control points of the piece, from the derivative at each end
+            let (d0x, d0y) = curve.direction(t0);
+            let (d1x, d1y) = curve.direction(t1);
+            let scale = (t1 - t0) / 3.0;
+            let p1 = (p0.0 + d0x * scale, p0.1 + d0y * scale);
+            let p2 = (p3.0 - d1x * scale, p3.1 - d1y * scale);
+            let control = (
+                (3.0 * (p1.0 + p2.0) - p0.0 - p3.0) / 4.0,
+                (3.0 * (p1.1 + p2.1) - p0.1 - p3.1) / 4.0,
+            );
+            (control, p3)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_and_derivatives_in_f64() {
+        // 2t³ - t + 5
+        let (value, slope, curvature) = value_f64(&[2.0, 0.0, -1.0, 5.0], 1.5);
+        assert_eq!((value, slope, curvature), (10.25, 12.5, 18.0));
+    }
+
+    #[test]
+    fn careful_search_finds_nearest_point() {
+        let quad = QuadCurve::new((0.0, 0.0), (0.5, 1.0), (1.0, 0.0));
+        let cubic = CubicCurve::new((0.0, 0.0), (0.0, 1.0), (1.0, -1.0), (1.0, 0.5));
+        let segments = [Segment::Quad(quad), Segment::Cubic(cubic)];
+        for segment in &segments {
+            for point in [(0.5, 0.2), (0.1, 0.9), (-0.3, 0.0), (0.9, 0.6), (0.5, -0.4)] {
+                let dist_sq = |t: f32| {
+                    let (x, y) = segment.point(t);
+                    (x - point.0).powi(2) + (y - point.1).powi(2)
+                };
+                // the nearest of many points spread along the curve
+                let sampled = (0..=10_000)
+                    .map(|step| dist_sq(step as f32 / 10_000.0))
+                    .fold(f32::INFINITY, f32::min);
+                let found = dist_sq(segment.careful_nearest_t(point));
+                assert!(found <= sampled + 1e-6, "{point:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::{
+    edge::Segment,
+    raster::{Buffer, RasterConfig, RasteredSize, Segments},
+    DistanceNorm,
+};
+
+const CURVE_STEPS: u8 = 16;
+
+/// Samples per texel along each axis when measuring coverage, enough that
+/// rounding to a byte dominates the error
+pub const COVERAGE_SAMPLES: usize = 16;
+
+/// Samples across each subpixel when measuring subpixel coverage, so a
+/// texel's three subpixels together have a few more than
+/// [`COVERAGE_SAMPLES`]
+pub const SUBPIXEL_SAMPLES: usize = 6;
+
+/// The weights, out of 256, which spread each subpixel's coverage over its
+/// neighbors to keep color fringes faint, as in FreeType's default LCD
+/// filter
+const LCD_FILTER: [u32; 5] = [8, 77, 86, 77, 8];
+
+/// A binary coverage mask of a glyph, sampled on a regular grid covering
+/// the rastered bounding box.
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    pub inside: Vec<bool>,
+}
+
+fn flatten(segments: &Segments) -> Vec<((f32, f32), (f32, f32))> {
+    let mut lines = Vec::new();
+    for (segment, _) in &segments.segments {
+        match segment {
+            Segment::LoopPoint(_, _) => {}
+            Segment::Line(_) => lines.push((segment.point(0.0), segment.point(1.0))),
+            Segment::Quad(_) | Segment::Cubic(_) => {
+                let mut prev = segment.point(0.0);
+                for step in 1..=CURVE_STEPS {
+                    let next = segment.point(f32::from(step) / f32::from(CURVE_STEPS));
+                    lines.push((prev, next));
+                    prev = next;
+                }
+            }
+        }
+    }
+    lines
+}
+
+impl Mask {
+    /// Rasterize the outline with the non-zero winding rule, sampling at the
+    /// center of each cell of a `width` by `height` grid.
+    pub fn from_segments(
+        segments: &Segments,
+        rastered_size: &RasteredSize,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        let lines = flatten(segments);
+        let mut inside = vec![false; width * height];
+        let cell_w = (rastered_size.right - rastered_size.left) / (width as f32);
+        let cell_h = (rastered_size.top - rastered_size.bottom) / (height as f32);
+        let mut crossings = Vec::new();
+        for row in 0..height {
+            let y = rastered_size.bottom + (row as f32 + 0.5) * cell_h;
+            crossings.clear();
+            for &((x0, y0), (x1, y1)) in &lines {
+                let winding = if y0 <= y && y < y1 {
+                    1
+                } else if y1 <= y && y < y0 {
+                    -1
+                } else {
+                    continue;
+                };
+                let t = (y - y0) / (y1 - y0);
+                crossings.push((x0 + t * (x1 - x0), winding));
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            let mut span_start = 0.0;
+            for &(x, dir) in &crossings {
+                let was_inside = winding != 0;
+                winding += dir;
+                if !was_inside && winding != 0 {
+                    span_start = x;
+                } else if was_inside && winding == 0 {
+                    let first = ((span_start - rastered_size.left) / cell_w - 0.5).ceil();
+                    let last = ((x - rastered_size.left) / cell_w - 0.5).floor();
+                    let first = first.clamp(0.0, width as f32) as usize;
+                    let last = last.clamp(-1.0, width as f32 - 1.0);
+                    if last >= 0.0 {
+                        let row_cells = &mut inside[row * width..(row + 1) * width];
+                        for cell in row_cells.iter_mut().take(last as usize + 1).skip(first) {
+                            *cell = true;
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            inside,
+        }
+    }
+
+    /// Compute the signed distance, in cells, from the center of each cell
+    /// to the boundary of the mask.  Distances are positive outside.
+    ///
+    /// For norms other than [`DistanceNorm::Euclidean`], distances greater
+    /// than `limit` are not computed exactly, and are reported as infinite.
+    pub fn signed_distances(&self, norm: DistanceNorm, limit: f32) -> Vec<f32> {
+        let (outside_dist, inside_dist) = match norm {
+            DistanceNorm::Euclidean => {
+                let root = |dist: Vec<f32>| dist.into_iter().map(f32::sqrt).collect();
+                (
+                    root(self.squared_distances(true)),
+                    root(self.squared_distances(false)),
+                )
+            }
+            _ => (
+                self.norm_distances(true, norm, limit),
+                self.norm_distances(false, norm, limit),
+            ),
+        };
+        outside_dist
+            .into_iter()
+            .zip(inside_dist)
+            .zip(&self.inside)
+            .map(|((to_inside, to_outside), &inside)| {
+                if inside {
+                    0.5 - to_outside
+                } else {
+                    to_inside - 0.5
+                }
+            })
+            .collect()
+    }
+
+    /// Chebyshev or manhattan distance from each cell to the nearest cell
+    /// whose `inside` value equals `target`, up to `limit`.
+    fn norm_distances(&self, target: bool, norm: DistanceNorm, limit: f32) -> Vec<f32> {
+        let mut grid: Vec<f32> = self
+            .inside
+            .iter()
+            .map(|&inside| if inside == target { 0.0 } else { f32::INFINITY })
+            .collect();
+        // vertical distances are exact in one dimension
+        for x in 0..self.width {
+            for y in 1..self.height {
+                let above = grid[(y - 1) * self.width + x] + 1.0;
+                let cell = &mut grid[y * self.width + x];
+                *cell = cell.min(above);
+            }
+            for y in (0..self.height.saturating_sub(1)).rev() {
+                let below = grid[(y + 1) * self.width + x] + 1.0;
+                let cell = &mut grid[y * self.width + x];
+                *cell = cell.min(below);
+            }
+        }
+        let combine = |horizontal: f32, vertical: f32| match norm {
+            DistanceNorm::Chebyshev => horizontal.max(vertical),
+            _ => horizontal + vertical,
+        };
+        let mut column = vec![0.0; self.width];
+        for row in grid.chunks_mut(self.width) {
+            column.copy_from_slice(row);
+            for (x, cell) in row.iter_mut().enumerate() {
+                let mut best = column[x];
+                let mut offset = 1;
+                while (offset as f32) < best && (offset as f32) <= limit {
+                    let left = x.checked_sub(offset).map_or(f32::INFINITY, |x| column[x]);
+                    let right = column.get(x + offset).copied().unwrap_or(f32::INFINITY);
+                    best = best.min(combine(offset as f32, left.min(right)));
+                    offset += 1;
+                }
+                *cell = best;
+            }
+        }
+        grid
+    }
+
+    /// Squared euclidean distance from each cell to the nearest cell whose
+    /// `inside` value equals `target`.
+    fn squared_distances(&self, target: bool) -> Vec<f32> {
+        let mut grid: Vec<f32> = self
+            .inside
+            .iter()
+            .map(|&inside| if inside == target { 0.0 } else { f32::INFINITY })
+            .collect();
+        let longest = self.width.max(self.height);
+        let mut column = vec![0.0; longest];
+        let mut output = vec![0.0; longest];
+        let mut scratch = Scratch::new(longest);
+        for x in 0..self.width {
+            for y in 0..self.height {
+                column[y] = grid[y * self.width + x];
+            }
+            scratch.transform(&column[..self.height], &mut output[..self.height]);
+            for y in 0..self.height {
+                grid[y * self.width + x] = output[y];
+            }
+        }
+        for row in grid.chunks_mut(self.width) {
+            column[..self.width].copy_from_slice(row);
+            scratch.transform(&column[..self.width], row);
+        }
+        grid
+    }
+}
+
+/// Working memory for the one dimensional distance transform described in
+/// "Distance Transforms of Sampled Functions" (Felzenszwalb & Huttenlocher).
+struct Scratch {
+    vertices: Vec<usize>,
+    boundaries: Vec<f32>,
+}
+
+impl Scratch {
+    fn new(len: usize) -> Self {
+        Self {
+            vertices: vec![0; len],
+            boundaries: vec![0.0; len + 1],
+        }
+    }
+
+    fn transform(&mut self, f: &[f32], out: &mut [f32]) {
+        let Some(first) = f.iter().position(|value| value.is_finite()) else {
+            out.fill(f32::INFINITY);
+            return;
+        };
+        let intersect = |q: usize, p: usize| {
+            let (qf, pf) = (q as f32, p as f32);
+            ((f[q] + qf * qf) - (f[p] + pf * pf)) / (2.0 * qf - 2.0 * pf)
+        };
+        let mut k = 0;
+        self.vertices[0] = first;
+        self.boundaries[0] = f32::NEG_INFINITY;
+        self.boundaries[1] = f32::INFINITY;
+        for (q, value) in f.iter().enumerate().skip(first + 1) {
+            if !value.is_finite() {
+                continue;
+            }
+            let mut s = intersect(q, self.vertices[k]);
+            while s <= self.boundaries[k] {
+                k -= 1;
+                s = intersect(q, self.vertices[k]);
+            }
+            k += 1;
+            self.vertices[k] = q;
+            self.boundaries[k] = s;
+            self.boundaries[k + 1] = f32::INFINITY;
+        }
+        k = 0;
+        for (q, value) in out.iter_mut().enumerate() {
+            let qf = q as f32;
+            while self.boundaries[k + 1] < qf {
+                k += 1;
+            }
+            let v = self.vertices[k];
+            let vf = v as f32;
+            *value = (qf - vf) * (qf - vf) + f[v];
+        }
+    }
+}
+
+pub fn raster(
+    buffer: &mut Buffer<'_>,
+    config: RasterConfig,
+    segments: &Segments,
+    rastered_size: &RasteredSize,
+    rect: &crunch::Rect,
+    rotate: bool,
+    oversample: u8,
+) {
+    let oversample = usize::from(oversample.max(1));
+    let mask_width = usize::from(rastered_size.pixel_width) * oversample;
+    let mask_height = usize::from(rastered_size.pixel_height) * oversample;
+    if mask_width == 0 || mask_height == 0 {
+        return;
+    }
+    let mask = Mask::from_segments(segments, rastered_size, mask_width, mask_height);
+    raster_mask(buffer, config, &mask, rastered_size, rect, rotate);
+}
+
+/// Fill a glyph's rect with the fraction of each texel inside the glyph,
+/// from a mask of [`COVERAGE_SAMPLES`] cells per texel along each axis
+pub fn raster_coverage(buffer: &mut Buffer<'_>, mask: &Mask, rect: &crunch::Rect, rotate: bool) {
+    let samples = COVERAGE_SAMPLES * COVERAGE_SAMPLES;
+    for dest_y in 0..rect.h {
+        for dest_x in 0..rect.w {
+            let (x, y) = if rotate {
+                (dest_y, dest_x)
+            } else {
+                (dest_x, dest_y)
+            };
+            let mut count = 0;
+            for row in y * COVERAGE_SAMPLES..(y + 1) * COVERAGE_SAMPLES {
+                let start = row * mask.width + x * COVERAGE_SAMPLES;
+                count += mask.inside[start..start + COVERAGE_SAMPLES]
+                    .iter()
+                    .filter(|&&inside| inside)
+                    .count();
+            }
+            let value = (count * usize::from(u8::MAX) + samples / 2) / samples;
+            buffer.set_pixel((dest_x + rect.x, dest_y + rect.y), value as u8);
+        }
+    }
+}
+
+/// Fill a glyph's rect with the fraction of each RGB subpixel inside the
+/// glyph, three bytes per texel, from a mask of [`SUBPIXEL_SAMPLES`] cells
+/// across each subpixel and [`COVERAGE_SAMPLES`] cells up each texel.  The
+/// subpixels lie along the glyph's horizontal axis, even if it is rotated.
+pub fn raster_subpixel_coverage(
+    buffer: &mut Buffer<'_>,
+    mask: &Mask,
+    rect: &crunch::Rect,
+    rotate: bool,
+) {
+    let (width, height) = if rotate {
+        (rect.h, rect.w)
+    } else {
+        (rect.w, rect.h)
+    };
+    let samples = (SUBPIXEL_SAMPLES * COVERAGE_SAMPLES) as u32;
+    let mut subpixels = vec![0; width * 3];
+    for y in 0..height {
+        for (subpixel, count) in subpixels.iter_mut().enumerate() {
+            *count = (y * COVERAGE_SAMPLES..(y + 1) * COVERAGE_SAMPLES)
+                .map(|row| {
+                    let start = row * mask.width + subpixel * SUBPIXEL_SAMPLES;
+                    mask.inside[start..start + SUBPIXEL_SAMPLES]
+                        .iter()
+                        .filter(|&&inside| inside)
+                        .count() as u32
+                })
+                .sum::<u32>();
+        }
+        for x in 0..width {
+            let mut value = [0; 3];
+            for (channel, value) in value.iter_mut().enumerate() {
+                let center = x * 3 + channel;
+                let filtered: u32 = LCD_FILTER
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(tap, weight)| {
+                        let subpixel = (center + tap).checked_sub(2)?;
+                        Some(weight * subpixels.get(subpixel)?)
+                    })
+                    .sum();
+                let scale = 256 * samples;
+                *value = ((filtered * u32::from(u8::MAX) + scale / 2) / scale) as u8;
+            }
+            let (dest_x, dest_y) = if rotate { (y, x) } else { (x, y) };
+            buffer.set_subpixels((dest_x + rect.x, dest_y + rect.y), value);
+        }
+    }
+}
+
+/// Fill a glyph's rect from a mask covering its rastered bounding box
+pub fn raster_mask(
+    buffer: &mut Buffer<'_>,
+    config: RasterConfig,
+    mask: &Mask,
+    rastered_size: &RasteredSize,
+    rect: &crunch::Rect,
+    rotate: bool,
+) {
+    let Mask {
+        width: mask_width,
+        height: mask_height,
+        ..
+    } = *mask;
+    if mask_width == 0 || mask_height == 0 {
+        return;
+    }
+    let cell_w = (rastered_size.right - rastered_size.left) / (mask_width as f32);
+    let cell_h = (rastered_size.top - rastered_size.bottom) / (mask_height as f32);
+    let cell_size = (cell_w + cell_h) / 2.0;
+    let spread = config.spread();
+    let limit = (spread + config.bold.abs()) / cell_size + 1.0;
+    let distances = mask.signed_distances(config.norm, limit);
+    for dest_y in 0..rect.h {
+        let y = (dest_y as f32 + 0.5) / (rect.h as f32);
+        let dest_y = dest_y + rect.y;
+        for dest_x in 0..rect.w {
+            let x = (dest_x as f32 + 0.5) / (rect.w as f32);
+            let dest_x = dest_x + rect.x;
+            let (x, y) = if rotate { (y, x) } else { (x, y) };
+            let mask_x = ((x * mask_width as f32) as usize).min(mask_width - 1);
+            let mask_y = ((y * mask_height as f32) as usize).min(mask_height - 1);
+            let dist = distances[mask_y * mask_width + mask_x] * cell_size;
+            buffer.set_pixel((dest_x, dest_y), config.encode(dist));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+//! Describe text styles built from a distance field, such as outlines, glows
+//! and drop shadows, as data.
+//!
+//! A style is an [`EffectPreset`]: a background color and a stack of
+//! [`Effect`] layers, each drawing the glyphs once with its own offset, color
+//! and band of distances.  Every type implements serde's `Serialize` and
+//! `Deserialize`, so presets can be loaded from configuration files and
+//! shared between applications.  Drawing is left to the renderer; see the
+//! `shader-effects` example for a shader which consumes these values.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+/// A displacement of an effect layer, as a ratio of the font size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Offset(pub f32, pub f32);
+
+/// A non-premultiplied RGBA color, with each channel from 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Color(pub f32, pub f32, pub f32, pub f32);
+
+/// The band of distance field values an effect layer covers
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// The lowest value inside the band.  With the default
+    /// [`DistanceMode::Signed`](crate::DistanceMode::Signed), the outline is
+    /// at 0.5, and lower values reach further outside the glyph.
+    pub start: f32,
+
+    /// The highest value inside the band.  Values above 1 include the whole
+    /// inside of the glyph.
+    pub end: f32,
+
+    /// The width of the fade at the edges of the band, in distance field
+    /// values.  Zero asks the renderer to pick a width which antialiases
+    /// the edges at the size the text is drawn.
+    #[serde(default)]
+    pub smoothing: f32,
+}
+
+/// One pass of drawing the glyphs
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Effect {
+    /// How far to move this layer from the text's position, for effects
+    /// like drop shadows
+    #[serde(default)]
+    pub offset: Offset,
+
+    /// The color to fill the band with
+    pub color: Color,
+
+    /// Which distances this layer covers
+    pub config: Config,
+}
+
+/// A named text style
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EffectPreset {
+    /// A name to show when choosing between presets
+    pub name: Cow<'static, str>,
+
+    /// The color to clear behind the text
+    pub background_color: Color,
+
+    /// The layers to draw, back to front
+    pub effects: Cow<'static, [Effect]>,
+}
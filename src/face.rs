@@ -0,0 +1,152 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::{Face, LineMetrics, Tag};
+
+/// Identifies which face a glyph was rendered from, see [`Glyph::face`].
+///
+/// Faces are numbered from zero in the order they first appear among the
+/// requests, so for requests built from a list of faces in order, the handle
+/// is the index into that list.
+///
+/// [`Glyph::face`]: crate::Glyph::face
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FaceHandle(pub(crate) u16);
+
+impl FaceHandle {
+    /// The position of the face among the faces of the asset
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+/// The metrics of a face for laying out lines of text, see
+/// [`SdfFontAsset::metrics`](crate::SdfFontAsset::metrics).  Distances are
+/// in ems, as the bounds of glyphs are, and measured up from the baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FontMetrics {
+    /// The height of the tallest glyphs above the baseline
+    pub ascent: f32,
+
+    /// The depth of the lowest glyphs, which is negative for glyphs
+    /// reaching below the baseline
+    pub descent: f32,
+
+    /// The space the font asks for between the descent of one line and the
+    /// ascent of the next
+    pub line_gap: f32,
+
+    /// The height of lowercase letters such as `x` above the baseline, if
+    /// the face's `OS/2` table gives it, for centering text vertically by
+    /// its lowercase letters
+    pub x_height: Option<f32>,
+
+    /// The height of capital letters above the baseline, if the face's
+    /// `OS/2` table gives it, for centering labels vertically or scaling
+    /// capitals down to stand in for small caps
+    pub cap_height: Option<f32>,
+
+    /// Where to draw underlines, if the face's `post` table says
+    pub underline: Option<DecorationMetrics>,
+
+    /// Where to draw lines striking through text, if the face's `OS/2`
+    /// table says
+    pub strikeout: Option<DecorationMetrics>,
+
+    /// The number of font units in an em, which the face's own metrics and
+    /// outlines are given in
+    pub units_per_em: u16,
+}
+
+impl FontMetrics {
+    pub(crate) fn new(face: &Face<'_>) -> Self {
+        let units_per_em = face.units_per_em();
+        let em = |units: i16| f32::from(units) / f32::from(units_per_em);
+        Self {
+            ascent: em(face.ascender()),
+            descent: em(face.descender()),
+            line_gap: em(face.line_gap()),
+            x_height: face.x_height().map(em),
+            cap_height: face.capital_height().map(em),
+            underline: face
+                .underline_metrics()
+                .map(|line| DecorationMetrics::new(line, em)),
+            strikeout: face
+                .strikeout_metrics()
+                .map(|line| DecorationMetrics::new(line, em)),
+            units_per_em,
+        }
+    }
+
+    /// The distance from one baseline to the next
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/// Where a font draws a line decorating text, such as an underline, see
+/// [`FontMetrics::underline`].  Distances are in ems.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct DecorationMetrics {
+    /// The height of the top edge of the line above the baseline, which is
+    /// negative for lines below it
+    pub position: f32,
+
+    /// How thick the line is, downward from its top edge
+    pub thickness: f32,
+}
+
+impl DecorationMetrics {
+    fn new(line: LineMetrics, em: impl Fn(i16) -> f32) -> Self {
+        Self {
+            position: em(line.position),
+            thickness: em(line.thickness),
+        }
+    }
+}
+
+/// Enough of a face to recognize it again once the borrowed face is gone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceFingerprint {
+    checksum: Option<u32>,
+    number_of_glyphs: u16,
+    units_per_em: u16,
+}
+
+impl FaceFingerprint {
+    pub fn new(face: &Face<'_>) -> Self {
+        // the whole-font checksum adjustment in the `head` table
+        let checksum = face
+            .raw_face()
+            .table(Tag::from_bytes(b"head"))
+            .and_then(|head| head.get(8..12))
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        Self {
+            checksum,
+            number_of_glyphs: face.number_of_glyphs(),
+            units_per_em: face.units_per_em(),
+        }
+    }
+}
+
+/// The distinct faces among some requests, in order of first appearance
+pub fn distinct_faces<'a>(faces: impl Iterator<Item = &'a Face<'a>>) -> Vec<&'a Face<'a>> {
+    let mut distinct: Vec<&Face<'_>> = Vec::new();
+    for face in faces {
+        if !distinct.iter().any(|known| std::ptr::eq(*known, face)) {
+            distinct.push(face);
+        }
+    }
+    distinct
+}
+
+/// The handle of a face among the distinct faces of a build
+pub fn handle_of(faces: &[&Face<'_>], face: &Face<'_>) -> FaceHandle {
+    let index = faces
+        .iter()
+        .position(|known| std::ptr::eq(*known, face))
+        .unwrap_or(0);
+    FaceHandle(index as u16)
+}
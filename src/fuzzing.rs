@@ -0,0 +1,187 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+//! Entry points for fuzzing the geometry code with arbitrary outlines.
+//!
+//! This module is not covered by semver guarantees; it exists so fuzz
+//! targets can drive the rasterizer without needing a valid font file.
+
+use ttf_parser::OutlineBuilder;
+
+use crate::{
+    raster::{Buffer, RasterConfig, RasteredSize, Segments},
+    DistanceMode, DistanceNorm, RasterBackend,
+};
+
+/// A single command of a glyph outline, in the same form as the callbacks of
+/// [`OutlineBuilder`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutlineCommand {
+    /// Begin a new contour at a point
+    MoveTo(f32, f32),
+    /// Draw a line to a point
+    LineTo(f32, f32),
+    /// Draw a quadratic curve with a control point, to a point
+    QuadTo(f32, f32, f32, f32),
+    /// Draw a cubic curve with two control points, to a point
+    CurveTo(f32, f32, f32, f32, f32, f32),
+    /// Close the current contour
+    Close,
+}
+
+impl OutlineCommand {
+    /// Decode a stream of commands from arbitrary bytes, as provided by a
+    /// fuzzer.  Each command is an opcode byte followed by its coordinates as
+    /// little-endian `f32`s; decoding stops when the data runs out.
+    pub fn decode(mut data: &[u8]) -> Vec<Self> {
+        let mut commands = Vec::new();
+        while let Some((&opcode, rest)) = data.split_first() {
+            let opcode = opcode % 5;
+            let count = [2, 2, 4, 6, 0][usize::from(opcode)];
+            let Some(coords) = rest.get(..count * 4) else {
+                break;
+            };
+            data = &rest[count * 4..];
+            let mut c = [0.0; 6];
+            for (value, bytes) in c.iter_mut().zip(coords.chunks_exact(4)) {
+                *value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            commands.push(match opcode {
+                0 => Self::MoveTo(c[0], c[1]),
+                1 => Self::LineTo(c[0], c[1]),
+                2 => Self::QuadTo(c[0], c[1], c[2], c[3]),
+                3 => Self::CurveTo(c[0], c[1], c[2], c[3], c[4], c[5]),
+                _ => Self::Close,
+            });
+        }
+        commands
+    }
+
+    fn points(&self) -> impl Iterator<Item = (f32, f32)> {
+        let points = match *self {
+            Self::MoveTo(x, y) | Self::LineTo(x, y) => [Some((x, y)), None, None],
+            Self::QuadTo(x1, y1, x, y) => [Some((x1, y1)), Some((x, y)), None],
+            Self::CurveTo(x1, y1, x2, y2, x, y) => [Some((x1, y1)), Some((x2, y2)), Some((x, y))],
+            Self::Close => [None, None, None],
+        };
+        points.into_iter().flatten()
+    }
+}
+
+/// Render the distance field of an arbitrary outline into a `width` by
+/// `height` buffer.  The outline is scaled to fill the buffer, leaving
+/// `padding` as a fraction of the longest side around it.
+///
+/// Commands with non-finite coordinates are ignored, contours which are not
+/// explicitly closed are closed, and edges before the first `MoveTo` start
+/// from the origin.
+pub fn raster_commands(
+    commands: &[OutlineCommand],
+    width: u16,
+    height: u16,
+    padding: f32,
+    backend: RasterBackend,
+    norm: DistanceNorm,
+) -> Vec<u8> {
+    let mut data = vec![0; usize::from(width) * usize::from(height)];
+    let commands: Vec<OutlineCommand> = commands
+        .iter()
+        .copied()
+        .filter(|command| {
+            command
+                .points()
+                .all(|(x, y)| x.is_finite() && y.is_finite())
+        })
+        .collect();
+    let points = || commands.iter().flat_map(OutlineCommand::points);
+    let left = points().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let right = points().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let bottom = points().map(|p| p.1).fold(f32::INFINITY, f32::min);
+    let top = points().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+    let extent = (right - left).max(top - bottom);
+    if width == 0 || height == 0 || !extent.is_finite() || !padding.is_finite() || padding <= 0.0 {
+        return data;
+    }
+    let scale = if extent > 0.0 { extent } else { 1.0 };
+    let padding = padding * scale;
+    let rastered_size = RasteredSize {
+        pixel_width: width,
+        pixel_height: height,
+        left: left - padding,
+        right: right + padding,
+        top: top + padding,
+        bottom: bottom - padding,
+    };
+    let mut segments = Segments::new(1.0);
+    let mut open = false;
+    for command in commands {
+        match command {
+            OutlineCommand::MoveTo(x, y) => {
+                if open {
+                    segments.close();
+                }
+                segments.move_to(x, y);
+                open = true;
+            }
+            OutlineCommand::LineTo(x, y) => {
+                if !open {
+                    segments.move_to(0.0, 0.0);
+                    open = true;
+                }
+                segments.line_to(x, y);
+            }
+            OutlineCommand::QuadTo(x1, y1, x, y) => {
+                if !open {
+                    segments.move_to(0.0, 0.0);
+                    open = true;
+                }
+                segments.quad_to(x1, y1, x, y);
+            }
+            OutlineCommand::CurveTo(x1, y1, x2, y2, x, y) => {
+                if !open {
+                    segments.move_to(0.0, 0.0);
+                    open = true;
+                }
+                segments.curve_to(x1, y1, x2, y2, x, y);
+            }
+            OutlineCommand::Close => {
+                if open {
+                    segments.close();
+                }
+                open = false;
+            }
+        }
+    }
+    if open {
+        segments.close();
+    }
+    let rect = crunch::Rect {
+        x: 0,
+        y: 0,
+        w: usize::from(width),
+        h: usize::from(height),
+    };
+    let mut buffer = Buffer {
+        data: &mut data,
+        width,
+    };
+    crate::raster::raster_or_retry(
+        &mut buffer,
+        RasterConfig {
+            padding: [padding; 4],
+            backend,
+            norm,
+            mode: DistanceMode::Signed,
+            bold: 0.0,
+            simplify: 0.0,
+            variations: Default::default(),
+            subpixel: false,
+            careful: false,
+        },
+        &segments,
+        &rastered_size,
+        &rect,
+        false,
+    );
+    data
+}
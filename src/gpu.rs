@@ -0,0 +1,470 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+//! Compute distance fields on the GPU using the jump flooding algorithm.
+//!
+//! Glyphs are packed exactly as they would be by
+//! [`build`](FontAssetBuilder::build), so the resulting metadata is
+//! interchangeable with the CPU path.  Distances are derived from a binary
+//! mask of the whole atlas, so the result is comparable to
+//! [`RasterBackend::Approximate`](crate::RasterBackend::Approximate).  Each
+//! texel of the mask records which glyph's tile it is in, and every pass
+//! only looks at texels of the same glyph, so a glyph's distances are to
+//! its own outline, never to a neighbour's across the gutter.
+
+use glow::HasContext;
+
+use crate::{
+    edt::Mask, raster, DistanceMode, DistanceNorm, Error, FontAssetBuilder, GlyphRequest,
+    SdfFontAsset,
+};
+
+const VERTEX_SHADER: &str = "#version 330 core
+void main() {
+    vec2 pos = vec2(float((gl_VertexID & 1) << 2), float((gl_VertexID & 2) << 1));
+    gl_Position = vec4(pos - 1.0, 0.0, 1.0);
+}
+";
+
+const SEED_SHADER: &str = "#version 330 core
+uniform sampler2D mask;
+uniform sampler2D owners;
+out vec2 seed;
+void main() {
+    ivec2 pos = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(mask, 0);
+    float owner = texelFetch(owners, pos, 0).r;
+    bool inside = texelFetch(mask, pos, 0).r > 0.5;
+    ivec2 offsets[4] = ivec2[](ivec2(1, 0), ivec2(-1, 0), ivec2(0, 1), ivec2(0, -1));
+    bool edge = false;
+    for (int i = 0; i < 4; i++) {
+        ivec2 other = clamp(pos + offsets[i], ivec2(0), size - 1);
+        bool same = texelFetch(owners, other, 0).r == owner;
+        edge = edge || (same && (texelFetch(mask, other, 0).r > 0.5) != inside);
+    }
+    seed = owner > 0.0 && edge ? vec2(pos) : vec2(-1.0);
+}
+";
+
+const FLOOD_SHADER: &str = "#version 330 core
+uniform sampler2D seeds;
+uniform sampler2D owners;
+uniform int step;
+out vec2 seed;
+void main() {
+    ivec2 pos = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(seeds, 0);
+    float owner = texelFetch(owners, pos, 0).r;
+    vec2 best = vec2(-1.0);
+    float best_dist = 3.0e38;
+    for (int dy = -1; dy <= 1; dy++) {
+        for (int dx = -1; dx <= 1; dx++) {
+            ivec2 other = pos + ivec2(dx, dy) * step;
+            if (any(lessThan(other, ivec2(0))) || any(greaterThanEqual(other, size))) {
+                continue;
+            }
+            vec2 candidate = texelFetch(seeds, other, 0).xy;
+            if (candidate.x < 0.0 || texelFetch(owners, ivec2(candidate), 0).r != owner) {
+                continue;
+            }
+            vec2 delta = candidate - vec2(pos);
+            float dist = dot(delta, delta);
+            if (dist < best_dist) {
+                best_dist = dist;
+                best = candidate;
+            }
+        }
+    }
+    seed = best;
+}
+";
+
+const RESOLVE_SHADER: &str = "#version 330 core
+uniform sampler2D mask;
+uniform sampler2D seeds;
+uniform int oversample;
+uniform float scale;
+uniform int norm;
+uniform float bias;
+uniform bool unsigned_mode;
+out float value;
+void main() {
+    ivec2 pos = ivec2(gl_FragCoord.xy) * oversample + oversample / 2;
+    bool inside = texelFetch(mask, pos, 0).r > 0.5;
+    vec2 seed = texelFetch(seeds, pos, 0).xy;
+    vec2 delta = abs(seed - vec2(pos));
+    float len = norm == 1 ? max(delta.x, delta.y) : norm == 2 ? delta.x + delta.y : length(delta);
+    float dist = seed.x < 0.0 ? 3.0e38 : len + 0.5;
+    if (unsigned_mode) {
+        value = clamp(1.0 - (dist - bias) * 2.0 * scale, 0.0, 1.0);
+    } else {
+        float signed_dist = (inside ? -dist : dist) - bias;
+        value = clamp(0.5 - signed_dist * scale, 0.0, 1.0);
+    }
+}
+";
+
+/// Parameters for converting distances in mask texels to output values
+struct Resolve {
+    /// The factor from mask texels to the range of the output
+    scale: f32,
+    /// The distance in mask texels to offset the outline outward
+    bias: f32,
+    norm: DistanceNorm,
+    mode: DistanceMode,
+}
+
+/// GPU resources for computing distance fields.
+///
+/// Rendering binds its own framebuffer, textures, and programs, and changes
+/// the viewport; callers should restore any state they depend on afterwards.
+pub struct JumpFlood<'gl> {
+    gl: &'gl glow::Context,
+    vertex_array: glow::VertexArray,
+    seed_program: glow::Program,
+    flood_program: glow::Program,
+    resolve_program: glow::Program,
+    oversample: u8,
+}
+
+impl<'gl> JumpFlood<'gl> {
+    /// Compile the shaders needed for jump flooding.  The context must be
+    /// current and support OpenGL 3.3.  Each glyph is rendered to a mask at
+    /// `oversample` times the resolution of the output; this is reduced if
+    /// necessary to fit the maximum texture size.
+    pub fn new(gl: &'gl glow::Context, oversample: u8) -> Result<Self, String> {
+        unsafe {
+            let vertex_array = gl.create_vertex_array()?;
+            let seed_program = compile_program(gl, SEED_SHADER)?;
+            let flood_program = compile_program(gl, FLOOD_SHADER)?;
+            let resolve_program = compile_program(gl, RESOLVE_SHADER)?;
+            Ok(Self {
+                gl,
+                vertex_array,
+                seed_program,
+                flood_program,
+                resolve_program,
+                oversample: oversample.max(1),
+            })
+        }
+    }
+
+    /// Compute the distance field of a mask, where `owners` holds, for each
+    /// texel of the mask, one more than the index of the glyph whose tile it
+    /// is in, or zero for texels outside every tile
+    unsafe fn render(
+        &self,
+        width: u16,
+        height: u16,
+        oversample: usize,
+        mask: &[u8],
+        owners: &[f32],
+        resolve: Resolve,
+    ) -> Result<Vec<u8>, Error> {
+        let gl = self.gl;
+        let mask_width = (usize::from(width) * oversample) as i32;
+        let mask_height = (usize::from(height) * oversample) as i32;
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+        let mask_size = (mask_width, mask_height);
+        let mask_texture = create_texture(gl, Texel::R8, mask_size, Some(mask))?;
+        let owners: Vec<u8> = owners
+            .iter()
+            .flat_map(|owner| owner.to_ne_bytes())
+            .collect();
+        let owner_texture = create_texture(gl, Texel::R32F, mask_size, Some(&owners))?;
+        let seeds = [
+            create_texture(gl, Texel::RG32F, mask_size, None)?,
+            create_texture(gl, Texel::RG32F, mask_size, None)?,
+        ];
+        let output = create_texture(gl, Texel::R8, (width.into(), height.into()), None)?;
+        let framebuffer = gl.create_framebuffer().map_err(|_| Error::GpuFailure)?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        gl.bind_vertex_array(Some(self.vertex_array));
+
+        let mut result = Ok(());
+        let mut target = |texture, w, h| {
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+            if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+                result = Err(Error::GpuFailure);
+            }
+            gl.viewport(0, 0, w, h);
+        };
+
+        target(seeds[0], mask_width, mask_height);
+        gl.use_program(Some(self.seed_program));
+        bind_sampler(gl, self.seed_program, "mask", 0, mask_texture);
+        bind_sampler(gl, self.seed_program, "owners", 1, owner_texture);
+        gl.draw_arrays(glow::TRIANGLES, 0, 3);
+
+        let mut current = 0;
+        let mut step = (mask_width.max(mask_height) as u32).next_power_of_two() / 2;
+        gl.use_program(Some(self.flood_program));
+        let step_uniform = gl.get_uniform_location(self.flood_program, "step");
+        bind_sampler(gl, self.flood_program, "owners", 1, owner_texture);
+        while step > 0 {
+            target(seeds[1 - current], mask_width, mask_height);
+            bind_sampler(gl, self.flood_program, "seeds", 0, seeds[current]);
+            gl.uniform_1_i32(step_uniform.as_ref(), step as i32);
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            current = 1 - current;
+            step /= 2;
+        }
+
+        target(output, width.into(), height.into());
+        gl.use_program(Some(self.resolve_program));
+        bind_sampler(gl, self.resolve_program, "mask", 0, mask_texture);
+        bind_sampler(gl, self.resolve_program, "seeds", 1, seeds[current]);
+        let oversample_uniform = gl.get_uniform_location(self.resolve_program, "oversample");
+        gl.uniform_1_i32(oversample_uniform.as_ref(), oversample as i32);
+        let scale_uniform = gl.get_uniform_location(self.resolve_program, "scale");
+        gl.uniform_1_f32(scale_uniform.as_ref(), resolve.scale);
+        let norm_uniform = gl.get_uniform_location(self.resolve_program, "norm");
+        let norm = match resolve.norm {
+            DistanceNorm::Euclidean => 0,
+            DistanceNorm::Chebyshev => 1,
+            DistanceNorm::Manhattan => 2,
+        };
+        gl.uniform_1_i32(norm_uniform.as_ref(), norm);
+        let bias_uniform = gl.get_uniform_location(self.resolve_program, "bias");
+        gl.uniform_1_f32(bias_uniform.as_ref(), resolve.bias);
+        let mode_uniform = gl.get_uniform_location(self.resolve_program, "unsigned_mode");
+        let unsigned_mode = resolve.mode == DistanceMode::Unsigned;
+        gl.uniform_1_i32(mode_uniform.as_ref(), i32::from(unsigned_mode));
+        gl.draw_arrays(glow::TRIANGLES, 0, 3);
+
+        let mut data = vec![0; usize::from(width) * usize::from(height)];
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(
+            0,
+            0,
+            width.into(),
+            height.into(),
+            glow::RED,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(&mut data),
+        );
+
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.delete_framebuffer(framebuffer);
+        for texture in [mask_texture, owner_texture, seeds[0], seeds[1], output] {
+            gl.delete_texture(texture);
+        }
+        result.map(|()| data)
+    }
+}
+
+impl Drop for JumpFlood<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.seed_program);
+            self.gl.delete_program(self.flood_program);
+            self.gl.delete_program(self.resolve_program);
+            self.gl.delete_vertex_array(self.vertex_array);
+        }
+    }
+}
+
+unsafe fn compile_program(
+    gl: &glow::Context,
+    fragment_source: &str,
+) -> Result<glow::Program, String> {
+    let program = gl.create_program()?;
+    let mut shaders = Vec::new();
+    for (kind, source) in [
+        (glow::VERTEX_SHADER, VERTEX_SHADER),
+        (glow::FRAGMENT_SHADER, fragment_source),
+    ] {
+        let shader = gl.create_shader(kind)?;
+        gl.shader_source(shader, source);
+        gl.compile_shader(shader);
+        if !gl.get_shader_compile_status(shader) {
+            return Err(gl.get_shader_info_log(shader));
+        }
+        gl.attach_shader(program, shader);
+        shaders.push(shader);
+    }
+    gl.link_program(program);
+    for shader in shaders {
+        gl.detach_shader(program, shader);
+        gl.delete_shader(shader);
+    }
+    if !gl.get_program_link_status(program) {
+        return Err(gl.get_program_info_log(program));
+    }
+    Ok(program)
+}
+
+/// The formats of the textures used for jump flooding
+#[derive(Clone, Copy)]
+enum Texel {
+    /// One byte, for masks and output
+    R8,
+    /// One float, for the glyph each texel belongs to
+    R32F,
+    /// Two floats, for the position of a seed
+    RG32F,
+}
+
+impl Texel {
+    /// The internal format, format, and type of the texture's pixels
+    fn formats(self) -> (u32, u32, u32) {
+        match self {
+            Self::R8 => (glow::R8, glow::RED, glow::UNSIGNED_BYTE),
+            Self::R32F => (glow::R32F, glow::RED, glow::FLOAT),
+            Self::RG32F => (glow::RG32F, glow::RG, glow::FLOAT),
+        }
+    }
+}
+
+/// Create a texture of `texel`s, `(width, height)` in size, filled with
+/// `data` if given
+unsafe fn create_texture(
+    gl: &glow::Context,
+    texel: Texel,
+    (width, height): (i32, i32),
+    data: Option<&[u8]>,
+) -> Result<glow::Texture, Error> {
+    let texture = gl.create_texture().map_err(|_| Error::GpuFailure)?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    let (internal_format, format, pixel_type) = texel.formats();
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        width,
+        height,
+        0,
+        format,
+        pixel_type,
+        data,
+    );
+    for param in [glow::TEXTURE_MIN_FILTER, glow::TEXTURE_MAG_FILTER] {
+        gl.tex_parameter_i32(glow::TEXTURE_2D, param, glow::NEAREST as i32);
+    }
+    Ok(texture)
+}
+
+unsafe fn bind_sampler(
+    gl: &glow::Context,
+    program: glow::Program,
+    name: &str,
+    unit: u32,
+    texture: glow::Texture,
+) {
+    gl.active_texture(glow::TEXTURE0 + unit);
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    let location = gl.get_uniform_location(program, name);
+    gl.uniform_1_i32(location.as_ref(), unit as i32);
+}
+
+impl FontAssetBuilder {
+    /// Build a SDF font asset given a set of glyphs to include, computing
+    /// the distance fields on the GPU.  The configured
+    /// [`RasterBackend`](crate::RasterBackend) is ignored, but the
+    /// [`DistanceNorm`] is respected.
+    pub fn build_gpu<'a, T, I>(
+        self,
+        jump_flood: &JumpFlood<'_>,
+        glyphs: I,
+    ) -> Result<SdfFontAsset<T>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        let plan = self.pack(&glyphs, None)?;
+        // pages are rendered as one image, stacked from the bottom up
+        let height = plan
+            .height
+            .checked_mul(plan.pages)
+            .ok_or(Error::GpuFailure)?;
+        let (width, font_size) = (plan.width, plan.font_size);
+        let max_size = unsafe { jump_flood.gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE) };
+        let longest = usize::from(width.max(height));
+        let oversample = usize::from(jump_flood.oversample)
+            .min(max_size.max(1) as usize / longest)
+            .max(1);
+        let mask_width = usize::from(width) * oversample;
+        let mask_height = usize::from(height) * oversample;
+        let mut mask = vec![0; mask_width * mask_height];
+        let mut owners = vec![0.0; mask_width * mask_height];
+        for (index, item) in plan.packing.iter().enumerate() {
+            // floats hold every whole number up to 2^24 exactly
+            let owner = (index + 1) as f32;
+            for y in item.rect.y * oversample..(item.rect.y + item.rect.h) * oversample {
+                let row = y * mask_width;
+                let (left, right) = (item.rect.x, item.rect.x + item.rect.w);
+                owners[row + left * oversample..row + right * oversample].fill(owner);
+            }
+            let (request, rastered_size) = (&item.request, &item.size);
+            let rotate = item.rotated;
+            let segments = raster::glyph_segments(request, self.simplify, &self.variations)?;
+            let glyph_width = usize::from(rastered_size.pixel_width) * oversample;
+            let glyph_height = usize::from(rastered_size.pixel_height) * oversample;
+            let glyph_mask = match () {
+                #[cfg(feature = "bitmap")]
+                () if segments.edge_count() == 0 => {
+                    match crate::bitmap::Bitmap::from_request(request) {
+                        Some(bitmap) => bitmap.mask(rastered_size, glyph_width, glyph_height),
+                        None => {
+                            Mask::from_segments(&segments, rastered_size, glyph_width, glyph_height)
+                        }
+                    }
+                }
+                () => Mask::from_segments(&segments, rastered_size, glyph_width, glyph_height),
+            };
+            for (i, &inside) in glyph_mask.inside.iter().enumerate() {
+                if !inside {
+                    continue;
+                }
+                let (x, y) = (i % glyph_width, i / glyph_width);
+                let (x, y) = if rotate { (y, x) } else { (x, y) };
+                let x = item.rect.x * oversample + x;
+                let y = item.rect.y * oversample + y;
+                mask[y * mask_width + x] = u8::MAX;
+            }
+        }
+        let spread_px = self.padding.spread(font_size) * font_size;
+        let resolve = Resolve {
+            scale: 1.0 / (2.0 * spread_px * oversample as f32),
+            bias: self.bold * font_size * oversample as f32,
+            norm: self.norm,
+            mode: self.mode,
+        };
+        let data =
+            unsafe { jump_flood.render(width, height, oversample, &mask, &owners, resolve)? };
+        let coverage = if self.coverage {
+            let texels = usize::from(width) * usize::from(height);
+            let mut coverage = vec![0; texels * self.coverage_bytes()];
+            let mut buffer = raster::Buffer {
+                data: &mut coverage,
+                width,
+            };
+            for item in &plan.packing {
+                let segments =
+                    raster::glyph_segments(&item.request, self.simplify, &self.variations)?;
+                raster::raster_coverage(&mut buffer, &segments, item, self.subpixel);
+            }
+            Some(coverage)
+        } else {
+            None
+        };
+        let glyphs: Vec<crunch::Rect> = plan.packing.iter().map(|item| item.rect).collect();
+        let reserved: Vec<crunch::Rect> = plan
+            .reserved
+            .iter()
+            .map(|rect| rect.as_rect(plan.height, self.pages_per_layer()))
+            .collect();
+        let (channels, data, mip_levels) =
+            self.finish_pages(&plan, data, coverage, &glyphs, &reserved);
+        let channels = channels * self.pages_per_layer() as u8;
+        Ok(SdfFontAsset::from_plan(
+            plan, spread_px, self.mode, channels, data, mip_levels,
+        ))
+    }
+}
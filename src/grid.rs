@@ -0,0 +1,39 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::packer::{Packer, PackerItem, PackerPlacement};
+
+/// The packer used by [`PackingStrategy::Grid`](crate::PackingStrategy::Grid).
+///
+/// Every item gets a cell the size of the widest and tallest item, and
+/// cells are filled in the order the items are given, left to right and
+/// then bottom to top.  Each item sits in the bottom left corner of its
+/// cell, so item `i` is at column `i % columns` and row `i / columns`,
+/// where `columns` is as many cells as fit across.  Items are never
+/// rotated.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GridPacker;
+
+impl Packer for GridPacker {
+    fn pack(&self, width: u32, height: u32, items: &[PackerItem]) -> Option<Vec<PackerPlacement>> {
+        let cell_width = items.iter().map(|item| item.width).max().unwrap_or(0);
+        let cell_height = items.iter().map(|item| item.height).max().unwrap_or(0);
+        let columns = width.checked_div(cell_width).unwrap_or(u32::MAX);
+        if columns == 0 {
+            return None;
+        }
+        let rows = (items.len() as u64).div_ceil(u64::from(columns));
+        if rows * u64::from(cell_height) > u64::from(height) {
+            return None;
+        }
+        let placements = (0..items.len() as u32)
+            .map(|index| PackerPlacement {
+                item: index as usize,
+                x: (index % columns) * cell_width,
+                y: (index / columns) * cell_height,
+                rotated: false,
+            })
+            .collect();
+        Some(placements)
+    }
+}
@@ -0,0 +1,293 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ttf_parser::{
+    gpos::{PairAdjustment, PositioningSubtable},
+    kern::Format,
+    Face, GlyphId, Tag,
+};
+
+use crate::{FaceHandle, Glyph};
+
+/// The kerning between two glyphs of an asset, see
+/// [`SdfFontAsset::kerning`](crate::SdfFontAsset::kerning)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct KerningPair {
+    /// The index in the metadata of the glyph on the left
+    pub left: u32,
+
+    /// The index in the metadata of the glyph on the right
+    pub right: u32,
+
+    /// The distance to add to the left glyph's advance when the right glyph
+    /// follows it, in ems, which is usually negative to close a gap
+    pub adjust: f32,
+}
+
+/// The kerning between the glyphs from one face, for the pairs with at
+/// least one glyph at index `new` or later in the metadata.  The face's
+/// `kern` feature in its `GPOS` table is used if it has one, or else its
+/// `kern` table.
+///
+/// Rather than look up every pair of glyphs, which is far too slow for
+/// large character sets, the pairs are read from the tables and kept if
+/// both glyphs are in the metadata.
+pub fn face_pairs<T>(
+    face: &Face<'_>,
+    handle: FaceHandle,
+    glyphs: &[Glyph<T>],
+    new: usize,
+) -> Vec<KerningPair> {
+    // the indices in the metadata of each glyph of the face, which may be
+    // there more than once, such as in different colors
+    let mut indices: BTreeMap<GlyphId, Vec<usize>> = BTreeMap::new();
+    for (index, glyph) in glyphs.iter().enumerate() {
+        if glyph.face == handle {
+            indices.entry(glyph.glyph_id).or_default().push(index);
+        }
+    }
+    let ids: Vec<GlyphId> = indices.keys().copied().collect();
+    let units = match gpos_pairs(face) {
+        Some(lookups) => gpos_kerning(&lookups, &ids),
+        None => kern_kerning(face, &ids),
+    };
+    let units_per_em = f32::from(face.units_per_em());
+    let mut pairs = Vec::new();
+    for ((left_id, right_id), units) in units {
+        if units == 0 {
+            continue;
+        }
+        for &left in &indices[&left_id] {
+            for &right in &indices[&right_id] {
+                if left < new && right < new {
+                    continue;
+                }
+                pairs.push(KerningPair {
+                    left: left as u32,
+                    right: right as u32,
+                    adjust: f32::from(units) / units_per_em,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// The pair adjustment subtables of each lookup of a face's `kern`
+/// feature, or `None` if it has no such feature
+fn gpos_pairs<'a>(face: &Face<'a>) -> Option<Vec<Vec<PairAdjustment<'a>>>> {
+    let gpos = face.tables().gpos?;
+    let mut indices: Vec<u16> = gpos
+        .features
+        .into_iter()
+        .filter(|feature| feature.tag == Tag::from_bytes(b"kern"))
+        .flat_map(|feature| feature.lookup_indices)
+        .collect();
+    if indices.is_empty() {
+        return None;
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    let lookups = indices
+        .into_iter()
+        .filter_map(|index| gpos.lookups.get(index))
+        .map(|lookup| {
+            lookup
+                .subtables
+                .into_iter::<PositioningSubtable>()
+                .filter_map(|subtable| match subtable {
+                    PositioningSubtable::Pair(pairs) => Some(pairs),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect();
+    Some(lookups)
+}
+
+/// The kerning in font units between each pair of `ids`, sorted, which the
+/// lookups give any, summed over the lookups.  Within a lookup, the first
+/// subtable which covers a pair gives its kerning.
+fn gpos_kerning(
+    lookups: &[Vec<PairAdjustment<'_>>],
+    ids: &[GlyphId],
+) -> BTreeMap<(GlyphId, GlyphId), i16> {
+    let mut units: BTreeMap<(GlyphId, GlyphId), i16> = BTreeMap::new();
+    for subtables in lookups {
+        let mut found = BTreeMap::new();
+        // glyphs which an earlier subtable of classes covers on the left,
+        // and so gives the kerning of every pair they start
+        let mut covered = BTreeSet::new();
+        for subtable in subtables {
+            match subtable {
+                PairAdjustment::Format1 { coverage, sets } => {
+                    // the pairs of each set can't be listed, so look up the
+                    // glyphs on the right, for only the glyphs it covers on
+                    // the left
+                    for &left in ids {
+                        let Some(set) = coverage.get(left).and_then(|index| sets.get(index)) else {
+                            continue;
+                        };
+                        if covered.contains(&left) {
+                            continue;
+                        }
+                        for &right in ids {
+                            if let Some((record, _)) = set.get(right) {
+                                found.entry((left, right)).or_insert(record.x_advance);
+                            }
+                        }
+                    }
+                }
+                PairAdjustment::Format2 {
+                    coverage,
+                    classes,
+                    matrix,
+                } => {
+                    // kern every glyph of one class by every glyph of the
+                    // other, for each pair of classes with kerning
+                    let mut lefts: BTreeMap<u16, Vec<GlyphId>> = BTreeMap::new();
+                    for &left in ids {
+                        if coverage.contains(left) && covered.insert(left) {
+                            lefts.entry(classes.0.get(left)).or_default().push(left);
+                        }
+                    }
+                    let mut rights: BTreeMap<u16, Vec<GlyphId>> = BTreeMap::new();
+                    for &right in ids {
+                        rights.entry(classes.1.get(right)).or_default().push(right);
+                    }
+                    for (&left_class, lefts) in &lefts {
+                        for (&right_class, rights) in &rights {
+                            let Some((record, _)) = matrix.get((left_class, right_class)) else {
+                                continue;
+                            };
+                            if record.x_advance == 0 {
+                                continue;
+                            }
+                            for &left in lefts {
+                                for &right in rights {
+                                    found.entry((left, right)).or_insert(record.x_advance);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (pair, adjust) in found {
+            let sum = units.entry(pair).or_insert(0);
+            *sum = sum.saturating_add(adjust);
+        }
+    }
+    units
+}
+
+/// The kerning in font units between each pair of `ids`, sorted, which the
+/// horizontal subtables of the `kern` table which give plain pairs give
+/// any, summed over the subtables
+fn kern_kerning(face: &Face<'_>, ids: &[GlyphId]) -> BTreeMap<(GlyphId, GlyphId), i16> {
+    let mut units: BTreeMap<(GlyphId, GlyphId), i16> = BTreeMap::new();
+    let Some(kern) = face.tables().kern else {
+        return units;
+    };
+    let subtables = kern.subtables.into_iter().filter(|subtable| {
+        subtable.horizontal
+            && !subtable.variable
+            && !subtable.has_cross_stream
+            && !subtable.has_state_machine
+    });
+    for subtable in subtables {
+        let mut add = |pair: (GlyphId, GlyphId), adjust: i16| {
+            let sum = units.entry(pair).or_insert(0);
+            *sum = sum.saturating_add(adjust);
+        };
+        match &subtable.format {
+            Format::Format0(list) => {
+                for pair in list.pairs {
+                    let (left, right) = (pair.left(), pair.right());
+                    if ids.binary_search(&left).is_ok() && ids.binary_search(&right).is_ok() {
+                        add((left, right), pair.value);
+                    }
+                }
+            }
+            _ => {
+                // the classes of these formats can't be listed, so look up
+                // every pair, but they are rare outside old Apple fonts
+                for &left in ids {
+                    for &right in ids {
+                        if let Some(adjust) = subtable.glyphs_kerning(left, right) {
+                            add((left, right), adjust);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTO: &[u8] = include_bytes!("../examples/roboto/Roboto-Regular.ttf");
+
+    /// The glyphs of Roboto's Latin 1 chars, sorted
+    fn latin1_ids(face: &Face<'_>) -> Vec<GlyphId> {
+        let mut ids: Vec<GlyphId> = crate::latin1()
+            .filter_map(|c| face.glyph_index(c))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// The kerning of one pair, looked up the slow way
+    fn gpos_pair(lookups: &[Vec<PairAdjustment<'_>>], left: GlyphId, right: GlyphId) -> i16 {
+        lookups
+            .iter()
+            .filter_map(|subtables| {
+                subtables.iter().find_map(|subtable| match subtable {
+                    PairAdjustment::Format1 { coverage, sets } => {
+                        let set = sets.get(coverage.get(left)?)?;
+                        Some(set.get(right)?.0.x_advance)
+                    }
+                    PairAdjustment::Format2 {
+                        coverage,
+                        classes,
+                        matrix,
+                    } => {
+                        if !coverage.contains(left) {
+                            return None;
+                        }
+                        let pair = (classes.0.get(left), classes.1.get(right));
+                        Some(matrix.get(pair)?.0.x_advance)
+                    }
+                })
+            })
+            .fold(0, i16::saturating_add)
+    }
+
+    #[test]
+    fn gpos_matches_every_pair() {
+        let face = Face::parse(ROBOTO, 0).unwrap();
+        let ids = latin1_ids(&face);
+        let lookups = gpos_pairs(&face).unwrap();
+        let units = gpos_kerning(&lookups, &ids);
+        for &left in &ids {
+            for &right in &ids {
+                let found = units.get(&(left, right)).copied().unwrap_or(0);
+                assert_eq!(
+                    found,
+                    gpos_pair(&lookups, left, right),
+                    "{left:?} {right:?}"
+                );
+            }
+        }
+        let pair = |a, b| (face.glyph_index(a).unwrap(), face.glyph_index(b).unwrap());
+        assert!(units[&pair('A', 'V')] < 0);
+        assert!(units[&pair('T', 'o')] < 0);
+    }
+}
@@ -3,28 +3,320 @@
 
 //! You can use this crate to generate an image atlas containing a signed
 //! distance field of glyphs from a font.
+//!
+//! Fonts are given as [`ttf_parser::Face`]s, which borrow the font file
+//! rather than copying it, and nothing here copies it either: outlines are
+//! read from the borrowed bytes as each glyph is measured and rendered, and
+//! a [`DynamicSdfAtlas`] keeps only a small fingerprint of each face.  So a
+//! large font, such as a CJK font of hundreds of megabytes, can be parsed
+//! straight from a memory-mapped file, with only the parts holding the
+//! requested glyphs ever read.
 
 #![warn(missing_docs)]
 
 pub extern crate ttf_parser;
 
+mod append;
+mod band;
 mod bisect;
+#[cfg(feature = "bitmap")]
+mod bitmap;
+mod bleed;
+mod borders;
+mod capabilities;
+mod channel_pack;
+mod charset;
+mod color;
+mod contour;
+mod dynamic;
 mod edge;
+mod edt;
+#[cfg(feature = "effects")]
+pub mod effects;
+mod face;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+mod grid;
+mod kerning;
+mod lookup;
 mod math;
+mod mip;
+mod normalize;
+mod overlap;
+mod packer;
+mod plan;
 mod raster;
+#[cfg(feature = "reference")]
+pub mod reference;
+mod reserved;
+mod rows;
+mod sequence;
+mod settings;
+mod shelf;
+mod split;
+#[cfg(feature = "svg")]
+mod svg;
+pub mod synthetic;
+mod tex_transform;
+mod view;
 
-use ttf_parser::Face;
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
-use crate::{bisect::BisectArgs, raster::RasteredSize};
+use ttf_parser::{Face, GlyphId};
+
+pub use crate::{
+    band::BandMask,
+    capabilities::{capabilities, Capabilities},
+    charset::{Chars, Charset},
+    color::palette_color,
+    contour::{ContourAreas, Orientation},
+    dynamic::DynamicSdfAtlas,
+    edge::{cubic_to_quads, quad_to_cubic},
+    face::{DecorationMetrics, FaceHandle, FontMetrics},
+    grid::GridPacker,
+    kerning::KerningPair,
+    lookup::CodepointIndex,
+    mip::MipLevel,
+    packer::{Packer, PackerItem, PackerPlacement, TightPacker},
+    plan::{AssetPlan, BuildStats, FontWarning, GlyphTiming, LayerImage, Placement, FAR_PAST_EM},
+    reserved::ReservedRect,
+    sequence::{sequence_fallback, SequenceFallback},
+    settings::AssetSettings,
+    shelf::ShelfPacker,
+    tex_transform::TexTransform,
+    view::{GlyphRecord, MetadataView},
+};
+
+use crate::{
+    bisect::{BisectArgs, PackArgs, Packing},
+    face::FaceFingerprint,
+    raster::RasteredSize,
+};
 
 /// Knobs and dials for asset generation
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct FontAssetBuilder {
     size: AssetSize,
-    padding: f32,
+    padding: Padding,
     allow_rotate: bool,
+    packer: Arc<dyn Packer + Send + Sync>,
+    gutter: u16,
+    reserved: Vec<(u16, u16, u32)>,
+    aliases: Vec<(char, char)>,
+    layers: u16,
+    shape: AtlasShape,
+    backend: RasterBackend,
+    norm: DistanceNorm,
+    mode: DistanceMode,
+    normalize_gradient: bool,
+    missing: MissingGlyphPolicy,
+    bold: f32,
+    simplify: f32,
+    variations: raster::Variations,
+    snap: Option<f32>,
+    sample: SamplePosition,
+    coverage: bool,
+    subpixel: bool,
+    channel_pack: bool,
+    bleed: bool,
+    mipmaps: bool,
+    trim: Option<u16>,
+    trim_borders: bool,
+    max_texture_size: u16,
+    size_bounds: Option<(f32, f32)>,
+    size_attempts: u32,
+    filter: Option<Filter>,
+    row_alignment: usize,
+    inset_tex: bool,
+    empty: EmptyGlyphsPolicy,
+    origin: UvOrigin,
+    kerning: bool,
+    threads: usize,
+}
+
+/// The method used to compute the distance stored in each texel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RasterBackend {
+    /// Search the glyph outline for the nearest point to every texel.  This
+    /// is the most accurate method, and the default.  It relies on contours
+    /// not overlapping, so glyphs made of layered shapes, such as those from
+    /// the `SVG ` table, are better rendered with
+    /// [`Approximate`](Self::Approximate).
+    Exact,
+
+    /// Rasterize each glyph as a binary mask at `oversample` times the
+    /// resolution of the output, and derive distances from the mask using a
+    /// euclidean distance transform.  This is much faster for glyphs with
+    /// many curves, at the cost of accuracy on the order of a mask cell;
+    /// oversample values of 4 to 8 are a good tradeoff.
+    Approximate {
+        /// The factor by which the mask resolution exceeds the output
+        oversample: u8,
+    },
+}
+
+/// The metric used to measure the distance from a texel to the glyph outline
+///
+/// With [`RasterBackend::Exact`], the Chebyshev and Manhattan distances are
+/// approximate: each texel holds the norm of its offset to the point of the
+/// outline nearest in straight line terms, rather than the least norm of
+/// its offset to any point of the outline.  This is the true distance around
+/// axis-aligned rectangles, but elsewhere can be up to √2 times the true
+/// distance in the norm, and is never less.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DistanceNorm {
+    /// Straight line distance, which gives smooth, rounded contours.  This is
+    /// the default, and the only norm which scales cleanly in a shader.
+    Euclidean,
+
+    /// The greater of the horizontal and vertical distance, which gives
+    /// square contours around corners.
+    Chebyshev,
+
+    /// The sum of the horizontal and vertical distance, which gives diamond
+    /// shaped contours around corners.
+    Manhattan,
+}
+
+/// How glyphs are arranged in the atlas.  To use another algorithm, see
+/// [`FontAssetBuilder::with_packer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PackingStrategy {
+    /// Fit glyphs together as closely as possible.  This is the default.
+    Tight,
+
+    /// Place glyphs in rows, tallest first.  This wastes more space,
+    /// especially when glyph heights vary widely, but packing is much faster
+    /// and allocates little, which suits building atlases at run time.
+    Shelf,
+
+    /// Place every glyph in an identical cell, in the order requested, like
+    /// a grid of terminal characters.  This wastes the most space, but a
+    /// glyph's place can be found from its index alone; see [`GridPacker`].
+    Grid,
+}
+
+/// The proportions of the atlas when its size is chosen to fit the glyphs,
+/// see [`FontAssetBuilder::with_atlas_shape`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum AtlasShape {
+    /// The width and height are equal.  This is the default.
+    Square,
+
+    /// The width divided by the height is as close as possible to this
+    /// ratio.
+    AspectRatio(f32),
+
+    /// Choose whichever proportions, between 4:1 and 1:4, give the smallest
+    /// area.  This packs the glyphs several times over, so it is slower
+    /// than a fixed shape.
+    Free,
+}
+
+/// Where in each texel the distance is sampled, relative to the glyph's
+/// bounding box
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SamplePosition {
+    /// Texels tile the bounding box, and each holds the distance at its
+    /// center.  The texture coordinates of a glyph cover its texels edge to
+    /// edge.  This is the default.
+    #[default]
+    Center,
+
+    /// The outermost texels hold the distance at the edges of the bounding
+    /// box, so the texture coordinates of a glyph run from the center of its
+    /// first texel to the center of its last.  This matches renderers that
+    /// treat texel values as the corners of a grid, and keeps a glyph one
+    /// texel wide from collapsing to a single sample in the middle.
+    Corner,
+}
+
+/// Where texture coordinates start from, and which row of the image comes
+/// first in its data, see [`FontAssetBuilder::with_uv_origin`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UvOrigin {
+    /// `v` runs up from the bottom of the image, and the bottom row comes
+    /// first, as OpenGL expects.  This is the default.
+    #[default]
+    BottomLeft,
+
+    /// `v` runs down from the top of the image, and the top row comes
+    /// first, as Vulkan, Direct3D, Metal, and wgpu expect, and as image
+    /// files such as PNGs are stored.
+    TopLeft,
 }
 
+/// What the value of each texel measures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DistanceMode {
+    /// The signed distance to the outline, which is positive inside the
+    /// glyph.  The outline is at a value of 0.5, and values fall off to 0
+    /// outside and rise to 1 inside.  This is the default.
+    Signed,
+
+    /// The distance to the outline regardless of which side of it the texel
+    /// is on, for rendering strokes and engraving effects.  The outline is at
+    /// a value of 1, and values fall off to 0 on both sides, so a stroke of
+    /// width `w` pixels covers values above `1 - w / (2 * spread_px)`.
+    Unsigned,
+}
+
+/// What to do when the font has no glyph for a request, see
+/// [`FontAssetBuilder::with_missing_glyphs`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MissingGlyphPolicy {
+    /// Fail the build with [`Error::MissingGlyph`].  This is the default.
+    Fail,
+
+    /// Leave the glyph out of the asset, and list it in
+    /// [`SdfFontAsset::skipped`].
+    Skip,
+
+    /// Render the font's `.notdef` glyph, usually a box, in its place.  The
+    /// glyph's metadata keeps the requested key.
+    NotDef,
+}
+
+/// What to build when there are no glyphs to render, see
+/// [`FontAssetBuilder::with_empty_glyphs`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EmptyGlyphsPolicy {
+    /// Build an asset with no glyphs.  If the size of the image is chosen
+    /// to fit the glyphs, it is 2 texels along each side it is free to
+    /// choose, unless there are reserved rects, which it is chosen to fit
+    /// as usual.
+    /// If the font size is chosen to fit the glyphs, it is the lower
+    /// [bound](FontAssetBuilder::with_font_size_bounds) if one was given,
+    /// or else the height of the image, the size at which one em fills it,
+    /// which glyphs [appended](SdfFontAsset::append) later are rendered at.
+    /// This is the default.
+    Minimal,
+
+    /// Fail the build with [`Error::NoGlyphs`]
+    Fail,
+}
+
+// keep concurrent builds possible, see `FontAssetBuilder::build`
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<FontAssetBuilder>();
+    assert_send_sync::<GlyphRequest<'static, ()>>();
+    assert_send_sync::<AssetPlan<'static, ()>>();
+    assert_send_sync::<SdfFontAsset<()>>();
+    assert_send_sync::<Error>();
+};
+
 /// The result of asset generation
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -35,26 +327,132 @@ pub struct SdfFontAsset<T> {
     /// The height of the resulting image in pixels
     pub height: u16,
 
-    /// The raw image data
+    /// The number of layers in the image, each `width` by `height`, which
+    /// follow each other in [`data`](Self::data).  This is 1 unless
+    /// [`with_layers`](FontAssetBuilder::with_layers) was used.
+    pub layers: u16,
+
+    /// The distance, in pixels at the rendered font size, from a glyph's
+    /// outline to where the distance field reaches its minimum or maximum
+    /// value
+    pub spread_px: f32,
+
+    /// The font size glyphs were rendered at, in pixels per em.  With
+    /// [`with_texture_size`](FontAssetBuilder::with_texture_size) this is
+    /// the largest size found at which every glyph fits.  Dividing
+    /// [`spread_px`](Self::spread_px) by it gives the spread in ems, to
+    /// work out how many screen pixels the distance field spans at the size
+    /// text is drawn.
+    pub font_size: f32,
+
+    /// What the value of each texel measures
+    pub mode: DistanceMode,
+
+    /// The number of bytes per texel.  This is 1 for the distance field
+    /// alone, 2 with
+    /// [`with_coverage_channel`](FontAssetBuilder::with_coverage_channel),
+    /// where each distance is followed by the texel's coverage, or 4 with
+    /// [`with_subpixel_coverage`](FontAssetBuilder::with_subpixel_coverage),
+    /// where it is followed by the coverage of each RGB subpixel.  With
+    /// [`with_channel_packing`](FontAssetBuilder::with_channel_packing) it
+    /// is also 4, each channel holding the distances of different glyphs.
+    pub channels: u8,
+
+    /// The raw image data, row by row from the bottom up, or from the top
+    /// down with [`UvOrigin::TopLeft`], with the layers following each
+    /// other.  Rows are
+    /// [`row_stride`](Self::row_stride) bytes apart.
     pub data: Vec<u8>,
 
-    /// A list of metadata for the rendered glyphs
+    /// The number of bytes from the start of one row of
+    /// [`data`](Self::data) to the next.  This is the width times
+    /// [`channels`](Self::channels), rounded up to a multiple of the
+    /// alignment given to
+    /// [`with_row_alignment`](FontAssetBuilder::with_row_alignment), and
+    /// the padding at the end of each row is zero.
+    pub row_stride: usize,
+
+    /// The mip levels below the full size image, from largest to smallest,
+    /// if [`with_mipmaps`](FontAssetBuilder::with_mipmaps) was used
+    pub mip_levels: Vec<MipLevel>,
+
+    /// A list of metadata for the rendered glyphs, in the order they were
+    /// requested, whatever order they were packed or rendered in.  Requests
+    /// which were left out, see [`skipped`](Self::skipped), or repeated
+    /// have no entry, so to match glyphs with requests compare their
+    /// [`user_data`](Glyph::user_data) or [`key`](Glyph::key).
     pub metadata: Vec<Glyph<T>>,
+
+    /// Where the rects given to
+    /// [`with_reserved_rects`](FontAssetBuilder::with_reserved_rects) were
+    /// placed, in the order they were given
+    pub reserved: Vec<ReservedRect>,
+
+    /// The requested glyphs which were left out of the asset
+    pub skipped: Vec<SkippedGlyph<T>>,
+
+    /// The kerning between pairs of glyphs, if
+    /// [`with_kerning`](FontAssetBuilder::with_kerning) was used, sorted by
+    /// the index of the left glyph and then the right.  Pairs which aren't
+    /// listed have no kerning.  See [`pair_kerning`](Self::pair_kerning).
+    pub kerning: Vec<KerningPair>,
+
+    /// The number of empty texels left between glyphs, and between glyphs
+    /// and the bottom and left edges of the image
+    pub gutter: u16,
+
+    /// The faces glyphs were rendered from, in the order of their handles
+    faces: Vec<FaceFingerprint>,
+
+    /// The metrics of each face, in the order of their handles
+    metrics: Vec<FontMetrics>,
+
+    /// The settings the asset was built with, to render appended glyphs
+    /// the same way
+    builder: FontAssetBuilder,
+
+    /// Where each glyph in the metadata was rendered
+    rects: Vec<crunch::Rect>,
 }
 
 /// Possible errors that can happen while generating the image
 #[derive(Clone, Copy, Debug)]
 #[non_exhaustive]
 pub enum Error {
-    /// This error occurs if the library could not get
-    /// all the information it needs to render a glyph
-    /// from the font file.
-    MissingGlyph(char),
+    /// This error occurs if the font has no glyph
+    /// for a requested character or glyph id.
+    /// Glyphs with nothing to draw, such as spaces,
+    /// are not errors; see [`Glyph::is_empty`].
+    MissingGlyph(GlyphKey),
 
     /// This error occurs if too large a font size
     /// is specified to neatly pack the requested glyphs
     /// in a single texture
     PackingAtlasFailed,
+
+    /// This error occurs if a glyph's outline in the
+    /// font is malformed, such as one cut short or
+    /// with a bounding box turned inside out.
+    BadOutline(GlyphId),
+
+    /// This error occurs if the padding ratio or spread is negative, not a
+    /// number, or zero along both axes, or if a padding ratio is half the
+    /// font size or more.
+    InvalidPadding,
+
+    /// This error occurs if there are no glyphs to render, whether none
+    /// were requested or all were left out, and the builder was set to
+    /// [fail](EmptyGlyphsPolicy::Fail) in that case.
+    NoGlyphs,
+
+    /// This error occurs if a glyph asked of a [`DynamicSdfAtlas`] is left
+    /// out by the builder's [filter](FontAssetBuilder::with_filter).
+    FilteredGlyph(GlyphKey),
+
+    /// This error occurs if the GPU backend failed to create or render to
+    /// the resources it needs.
+    #[cfg(feature = "gpu")]
+    GpuFailure,
 }
 
 impl FontAssetBuilder {
@@ -64,8 +462,41 @@ impl FontAssetBuilder {
         assert!(width >= 2 && height >= 2);
         Self {
             size: AssetSize::TextureSize(width, height),
-            padding: 0.1,
+            padding: Padding::Ratio(0.1, 0.1),
             allow_rotate: false,
+            packer: Arc::new(TightPacker),
+            gutter: 1,
+            reserved: Vec::new(),
+            aliases: Vec::new(),
+            layers: 1,
+            shape: AtlasShape::Square,
+            backend: RasterBackend::Exact,
+            norm: DistanceNorm::Euclidean,
+            mode: DistanceMode::Signed,
+            normalize_gradient: false,
+            missing: MissingGlyphPolicy::Fail,
+            bold: 0.0,
+            simplify: 0.0,
+            variations: raster::Variations::new(&[]),
+            snap: None,
+            sample: SamplePosition::Center,
+            coverage: false,
+            subpixel: false,
+            channel_pack: false,
+            bleed: false,
+            mipmaps: false,
+            trim: None,
+            trim_borders: false,
+            max_texture_size: u16::MAX,
+            size_bounds: None,
+            size_attempts: 11,
+            filter: None,
+            row_alignment: 1,
+            inset_tex: false,
+            empty: EmptyGlyphsPolicy::Minimal,
+            origin: UvOrigin::BottomLeft,
+            kerning: false,
+            threads: 1,
         }
     }
 
@@ -76,21 +507,139 @@ impl FontAssetBuilder {
         assert!(font_size > 0.0);
         Self {
             size: AssetSize::FontSize(font_size),
-            padding: 0.1,
+            padding: Padding::Ratio(0.1, 0.1),
             allow_rotate: false,
+            packer: Arc::new(TightPacker),
+            gutter: 1,
+            reserved: Vec::new(),
+            aliases: Vec::new(),
+            layers: 1,
+            shape: AtlasShape::Square,
+            backend: RasterBackend::Exact,
+            norm: DistanceNorm::Euclidean,
+            mode: DistanceMode::Signed,
+            normalize_gradient: false,
+            missing: MissingGlyphPolicy::Fail,
+            bold: 0.0,
+            simplify: 0.0,
+            variations: raster::Variations::new(&[]),
+            snap: None,
+            sample: SamplePosition::Center,
+            coverage: false,
+            subpixel: false,
+            channel_pack: false,
+            bleed: false,
+            mipmaps: false,
+            trim: None,
+            trim_borders: false,
+            max_texture_size: u16::MAX,
+            size_bounds: None,
+            size_attempts: 11,
+            filter: None,
+            row_alignment: 1,
+            inset_tex: false,
+            empty: EmptyGlyphsPolicy::Minimal,
+            origin: UvOrigin::BottomLeft,
+            kerning: false,
+            threads: 1,
+        }
+    }
+
+    /// Define both the image dimensions and the font size.  If the glyphs
+    /// don't all fit, the ones of lowest
+    /// [priority](GlyphRequest::with_priority) are left out until the rest
+    /// do, and reported in [`skipped`](SdfFontAsset::skipped) as
+    /// [`SkipReason::DidNotFit`].  Of glyphs with the same priority, those
+    /// requested last are left out first.
+    pub fn with_fixed_size(width: u16, height: u16, font_size: f32) -> Self {
+        assert!(width >= 2 && height >= 2);
+        Self {
+            size: AssetSize::Fixed(width, height, font_size),
+            ..Self::with_font_size(font_size)
+        }
+    }
+
+    /// Define the width of the image and the font size.  The height of the
+    /// image will be chosen to fit all glyphs at the provided size, for
+    /// textures which can grow vertically, such as ones streamed in a row
+    /// at a time.
+    pub fn with_fixed_width(width: u16, font_size: f32) -> Self {
+        assert!(width >= 2);
+        Self {
+            size: AssetSize::FixedWidth(width, font_size),
+            ..Self::with_font_size(font_size)
         }
     }
 
     /// Define the ratio of the distance field to the size of the glyph.  For
     /// example, a 16px glyph with a padding ratio of 0.25 render such that the
     /// signed distance field measures -4 to +4 pixels.
+    ///
+    /// The ratio must be at least zero and less than 0.5, or building fails
+    /// with [`Error::InvalidPadding`].
     pub fn with_padding_ratio(self, padding: f32) -> Self {
-        Self { padding, ..self }
+        Self {
+            padding: Padding::Ratio(padding, padding),
+            ..self
+        }
+    }
+
+    /// Define separate horizontal and vertical padding ratios, for effects
+    /// which need to extend further along one axis than the other, such as
+    /// drop shadows or underlines.  The distance field is normalized to the
+    /// larger of the two, so the smaller padding clips the field early
+    /// rather than compressing it.
+    ///
+    /// Each ratio must be at least zero and less than 0.5, and at least one
+    /// must be more than zero, or building fails with
+    /// [`Error::InvalidPadding`].
+    pub fn with_padding_ratios(self, padding_x: f32, padding_y: f32) -> Self {
+        Self {
+            padding: Padding::Ratio(padding_x, padding_y),
+            ..self
+        }
+    }
+
+    /// Define a separate padding ratio for each side of a glyph, for atlases
+    /// baked for an effect in a known direction, such as a drop shadow
+    /// offset right and down, which only needs the field to reach further
+    /// on the right and bottom.  As with
+    /// [`with_padding_ratios`](Self::with_padding_ratios), the distance
+    /// field is normalized to the largest of them, and the smaller ones
+    /// clip the field early.
+    ///
+    /// Each ratio must be at least zero and less than 0.5, and at least one
+    /// must be more than zero, or building fails with
+    /// [`Error::InvalidPadding`].
+    pub fn with_padding_sides(self, left: f32, right: f32, bottom: f32, top: f32) -> Self {
+        Self {
+            padding: Padding::Sides([left, right, bottom, top]),
+            ..self
+        }
+    }
+
+    /// Define the range of the distance field as an absolute number of pixels
+    /// at the rendered font size, instead of as a ratio.  This is most useful
+    /// with [`with_texture_size`](Self::with_texture_size), where the font
+    /// size is not known in advance; the spread will be the same regardless of
+    /// the font size chosen.
+    ///
+    /// The spread must be more than zero, or building fails with
+    /// [`Error::InvalidPadding`].  If the font size chosen is so small that
+    /// the spread would be half of it or more, the spread is reduced to just
+    /// under half the font size, as if by
+    /// [`with_padding_ratio`](Self::with_padding_ratio).
+    pub fn with_spread_px(self, spread: f32) -> Self {
+        Self {
+            padding: Padding::Pixels(spread, spread),
+            ..self
+        }
     }
 
     /// Use this to allow rotating glyphs, which may make the atlas packing more
     /// optimal but requires more attention when decoding the resulting texture
-    /// coordinates.
+    /// coordinates.  Individual glyphs can override this with
+    /// [`GlyphRequest::with_rotation`].
     pub fn allow_rotating_glyphs(self) -> Self {
         Self {
             allow_rotate: true,
@@ -98,92 +647,1449 @@ impl FontAssetBuilder {
         }
     }
 
+    /// Choose how glyphs are arranged in the atlas, see
+    /// [`PackingStrategy`].
+    pub fn with_packing(self, packing: PackingStrategy) -> Self {
+        let packer: Arc<dyn Packer + Send + Sync> = match packing {
+            PackingStrategy::Tight => Arc::new(TightPacker),
+            PackingStrategy::Shelf => Arc::new(ShelfPacker),
+            PackingStrategy::Grid => Arc::new(GridPacker),
+        };
+        Self { packer, ..self }
+    }
+
+    /// Arrange glyphs in the atlas with a packer of your own, such as a
+    /// skyline or guillotine packer, instead of one of the built in
+    /// [`PackingStrategy`]s.  The packer is shared by clones of the
+    /// builder and the assets built with it, so it may hold state of its
+    /// own, such as sizes read from settings.
+    pub fn with_packer(self, packer: Arc<dyn Packer + Send + Sync>) -> Self {
+        Self { packer, ..self }
+    }
+
+    /// Leave `texels` empty texels between glyphs, instead of 1.  A wider
+    /// gutter lets more mip levels be sampled without glyphs bleeding into
+    /// each other, see [`SdfFontAsset::max_mip_level`].
+    pub fn with_gutter(self, texels: u16) -> Self {
+        Self {
+            gutter: texels,
+            ..self
+        }
+    }
+
+    /// Set aside space in the atlas for content of your own, such as a white
+    /// texel or icons, so it can share a texture with the text.  Each rect is
+    /// given as its width, height, and a key to find it by in
+    /// [`SdfFontAsset::reserved`].  Rects are packed with the glyphs, never
+    /// rotated, and kept apart from them by the gutter; they are left
+    /// zeroed, to be drawn into after building.
+    ///
+    /// The rects are copied into the builder, so they may be worked out at
+    /// run time.
+    pub fn with_reserved_rects(self, rects: &[(u16, u16, u32)]) -> Self {
+        Self {
+            reserved: rects.to_vec(),
+            ..self
+        }
+    }
+
+    /// Let codepoints be found by the glyph of another, without rendering
+    /// a tile of their own, such as a no-break space drawn as a space, or
+    /// fullwidth forms drawn as ASCII.  Each pair is an alias and the
+    /// codepoint whose glyph it shares, for example `('\u{a0}', ' ')`.
+    ///
+    /// The aliases are copied, so they may be loaded at run time, such as
+    /// from configuration.  They are kept in the asset, see
+    /// [`SdfFontAsset::aliases`], and lookups by codepoint find an alias's
+    /// glyph through them: the
+    /// [`codepoint_index`](SdfFontAsset::codepoint_index) and the
+    /// [`codepoint_table`](SdfFontAsset::codepoint_table).  An alias is
+    /// only found if the codepoint it points to has a glyph in the asset,
+    /// and a codepoint with a glyph of its own ignores any alias.
+    pub fn with_aliases(self, aliases: &[(char, char)]) -> Self {
+        Self {
+            aliases: aliases.to_vec(),
+            ..self
+        }
+    }
+
+    /// Choose the proportions of the atlas, see [`AtlasShape`].  This only
+    /// has an effect when the size of the atlas is chosen to fit the glyphs,
+    /// with [`with_font_size`](Self::with_font_size).
+    pub fn with_atlas_shape(self, shape: AtlasShape) -> Self {
+        if let AtlasShape::AspectRatio(ratio) = shape {
+            assert!(ratio.is_finite() && ratio > 0.0);
+        }
+        Self { shape, ..self }
+    }
+
+    /// Keep the atlas chosen by [`with_font_size`](Self::with_font_size) to
+    /// at most `max` texels along each side, or the height chosen by
+    /// [`with_fixed_width`](Self::with_fixed_width) to at most `max`
+    /// texels, as GPUs can only load textures up to a limit, commonly 8192
+    /// or 16384.  If the glyphs don't fit at
+    /// that size, building fails with [`Error::PackingAtlasFailed`].  To
+    /// hold more glyphs than fit in one texture, use
+    /// [`with_texture_size`](Self::with_texture_size) with
+    /// [`with_layers`](Self::with_layers) instead.
+    pub fn with_max_texture_size(self, max: u16) -> Self {
+        assert!(max >= 2);
+        Self {
+            max_texture_size: max,
+            ..self
+        }
+    }
+
+    /// Select the method used to compute distances, see [`RasterBackend`].
+    pub fn with_raster_backend(self, backend: RasterBackend) -> Self {
+        Self { backend, ..self }
+    }
+
+    /// Select the metric used to measure distances, see [`DistanceNorm`].
+    /// Non-euclidean norms are a stylistic option for effects like blocky
+    /// outlines and glows.
+    pub fn with_distance_norm(self, norm: DistanceNorm) -> Self {
+        Self { norm, ..self }
+    }
+
+    /// Select what the value of each texel measures, see [`DistanceMode`].
+    pub fn with_distance_mode(self, mode: DistanceMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// Offset glyph outlines outward by `amount`, as a ratio of the font
+    /// size, to fake a bold weight when no bold face is available.  Glyph
+    /// bounding boxes grow to fit the thicker outline.  Negative amounts thin
+    /// the outline instead.
+    pub fn with_embolden(self, amount: f32) -> Self {
+        Self {
+            bold: amount,
+            ..self
+        }
+    }
+
+    /// Add a pass which rescales each glyph's distance field so its gradient
+    /// is close to one texel per texel throughout the range of the field.
+    /// Gradients are compressed near thin features, where the outlines on
+    /// either side are close together, and throughout fields computed with
+    /// [`RasterBackend::Approximate`]; normalizing them keeps the width of
+    /// anti-aliasing consistent when the shader derives it from the field.
+    /// Fields from [`RasterBackend::Exact`] are already close to normalized
+    /// except along ridges, which this pass leaves alone.
+    /// This pass has no effect with [`DistanceMode::Unsigned`].
+    pub fn normalize_gradients(self) -> Self {
+        Self {
+            normalize_gradient: true,
+            ..self
+        }
+    }
+
+    /// Set coordinates on the axes of variable fonts, such as
+    /// `(Tag::from_bytes(b"wght"), 700.0)`, so one variable font can produce
+    /// atlases at several weights or widths.  The coordinates are applied to
+    /// a copy of each requested face; axes a face lacks are ignored, as are
+    /// any past the 32nd.
+    pub fn with_variation(self, variations: &[(ttf_parser::Tag, f32)]) -> Self {
+        Self {
+            variations: raster::Variations::new(variations),
+            ..self
+        }
+    }
+
+    /// Choose what happens when the font has no glyph for a request, see
+    /// [`MissingGlyphPolicy`].
+    pub fn with_missing_glyphs(self, missing: MissingGlyphPolicy) -> Self {
+        Self { missing, ..self }
+    }
+
+    /// Choose what happens when there are no glyphs to render, because none
+    /// were requested or all were left out, see [`EmptyGlyphsPolicy`].
+    pub fn with_empty_glyphs(self, empty: EmptyGlyphsPolicy) -> Self {
+        Self { empty, ..self }
+    }
+
+    /// Leave out glyphs for which `keep` returns false, such as those in a
+    /// private use area, or with outlines too complex to be worth their
+    /// build time.  `keep` is given the face, the requested char if the
+    /// glyph was requested by char, and the glyph the request resolved to,
+    /// which is `.notdef` for chars the face lacks under
+    /// [`MissingGlyphPolicy::NotDef`].  It's called before glyphs are
+    /// measured, and glyphs left out are reported as skipped, with
+    /// [`SkipReason::Filtered`].
+    ///
+    /// `keep` may capture what it needs, such as a charset or a limit read
+    /// from settings.  It is shared by clones of the builder, so it must be
+    /// safe to call from any thread.
+    pub fn with_filter(
+        self,
+        keep: impl Fn(&Face<'_>, Option<char>, GlyphId) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            filter: Some(Filter(Arc::new(keep))),
+            ..self
+        }
+    }
+
+    /// Grow each glyph's bounding box so its edges land on pixel boundaries
+    /// when text is drawn at `size` pixels per em from a pen position on a
+    /// pixel boundary.  Quads then cover whole pixels at that size, which
+    /// avoids shimmer in pixel-perfect UI text, while the distance field
+    /// still scales smoothly to other sizes.
+    pub fn with_pixel_snapping(self, size: f32) -> Self {
+        Self {
+            snap: (size > 0.0).then_some(size),
+            ..self
+        }
+    }
+
+    /// Store the exact coverage of each texel at the atlas's font size
+    /// alongside the distance field, so text drawn at that size, one texel
+    /// to one pixel, can use the coverage directly while the distance field
+    /// serves other sizes.  Each texel of the asset then has two bytes, the
+    /// distance followed by the coverage, which is premultiplied alpha for
+    /// text of any color.  See [`SdfFontAsset::channels`].
+    pub fn with_coverage_channel(self) -> Self {
+        Self {
+            coverage: true,
+            subpixel: false,
+            channel_pack: false,
+            ..self
+        }
+    }
+
+    /// Like [`with_coverage_channel`](Self::with_coverage_channel), but
+    /// store the coverage of each of a texel's red, green, and blue
+    /// subpixels, for LCD screens whose subpixels are horizontal RGB
+    /// stripes.  Coverage is sampled at three times the horizontal
+    /// resolution and filtered across neighboring subpixels to keep color
+    /// fringes faint.  Each texel of the asset then has four bytes, the
+    /// distance followed by the red, green, and blue coverage, to blend per
+    /// channel, such as with dual source blending.  For BGR screens, swap
+    /// the red and blue coverage when drawing.
+    pub fn with_subpixel_coverage(self) -> Self {
+        Self {
+            coverage: true,
+            subpixel: true,
+            channel_pack: false,
+            ..self
+        }
+    }
+
+    /// Pack four pages of glyphs into each layer of the image, one in each
+    /// of its red, green, blue, and alpha channels, for four times the
+    /// glyphs in a texture of the same size, bound once.  Each glyph reports
+    /// its [`channel`](Glyph::channel), which shaders sample the distance
+    /// from.  Texels then have four bytes, see [`SdfFontAsset::channels`].
+    ///
+    /// A coverage channel can't be packed this way, so this replaces
+    /// [`with_coverage_channel`](Self::with_coverage_channel) and
+    /// [`with_subpixel_coverage`](Self::with_subpixel_coverage).
+    pub fn with_channel_packing(self) -> Self {
+        Self {
+            coverage: false,
+            subpixel: false,
+            channel_pack: true,
+            ..self
+        }
+    }
+
+    /// Fill the gutter and any unused space with copies of the nearest
+    /// glyph texel, so filtering and mipmapping near the edge of a glyph
+    /// blend in values like its own, instead of the empty background or a
+    /// neighboring glyph.  This applies to the coverage channel too, if
+    /// there is one.
+    pub fn with_edge_bleed(self) -> Self {
+        Self {
+            bleed: true,
+            ..self
+        }
+    }
+
+    /// Spread the glyphs over as many as `layers` images of the size given
+    /// to [`with_texture_size`](Self::with_texture_size), for use as a
+    /// texture array, so a small texture size can still hold a large
+    /// character set.  Layers are filled one after another, each with as
+    /// many of the remaining glyphs as fit, and the font size is chosen so
+    /// every glyph fits in some layer.  Each glyph reports its
+    /// [`layer`](Glyph::layer).
+    ///
+    /// Layers also apply to [`with_fixed_size`](Self::with_fixed_size).
+    /// This has no effect with [`with_font_size`](Self::with_font_size),
+    /// where the atlas grows to fit the glyphs instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layers` is 0, or more than 16383, so that the glyphs of
+    /// every layer can be counted even when
+    /// [channel packed](Self::with_channel_packing).
+    pub fn with_layers(self, layers: u16) -> Self {
+        assert!((1..=u16::MAX / channel_pack::PAGES_PER_TEXEL).contains(&layers));
+        Self { layers, ..self }
+    }
+
+    /// Search for the font size chosen by
+    /// [`with_texture_size`](Self::with_texture_size) between `lower`, a
+    /// size the glyphs are known to fit at, and `too_big`, a size they are
+    /// known not to, instead of around a size estimated from the area the
+    /// glyphs cover.  Bounds from a previous build of similar glyphs, such
+    /// as 10% either side of its font size, let a rebuild find the size in
+    /// fewer [attempts](Self::with_font_size_attempts).
+    ///
+    /// The bounds are not checked: if the glyphs don't fit at `lower`, the
+    /// search may fail with [`Error::PackingAtlasFailed`].
+    pub fn with_font_size_bounds(self, lower: f32, too_big: f32) -> Self {
+        assert!(lower > 0.0 && lower < too_big && too_big.is_finite());
+        Self {
+            size_bounds: Some((lower, too_big)),
+            ..self
+        }
+    }
+
+    /// Search for the font size chosen by
+    /// [`with_texture_size`](Self::with_texture_size) as finely as halving
+    /// the range it is searched in `attempts` times would, instead of 11.
+    /// With [bounds](Self::with_font_size_bounds), each attempt packs every
+    /// glyph and halves the range between them, and more attempts are made
+    /// if none of them fit.  Otherwise the range is 1 to 8 times the height
+    /// of the texture, but the search starts from a narrow range around a
+    /// size estimated from the area the glyphs cover, so it packs the glyphs
+    /// far fewer times.
+    pub fn with_font_size_attempts(self, attempts: u32) -> Self {
+        assert!(attempts >= 1);
+        Self {
+            size_attempts: attempts,
+            ..self
+        }
+    }
+
+    /// Shrink the atlas to the area the packed glyphs actually use, with
+    /// each dimension rounded up to a multiple of `alignment`.  This is
+    /// mostly useful with [`with_texture_size`](Self::with_texture_size),
+    /// where glyphs may not fill the requested texture; the size given there
+    /// then becomes an upper bound.
+    pub fn trim_to_content(self, alignment: u16) -> Self {
+        Self {
+            trim: Some(alignment.max(1)),
+            ..self
+        }
+    }
+
+    /// Shrink each glyph's tile by the rows and columns along its edges in
+    /// which every texel is at the far outside value.  The padded bounding
+    /// box leaves such texels around the corners of round glyphs, and
+    /// around glyphs whose bounding box in the font is loose, and most of
+    /// all when the outline is thinned with a negative
+    /// [`with_embolden`](Self::with_embolden) or its box is grown by
+    /// [`with_pixel_snapping`](Self::with_pixel_snapping).  The texels kept
+    /// are sampled at the same points as without trimming, and the glyph's
+    /// bounds in the metadata shrink to match.
+    ///
+    /// Finding the empty rows and columns renders each glyph once for every
+    /// font size tried, so this slows builds down, especially with
+    /// [`with_texture_size`](Self::with_texture_size).
+    pub fn trim_glyph_borders(self) -> Self {
+        Self {
+            trim_borders: true,
+            ..self
+        }
+    }
+
+    /// Also generate every smaller mip level of the atlas, down to 1 by 1,
+    /// in [`SdfFontAsset::mip_levels`], so the whole chain can be uploaded
+    /// at once.  Levels above [`SdfFontAsset::max_mip_level`] blend
+    /// neighboring glyphs together.
+    pub fn with_mipmaps(self) -> Self {
+        Self {
+            mipmaps: true,
+            ..self
+        }
+    }
+
+    /// Pad each row of the image, and of its mip levels, to a multiple of
+    /// `alignment` bytes, for upload paths which require it, such as
+    /// buffer to texture copies in wgpu, which need rows of a multiple of
+    /// 256 bytes.  See [`SdfFontAsset::row_stride`].
+    pub fn with_row_alignment(self, alignment: usize) -> Self {
+        assert!(alignment >= 1);
+        Self {
+            row_alignment: alignment,
+            ..self
+        }
+    }
+
+    /// Choose where distances are sampled within each texel, see
+    /// [`SamplePosition`].
+    pub fn with_sample_position(self, sample: SamplePosition) -> Self {
+        Self { sample, ..self }
+    }
+
+    /// Choose where texture coordinates start from, see [`UvOrigin`].
+    ///
+    /// Either way, uploading [`data`](SdfFontAsset::data) as it is puts
+    /// each glyph where its texture coordinates say, as graphics APIs take
+    /// the first row to be at the origin of their texture coordinates.  The
+    /// choice decides which way up the image is when viewed, such as when
+    /// it is saved to a PNG.  With [`UvOrigin::TopLeft`], every vertical
+    /// position in the asset is measured down from the top: the rows of the
+    /// image and its mip levels are stored top row first, and each glyph's
+    /// [`y`](Glyph::y) and each reserved rect's
+    /// [`y`](ReservedRect::y) is its top edge, measured from the top.
+    /// Glyphs keep their [`tex_top`](Glyph::tex_top) and
+    /// [`tex_bottom`](Glyph::tex_bottom) at their top and bottom edges, so
+    /// `tex_top` is the smaller.
+    pub fn with_uv_origin(self, origin: UvOrigin) -> Self {
+        Self { origin, ..self }
+    }
+
+    /// Look up the kerning between every pair of glyphs from the same face,
+    /// and keep the pairs which have any in [`SdfFontAsset::kerning`], so
+    /// simple renderers can kern text without a shaping engine.  Kerning is
+    /// read from the `kern` feature of the face's `GPOS` table, or if it
+    /// has none, from its `kern` table; contextual kerning, and kerning
+    /// which varies with the face's variations, is not included.
+    ///
+    /// Every pair is checked, so this takes time growing with the square of
+    /// the number of glyphs, and suits atlases of hundreds of glyphs rather
+    /// than thousands.
+    pub fn with_kerning(self) -> Self {
+        Self {
+            kerning: true,
+            ..self
+        }
+    }
+
+    /// Render glyphs on as many as `threads` threads at once.  Glyphs are
+    /// still measured and packed on the calling thread first.  Each glyph
+    /// is rendered into a tile of its own and copied into the image in the
+    /// order the glyphs were requested, so the asset, its metadata, and the
+    /// timings of [`raster_with_stats`](AssetPlan::raster_with_stats) are
+    /// the same whatever the number of threads.  The default is 1, which
+    /// renders on the calling thread; see
+    /// [`available_parallelism`](std::thread::available_parallelism) for a
+    /// number to use.
+    ///
+    /// This has no effect on `build_gpu`.
+    pub fn with_threads(self, threads: usize) -> Self {
+        assert!(threads >= 1);
+        Self { threads, ..self }
+    }
+
+    /// Report each glyph's texture coordinates half a texel in from the
+    /// edges of its tile, so bilinear sampling at the glyph's edges reads
+    /// only its own texels, never the gutter or a neighbouring glyph, which
+    /// can show as faint halos around glyphs.  Each glyph's bounding box
+    /// shrinks to match, by half a texel of padding on every side.  This has
+    /// no effect with [`SamplePosition::Corner`], whose coordinates are
+    /// already inset.
+    pub fn inset_tex_coords(self) -> Self {
+        Self {
+            inset_tex: true,
+            ..self
+        }
+    }
+
+    /// Simplify glyph outlines before rendering, so no point on the
+    /// rendered outline is further than `tolerance` from the original, as a
+    /// ratio of the font size.  Runs of nearly collinear lines are merged,
+    /// and small curves are replaced by lines or quadratic curves.  This
+    /// can greatly speed up ornamental fonts with thousands of edges per
+    /// glyph; a tolerance under half a texel, such as `0.25 / font_size`,
+    /// is rarely visible.
+    pub fn simplify_outlines(self, tolerance: f32) -> Self {
+        Self {
+            simplify: tolerance.max(0.0),
+            ..self
+        }
+    }
+
     /// Build a SDF font asset given a set of glyphs to include.
+    ///
+    /// A glyph requested more than once, from the same face with the same
+    /// color and transform, is only included once, with the `user_data` of
+    /// the first request.  This makes it safe to chain character sets which
+    /// overlap.
+    ///
+    /// Building is deterministic: no step of packing or rendering uses
+    /// randomness or the order of a hash table, so the same settings and
+    /// glyphs, in the same order, always produce an identical atlas.  This
+    /// holds across platforms too, as distances are computed with only the
+    /// arithmetic and square roots which IEEE 754 defines exactly, not with
+    /// functions such as `cbrt` whose rounding varies between platforms'
+    /// math libraries, so atlases may be cached by the hash of their inputs.
+    /// The exception is building with the `gpu` feature's `build_gpu`, whose
+    /// results depend on the GPU.
+    ///
+    /// Builds share no state besides the fonts they read from, so several
+    /// can run at once on different threads, borrowing the same
+    /// [`Face`](ttf_parser::Face) without locking or copying it.  This suits
+    /// building several sizes of one font in parallel.
+    ///
+    /// The whole image is held in memory until it is returned; to build
+    /// assets of many layers with only one layer of the image held at once,
+    /// see [`build_layers`](Self::build_layers).
     pub fn build<'a, T, I>(self, glyphs: I) -> Result<SdfFontAsset<T>, Error>
     where
         T: Clone,
         I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
     {
-        let (width, height, packing);
-        match self.size {
-            AssetSize::FontSize(font_size) => {
-                let (dim, packresult) =
-                    bisect::bisect_asset_size(font_size, self.padding, self.allow_rotate, &glyphs)?;
-                width = dim;
-                height = dim;
-                packing = packresult;
+        self.plan(glyphs)?.raster()
+    }
+
+    /// Build an asset like [`build`](Self::build), giving each layer of the
+    /// image to `each_layer` as soon as it is rendered, then dropping it.
+    /// This is the way to build with the least memory: only one layer of
+    /// the image is held at once, so the image's part of peak memory grows
+    /// with the size of a layer rather than the number of layers.  It suits
+    /// writing layers to disk or uploading them to a texture array as they
+    /// are made.
+    ///
+    /// The glyphs are still all measured and packed before any is rendered,
+    /// since the font size depends on every glyph, so the memory used while
+    /// packing is the same as for [`build`](Self::build).  Each layer's
+    /// packed glyphs are released once it is rendered, leaving only their
+    /// metadata.
+    ///
+    /// The returned asset has the metadata, but its
+    /// [`data`](SdfFontAsset::data) and
+    /// [`mip_levels`](SdfFontAsset::mip_levels) are empty.
+    pub fn build_layers<'a, T, I>(
+        self,
+        glyphs: I,
+        each_layer: impl FnMut(LayerImage),
+    ) -> Result<SdfFontAsset<T>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        self.plan(glyphs)?.raster_layers(each_layer)
+    }
+
+    /// Build an asset from a font stack: each char is rendered from the
+    /// first of `faces` which has a glyph for it, so later faces only fill
+    /// in what earlier ones lack.  Each glyph's `user_data` is the index in
+    /// `faces` of the face it was rendered from.  `chars` may be a
+    /// [`Charset`].
+    ///
+    /// Chars which no face has are requested from the first face, and
+    /// handled by the [missing glyph policy](Self::with_missing_glyphs).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `faces` is empty.
+    pub fn build_fallback<'a, I>(
+        self,
+        faces: &'a [&'a Face<'a>],
+        chars: I,
+    ) -> Result<SdfFontAsset<usize>, Error>
+    where
+        I: IntoIterator<Item = char>,
+        I::IntoIter: 'a + Clone,
+    {
+        assert!(!faces.is_empty());
+        self.build(chars.into_iter().map(move |ch| {
+            let index = faces
+                .iter()
+                .position(|face| face.glyph_index(ch).is_some())
+                .unwrap_or(0);
+            GlyphRequest::new(index, faces[index], ch)
+        }))
+    }
+
+    /// Measure and pack the glyphs without rendering them, so the layout can
+    /// be inspected or adjusted first.  Call [`AssetPlan::raster`] to finish
+    /// building the asset.
+    pub fn plan<'a, T, I>(self, glyphs: I) -> Result<AssetPlan<'a, T>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        self.pack(&glyphs, None)
+    }
+
+    /// Measure the glyphs at `font_size`, and place them in a `width` by
+    /// `height` atlas with a layout function of your own, instead of
+    /// searching for the size of the font or atlas.  Call
+    /// [`AssetPlan::raster`] to render the glyphs into the places chosen.
+    /// This suits layouts a [`Packer`] can't describe, such as spreading
+    /// glyphs over the layers of a texture array: plan each layer with the
+    /// glyphs it should hold.
+    ///
+    /// `layout` is given an item for each glyph, in order, followed by the
+    /// [reserved rects](Self::with_reserved_rects), and places them as
+    /// [`Packer::pack`] would in an area the width of the gutter smaller
+    /// than the atlas, since the atlas keeps a gutter along its left and
+    /// bottom edges.  If it returns `None`, planning fails with
+    /// [`Error::PackingAtlasFailed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the placements break the rules of [`Packer::pack`].
+    pub fn plan_with_layout<'a, T, I>(
+        self,
+        width: u16,
+        height: u16,
+        font_size: f32,
+        glyphs: I,
+        layout: impl FnOnce(&[PackerItem]) -> Option<Vec<PackerPlacement>>,
+    ) -> Result<AssetPlan<'a, T>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        assert!(width >= 2 && height >= 2);
+        assert!(font_size > 0.0);
+        let layout = CustomLayout {
+            width,
+            height,
+            pages: self.pages_per_layer(),
+            font_size,
+            place: Box::new(layout),
+        };
+        self.pack(&glyphs, Some(layout))
+    }
+
+    /// The pages of glyphs in each layer of the image, each in its own
+    /// channel
+    fn pages_per_layer(&self) -> u16 {
+        if self.channel_pack {
+            channel_pack::PAGES_PER_TEXEL
+        } else {
+            1
+        }
+    }
+
+    /// The bytes of coverage stored for each texel
+    fn coverage_bytes(&self) -> usize {
+        match (self.coverage, self.subpixel) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => 3,
+        }
+    }
+
+    fn raster_config(&self, font_size: f32) -> raster::RasterConfig {
+        raster::RasterConfig {
+            padding: self.padding.sides(font_size),
+            backend: self.backend,
+            norm: self.norm,
+            mode: self.mode,
+            bold: self.bold,
+            simplify: self.simplify,
+            variations: self.variations,
+            subpixel: self.subpixel,
+            careful: false,
+        }
+    }
+
+    /// The space on the left, right, bottom, and top of each glyph's
+    /// bounding box, as a ratio of the font size
+    fn margin(&self, font_size: f32) -> [f32; 4] {
+        let grow = self.bold.max(0.0);
+        self.padding.sides(font_size).map(|side| side + grow)
+    }
+
+    /// Measure the box a glyph will be rendered into at a font size
+    fn measure<T>(
+        &self,
+        font_size: f32,
+        request: &GlyphRequest<'_, T>,
+    ) -> Result<RasteredSize, Error> {
+        raster::get_rastered_size(
+            self.margin(font_size),
+            font_size,
+            &self.variations,
+            self.snap,
+            self.sample,
+            request,
+        )
+    }
+
+    /// Apply any post-processing passes to rendered layers of a plan, `data`
+    /// holding their pages stacked from the bottom up, and `glyphs` and
+    /// `reserved` their rects in those pages.  Returns the bytes per texel
+    /// before pages are packed into channels, the image, and its mip levels.
+    fn finish_pages<T>(
+        &self,
+        plan: &AssetPlan<'_, T>,
+        mut data: Vec<u8>,
+        coverage: Option<Vec<u8>>,
+        glyphs: &[crunch::Rect],
+        reserved: &[crunch::Rect],
+    ) -> (u8, Vec<u8>, Vec<MipLevel>) {
+        if self.normalize_gradient && self.mode == DistanceMode::Signed {
+            let spread_px = self.padding.spread(plan.font_size) * plan.font_size;
+            let mut buffer = raster::Buffer {
+                data: &mut data,
+                width: plan.width,
+            };
+            for rect in glyphs {
+                normalize::normalize_gradient(&mut buffer, rect, spread_px);
             }
-            AssetSize::TextureSize(w, h) => {
-                width = w;
-                height = h;
-                packing = bisect::bisect_font_size(
+        }
+        let (channels, data) = match coverage {
+            Some(coverage) => {
+                let per_texel = self.coverage_bytes();
+                (
+                    1 + per_texel as u8,
+                    data.into_iter()
+                        .zip(coverage.chunks_exact(per_texel))
+                        .flat_map(|(distance, coverage)| {
+                            std::iter::once(distance).chain(coverage.iter().copied())
+                        })
+                        .collect(),
+                )
+            }
+            None => (1, data),
+        };
+        let (data, mip_levels) =
+            self.finish_image(plan.width, plan.height, channels, data, glyphs, reserved);
+        (channels, data, mip_levels)
+    }
+
+    /// Bleed the edges of the glyphs and build the mip levels, if enabled,
+    /// from pages of `width` by `height` texels stacked from the bottom up,
+    /// and then pack the pages into channels, if enabled, and pad the rows
+    /// to the row alignment.  `glyphs` and `reserved` are rects in the
+    /// stacked pages.
+    fn finish_image(
+        &self,
+        width: u16,
+        height: u16,
+        channels: u8,
+        mut data: Vec<u8>,
+        glyphs: &[crunch::Rect],
+        reserved: &[crunch::Rect],
+    ) -> (Vec<u8>, Vec<MipLevel>) {
+        if self.bleed {
+            bleed::bleed_atlas(width, height, channels, &mut data, glyphs, reserved);
+        }
+        let mut mip_levels = if self.mipmaps {
+            mip::mip_chain(width, height, channels, &data)
+        } else {
+            Vec::new()
+        };
+        if self.channel_pack {
+            data = channel_pack::interleave(&data, usize::from(width) * usize::from(height));
+            for level in &mut mip_levels {
+                let page_len = usize::from(level.width) * usize::from(level.height);
+                level.data = channel_pack::interleave(&level.data, page_len);
+            }
+        }
+        let channels = channels * self.pages_per_layer() as u8;
+        self.arrange_rows(width, height, channels, data, mip_levels)
+    }
+
+    /// The bytes from the start of one row of an image `width` texels wide
+    /// to the next, see [`with_row_alignment`](Self::with_row_alignment)
+    fn row_stride(&self, width: u16, channels: u8) -> usize {
+        (usize::from(width) * usize::from(channels)).next_multiple_of(self.row_alignment)
+    }
+
+    /// Put the rows of a finished image and its mip levels in the order of
+    /// the UV origin, and pad them to the row alignment
+    fn arrange_rows(
+        &self,
+        width: u16,
+        height: u16,
+        channels: u8,
+        mut data: Vec<u8>,
+        mut mip_levels: Vec<MipLevel>,
+    ) -> (Vec<u8>, Vec<MipLevel>) {
+        let row_len = |width: u16| usize::from(width) * usize::from(channels);
+        if self.origin == UvOrigin::TopLeft {
+            rows::flip(&mut data, row_len(width), height.into());
+            for level in &mut mip_levels {
+                rows::flip(&mut level.data, row_len(level.width), level.height.into());
+            }
+        }
+        let data = rows::pad(data, row_len(width), self.row_stride(width, channels));
+        for level in &mut mip_levels {
+            level.row_stride = self.row_stride(level.width, channels);
+            level.data = rows::pad(
+                std::mem::take(&mut level.data),
+                row_len(level.width),
+                level.row_stride,
+            );
+        }
+        (data, mip_levels)
+    }
+
+    /// Choose the dimensions of the asset, and pack the glyphs into it.
+    fn pack<'a, T, I>(
+        &self,
+        glyphs: &I,
+        layout: Option<CustomLayout<'_>>,
+    ) -> Result<AssetPlan<'a, T>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        self.padding.validate()?;
+        let glyphs = &unique_requests(glyphs);
+        let missing = self.missing;
+        let mut skipped = match missing {
+            MissingGlyphPolicy::Skip => glyphs
+                .clone()
+                .filter(|request| request.key.glyph_id(request.face).is_none())
+                .map(|request| SkippedGlyph {
+                    user_data: request.user_data,
+                    key: request.key,
+                    reason: SkipReason::Missing,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let faces = face::distinct_faces(glyphs.clone().map(|request| request.face));
+        let glyphs = &glyphs.clone().filter_map(move |request| {
+            if request.key.glyph_id(request.face).is_some() {
+                return Some(request);
+            }
+            match missing {
+                MissingGlyphPolicy::Skip => None,
+                MissingGlyphPolicy::NotDef => Some(GlyphRequest {
+                    notdef: true,
+                    ..request
+                }),
+                // let measuring report the error
+                _ => Some(request),
+            }
+        });
+        let filter = self.filter.clone();
+        skipped.extend(
+            glyphs
+                .clone()
+                .filter(|request| filtered_out(filter.as_ref(), request))
+                .map(|request| SkippedGlyph {
+                    user_data: request.user_data,
+                    key: request.key,
+                    reason: SkipReason::Filtered,
+                }),
+        );
+        let glyphs = &glyphs
+            .clone()
+            .filter(move |request| !filtered_out(filter.as_ref(), request));
+        let empty = glyphs.clone().next().is_none();
+        if empty && self.empty == EmptyGlyphsPolicy::Fail {
+            return Err(Error::NoGlyphs);
+        }
+        // with no glyphs to fit, a chosen font size only matters to glyphs
+        // appended later, so keep the size of the image
+        let size = match self.size {
+            AssetSize::TextureSize(width, height) if empty => {
+                let font_size = self
+                    .size_bounds
+                    .map_or(f32::from(height), |(lower, _)| lower);
+                AssetSize::Fixed(width, height, font_size)
+            }
+            size => size,
+        };
+        let pack_args = PackArgs {
+            allow_rotate: self.allow_rotate,
+            packer: &*self.packer,
+            gutter: self.gutter,
+            reserved: &self.reserved,
+            layers: self.pages_per_layer(),
+        };
+        // glyphs are packed into pages, stacked like layers, which are put
+        // in the channels of the image's layers once rendered
+        let pages = self.layers * self.pages_per_layer();
+        // tiles are trimmed by rendering them, so remember each glyph's trim
+        // at each font size for packing attempts which measure it again
+        let trimmed = RefCell::new(HashMap::new());
+        let measure = |font_size: f32, request: &GlyphRequest<'a, T>| {
+            let size = self.measure(font_size, request)?;
+            if !self.trim_borders {
+                return Ok(size);
+            }
+            let key = (
+                font_size.to_bits(),
+                request.face as *const Face<'_>,
+                request.key,
+                request.notdef,
+                request
+                    .transform
+                    .map(|transform| transform.map(f32::to_bits)),
+            );
+            if let Some(&size) = trimmed.borrow().get(&key) {
+                return Ok(size);
+            }
+            let segments = raster::glyph_segments(request, self.simplify, &self.variations)?;
+            let size = borders::trim_empty_borders(self.raster_config(font_size), &segments, size);
+            trimmed.borrow_mut().insert(key, size);
+            Ok(size)
+        };
+        let (width, height, pages, font_size, packing) = match (layout, size) {
+            (
+                Some(CustomLayout {
                     width,
                     height,
-                    self.padding,
-                    self.allow_rotate,
+                    pages,
+                    font_size,
+                    place,
+                }),
+                _,
+            ) => {
+                let measured = bisect::measure_all(font_size, &measure, glyphs)?;
+                // the layout fills the pages as one area, keeping a gutter
+                // along the top of the last
+                let area_height = u32::from(pages - 1) * u32::from(height)
+                    + u32::from(height.saturating_sub(self.gutter));
+                let packing = pack_args
+                    .place(
+                        width.saturating_sub(self.gutter).into(),
+                        area_height,
+                        height.into(),
+                        measured,
+                        &"the layout function",
+                        place,
+                    )
+                    .ok_or(Error::PackingAtlasFailed)?;
+                (width, height, pages, font_size, packing)
+            }
+            (None, AssetSize::FontSize(font_size) | AssetSize::FixedWidth(_, font_size))
+                if empty && self.reserved.is_empty() =>
+            {
+                let width = match size {
+                    AssetSize::FixedWidth(width, _) => width,
+                    _ => 2,
+                };
+                let packing = Packing {
+                    glyphs: Vec::new(),
+                    reserved: Vec::new(),
+                };
+                (width, 2, pack_args.layers, font_size, packing)
+            }
+            (None, AssetSize::FontSize(font_size)) => {
+                let ((width, height), packing) = bisect::bisect_asset_size(
+                    font_size,
+                    self.max_texture_size,
+                    measure,
+                    pack_args,
+                    self.shape,
+                    glyphs,
+                )?;
+                (width, height, pack_args.layers, font_size, packing)
+            }
+            (None, AssetSize::FixedWidth(width, font_size)) => {
+                let (height, packing) = bisect::smallest_height(
+                    width,
+                    font_size,
+                    self.max_texture_size,
+                    measure,
+                    pack_args,
+                    glyphs,
+                )?;
+                (width, height, pack_args.layers, font_size, packing)
+            }
+            (None, AssetSize::TextureSize(width, height)) => {
+                let (lower_bound, too_big) =
+                    self.size_bounds.unwrap_or((1.0, 8.0 * (height as f32)));
+                // without bounds given, start from a size estimated from
+                // the area the glyphs cover
+                let (font_size, packing) = bisect::bisect_font_size(
+                    width,
+                    height,
+                    measure,
+                    PackArgs {
+                        layers: pages,
+                        ..pack_args
+                    },
                     BisectArgs {
-                        lower_bound: 1.0,
-                        too_big: 8.0 * (height as f32),
-                        attempts: 11,
+                        lower_bound,
+                        too_big,
+                        attempts: self.size_attempts,
                     },
-                    &glyphs,
-                )?
-                .1;
+                    self.size_bounds.is_none(),
+                    glyphs,
+                )?;
+                (width, height, pages, font_size, packing)
             }
-        }
-        let buflen = usize::from(width) * usize::from(height);
-        let mut buf = vec![0; buflen];
-        let mut meta = Vec::with_capacity(packing.len());
-        for item in packing {
-            raster::raster(
-                raster::Buffer {
-                    data: &mut buf,
+            (None, AssetSize::Fixed(width, height, font_size)) => {
+                let (packing, left_out) = bisect::pack_by_priority(
                     width,
-                },
-                self.padding,
-                &item,
-            )?;
-            // calculate metadata
-            let (request, rastered_size) = *item.data;
-            let rotated = (item.rect.w - 1) != rastered_size.pixel_width.into();
-            let RasteredSize {
-                left,
-                right,
-                top,
-                bottom,
-                ..
-            } = rastered_size;
-            let tex_left = (item.rect.x as f32) / f32::from(width);
-            let tex_right =
-                (item.rect.x as f32 + f32::from(rastered_size.pixel_width)) / f32::from(width);
-            let tex_bottom = (item.rect.y as f32) / f32::from(height);
-            let tex_top =
-                (item.rect.y as f32 + f32::from(rastered_size.pixel_height)) / f32::from(height);
-            meta.push(Glyph {
-                user_data: request.user_data,
-                codepoint: request.codepoint,
-                rotated,
-                left,
-                right,
-                top,
-                bottom,
-                tex_left,
-                tex_right,
-                tex_bottom,
-                tex_top,
-            });
-        }
-        Ok(SdfFontAsset {
+                    height,
+                    font_size,
+                    measure,
+                    PackArgs {
+                        layers: pages,
+                        ..pack_args
+                    },
+                    glyphs,
+                )?;
+                skipped.extend(left_out.into_iter().map(|request| SkippedGlyph {
+                    user_data: request.user_data,
+                    key: request.key,
+                    reason: SkipReason::DidNotFit,
+                }));
+                (width, height, pages, font_size, packing)
+            }
+        };
+        let Packing {
+            glyphs: mut packing,
+            reserved,
+        } = packing;
+        let pages_per_layer = self.pages_per_layer();
+        let reserved: Vec<ReservedRect> = reserved
+            .into_iter()
+            .map(|rect| ReservedRect {
+                layer: rect.layer / pages_per_layer,
+                channel: (rect.layer % pages_per_layer) as u8,
+                ..rect
+            })
+            .collect();
+        let (trimmed_width, trimmed_height) = match self.trim {
+            Some(alignment) => {
+                // keep the gutter past the last row and column
+                let used = |end: usize| end + usize::from(self.gutter);
+                let rects = packing
+                    .iter()
+                    .map(|item| crunch::Rect {
+                        y: item.rect.y % usize::from(height),
+                        ..item.rect
+                    })
+                    .chain(reserved.iter().map(|rect| rect.as_rect(0, pages_per_layer)));
+                let used_width = rects.clone().map(|rect| used(rect.x + rect.w));
+                let used_height = rects.map(|rect| used(rect.y + rect.h));
+                let trimmed = |used: Option<usize>, size: u16| {
+                    let aligned =
+                        used.unwrap_or(1).div_ceil(usize::from(alignment)) * usize::from(alignment);
+                    aligned.clamp(1, size.into()) as u16
+                };
+                (
+                    trimmed(used_width.max(), width),
+                    trimmed(used_height.max(), height),
+                )
+            }
+            None => (width, height),
+        };
+        if trimmed_height != height {
+            // restack the pages at the trimmed height
+            for item in &mut packing {
+                let (page, y) = (
+                    item.rect.y / usize::from(height),
+                    item.rect.y % usize::from(height),
+                );
+                item.rect.y = page * usize::from(trimmed_height) + y;
+            }
+        }
+        if cfg!(debug_assertions) {
+            let rects: Vec<crunch::Rect> = packing
+                .iter()
+                .map(|item| item.rect)
+                .chain(
+                    reserved
+                        .iter()
+                        .map(|rect| rect.as_rect(trimmed_height, pages_per_layer)),
+                )
+                .collect();
+            overlap::check_rects(trimmed_width, trimmed_height, self.gutter, &rects);
+        }
+        Ok(AssetPlan {
+            builder: self.clone(),
+            width: trimmed_width,
+            height: trimmed_height,
+            pages,
+            font_size,
+            packing,
+            reserved,
+            skipped,
+            faces,
+        })
+    }
+}
+
+impl<T> SdfFontAsset<T> {
+    #[cfg(feature = "gpu")]
+    fn from_plan(
+        mut plan: AssetPlan<'_, T>,
+        spread_px: f32,
+        mode: DistanceMode,
+        channels: u8,
+        data: Vec<u8>,
+        mip_levels: Vec<MipLevel>,
+    ) -> Self {
+        let placed = std::mem::take(&mut plan.packing)
+            .into_iter()
+            .map(|item| (item.rect, Glyph::placed(&plan, item)))
+            .collect();
+        Self {
+            data,
+            mip_levels,
+            ..Self::from_placed(plan, placed, spread_px, mode, channels)
+        }
+    }
+
+    /// The asset of a plan whose glyphs have been rendered, each given by
+    /// its rect and metadata, in the order of the plan's packing.  The
+    /// asset's data is left empty.
+    pub(crate) fn from_placed(
+        plan: AssetPlan<'_, T>,
+        placed: Vec<(crunch::Rect, Glyph<T>)>,
+        spread_px: f32,
+        mode: DistanceMode,
+        channels: u8,
+    ) -> Self {
+        let AssetPlan {
+            builder,
+            width,
+            height,
+            pages,
+            font_size,
+            packing: _,
+            reserved,
+            skipped,
+            faces,
+        } = plan;
+        let top_left = builder.origin == UvOrigin::TopLeft;
+        let (rects, meta): (Vec<_>, Vec<_>) = placed.into_iter().unzip();
+        let mut kerning = Vec::new();
+        if builder.kerning {
+            for (index, face) in faces.iter().enumerate() {
+                let face = builder.variations.apply(face);
+                let handle = FaceHandle(index as u16);
+                kerning.extend(kerning::face_pairs(&face, handle, &meta, 0));
+            }
+            kerning.sort_unstable_by_key(|pair| (pair.left, pair.right));
+        }
+        SdfFontAsset {
             width,
             height,
-            data: buf,
+            layers: pages / builder.pages_per_layer(),
+            spread_px,
+            font_size,
+            mode,
+            channels,
+            data: Vec::new(),
+            row_stride: builder.row_stride(width, channels),
+            mip_levels: Vec::new(),
             metadata: meta,
+            reserved: reserved
+                .into_iter()
+                .map(|rect| if top_left { rect.flipped(height) } else { rect })
+                .collect(),
+            skipped,
+            kerning,
+            gutter: builder.gutter,
+            metrics: faces
+                .iter()
+                .map(|face| FontMetrics::new(&builder.variations.apply(face)))
+                .collect(),
+            faces: faces.into_iter().map(FaceFingerprint::new).collect(),
+            builder,
+            rects,
+        }
+    }
+}
+
+impl<T> Glyph<T> {
+    /// The metadata of a glyph packed by a plan
+    pub(crate) fn placed(plan: &AssetPlan<'_, T>, item: PackedGlyph<'_, T>) -> Self {
+        let AssetPlan {
+            builder,
+            width,
+            height,
+            faces,
+            ..
+        } = plan;
+        let (width, height) = (*width, *height);
+        let top_left = builder.origin == UvOrigin::TopLeft;
+        // measure a texture coordinate down from the top of the image
+        let flip_v = |v: f32| if top_left { 1.0 - v } else { v };
+        let PackedGlyph {
+            request,
+            size: rastered_size,
+            rect,
+            rotated,
+        } = item;
+        // pages are stacked from the bottom up in the rendered image
+        let page = rect.y / usize::from(height);
+        let pages_per_layer = usize::from(builder.pages_per_layer());
+        let (layer, channel) = (page / pages_per_layer, page % pages_per_layer);
+        let rect = crunch::Rect {
+            y: rect.y % usize::from(height),
+            ..rect
+        };
+        let RasteredSize {
+            mut left,
+            mut right,
+            mut top,
+            mut bottom,
+            pixel_width,
+            pixel_height,
+        } = rastered_size;
+        // the box was grown by half a texel each way to put the outer
+        // samples on its edges, report the box those samples span, which
+        // is also the box inset texture coordinates span
+        let inset = match builder.sample {
+            SamplePosition::Center if !builder.inset_tex => 0.0,
+            _ => 0.5,
+        };
+        if pixel_width > 0 {
+            let half_x = inset as f32 * (right - left) / f32::from(pixel_width);
+            left += half_x;
+            right -= half_x;
+        }
+        if pixel_height > 0 {
+            let half_y = inset as f32 * (top - bottom) / f32::from(pixel_height);
+            bottom += half_y;
+            top -= half_y;
+        }
+        let face = builder.variations.apply(request.face);
+        // packed glyphs were measured, so their key resolved to an id
+        let glyph_id = request.glyph_id(&face).unwrap_or(GlyphId(0));
+        let advance = face.glyph_hor_advance(glyph_id).map_or(0.0, |advance| {
+            f32::from(advance) / f32::from(face.units_per_em())
+        });
+        let tex_left = tex_coord(rect.x, inset, width);
+        let tex_right = tex_coord(rect.x + rect.w, -inset, width);
+        let tex_bottom = flip_v(tex_coord(rect.y, inset, height));
+        let tex_top = flip_v(tex_coord(rect.y + rect.h, -inset, height));
+        // the baseline crosses the tile from left to right, or from
+        // bottom to top when the glyph is rotated
+        let (origin, span, size) = if rotated {
+            (rect.x, rect.w, width)
+        } else {
+            (rect.y, rect.h, height)
+        };
+        let above_bottom = if top > bottom {
+            -bottom / (top - bottom)
+        } else {
+            0.0
+        };
+        let inset_texels = inset as f32;
+        let baseline_texels = inset_texels + above_bottom * (span as f32 - 2.0 * inset_texels);
+        let tex_baseline = tex_coord(origin, f64::from(baseline_texels), size);
+        let tex_baseline = if rotated {
+            tex_baseline
+        } else {
+            flip_v(tex_baseline)
+        };
+        let (y, baseline_texels) = if top_left {
+            let y = usize::from(height) - rect.y - rect.h;
+            if rotated {
+                (y, baseline_texels)
+            } else {
+                (y, span as f32 - baseline_texels)
+            }
+        } else {
+            (rect.y, baseline_texels)
+        };
+        Glyph {
+            user_data: request.user_data,
+            key: request.key,
+            glyph_id,
+            face: face::handle_of(faces, request.face),
+            layer: layer as u16,
+            channel: channel as u8,
+            rotated,
+            color: request.color,
+            transform: request.transform,
+            rotation: request.rotation,
+            priority: request.priority,
+            notdef: request.notdef,
+            left,
+            right,
+            top,
+            bottom,
+            advance,
+            tex_left,
+            tex_right,
+            tex_bottom,
+            tex_top,
+            x: rect.x as u16,
+            y: y as u16,
+            width: rect.w as u16,
+            height: rect.h as u16,
+            baseline_texels,
+            tex_baseline,
+            retried: false,
+        }
+    }
+}
+
+impl<T> SdfFontAsset<T> {
+    /// Find the handle glyphs from a face were given, if the asset has
+    /// glyphs from it.  Faces are recognized by their `head` checksum, glyph
+    /// count, and units per em, so the face may be parsed again from the
+    /// same font file.
+    pub fn face_handle(&self, face: &Face<'_>) -> Option<FaceHandle> {
+        let fingerprint = FaceFingerprint::new(face);
+        let index = self.faces.iter().position(|known| *known == fingerprint)?;
+        Some(FaceHandle(index as u16))
+    }
+
+    /// The number of distinct faces glyphs were rendered from
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// The ascent, descent, line gap, letter heights, and decoration lines
+    /// of a face, to lay out and decorate lines of text without the face at
+    /// hand.  Faces are given the
+    /// [variations](FontAssetBuilder::with_variation) the asset was built
+    /// with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is not of one of this asset's faces.
+    pub fn metrics(&self, face: FaceHandle) -> FontMetrics {
+        self.metrics[face.index()]
+    }
+
+    /// The settings the asset was built with which affect how it should be
+    /// drawn, such as its spread and what its texels measure
+    pub fn settings(&self) -> AssetSettings {
+        AssetSettings::new(&self.builder, self.font_size)
+    }
+
+    /// The texels of one layer of the image, for renderers which upload or
+    /// bind a layer at a time.  Layers follow each other in
+    /// [`data`](Self::data), so this is a slice of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is not less than [`layers`](Self::layers).
+    pub fn layer_data(&self, layer: u16) -> &[u8] {
+        assert!(layer < self.layers);
+        let layer_len = usize::from(self.height) * self.row_stride;
+        &self.data[usize::from(layer) * layer_len..][..layer_len]
+    }
+
+    /// The glyphs rendered to one layer of the image, in the order of
+    /// [`metadata`](Self::metadata), to draw the text using that layer
+    /// while it is bound.  Glyphs are mostly grouped by layer in the
+    /// metadata, but not always, such as after
+    /// [`append`](Self::append), so this checks every glyph.
+    pub fn layer_glyphs(&self, layer: u16) -> impl '_ + Iterator<Item = &Glyph<T>> {
+        self.metadata
+            .iter()
+            .filter(move |glyph| glyph.layer == layer)
+    }
+
+    /// The highest mip level which can be sampled with bilinear filtering
+    /// without glyphs bleeding into each other.  Each texel of level `n`
+    /// averages a block `2^n` texels wide, and filtering blends neighboring
+    /// blocks, so glyphs stay apart while the gutter is at least
+    /// `2^(n + 1) - 1` texels.  Zero means only the full size image is safe.
+    pub fn max_mip_level(&self) -> u32 {
+        (u32::from(self.gutter) + 1).ilog2().saturating_sub(1)
+    }
+
+    /// A table for finding glyphs by codepoint, to upload to the GPU so
+    /// shaders can resolve codepoints themselves.
+    ///
+    /// The table is a flat array of pairs: each codepoint, as a `u32`,
+    /// followed by the index of its glyph in [`metadata`](Self::metadata).
+    /// Pairs are sorted by codepoint, so a shader can binary search the
+    /// even elements of the table; there are `table.len() / 2` pairs.
+    /// Glyphs requested by glyph id are left out, and if several faces have
+    /// a glyph for the same codepoint, the first in the metadata is used.
+    /// [`aliases`](Self::aliases) are paired with the index of the glyph
+    /// they share.
+    pub fn codepoint_table(&self) -> Vec<u32> {
+        self.codepoint_index()
+            .first_of_each()
+            .flat_map(|(codepoint, index)| [u32::from(codepoint), index as u32])
+            .collect()
+    }
+
+    /// The [`advance`](Glyph::advance) of each glyph, in the order of
+    /// [`metadata`](Self::metadata), to upload to the GPU alongside the
+    /// [`codepoint_table`](Self::codepoint_table) so shaders can lay out
+    /// text themselves.  The index a codepoint is found at in that table is
+    /// the index of its advance here.
+    ///
+    /// The advances are in ems, and take no account of kerning, so laying
+    /// out text with them is only as accurate as summing advances is.
+    pub fn advance_table(&self) -> Vec<f32> {
+        self.metadata.iter().map(|glyph| glyph.advance).collect()
+    }
+
+    /// Sort the glyphs by codepoint, to find them without scanning all of
+    /// [`metadata`](Self::metadata), for atlases with many thousands of
+    /// glyphs.  Build the index once and keep it while the asset is
+    /// unchanged.  Glyphs requested by glyph id are left out, and
+    /// [`aliases`](Self::aliases) are found by the glyph they share.
+    pub fn codepoint_index(&self) -> CodepointIndex<'_, T> {
+        CodepointIndex::new(&self.metadata, &self.builder.aliases)
+    }
+
+    /// The kerning to add to the advance of the glyph at index `left` in the
+    /// [`metadata`](Self::metadata) when the glyph at index `right` follows
+    /// it, in ems, or zero if the pair has none
+    pub fn pair_kerning(&self, left: usize, right: usize) -> f32 {
+        let key = (left as u32, right as u32);
+        match self
+            .kerning
+            .binary_search_by_key(&key, |pair| (pair.left, pair.right))
+        {
+            Ok(index) => self.kerning[index].adjust,
+            Err(_) => 0.0,
+        }
+    }
+
+    /// The aliases given to
+    /// [`with_aliases`](FontAssetBuilder::with_aliases), each an alias and
+    /// the codepoint whose glyph it shares
+    pub fn aliases(&self) -> &[(char, char)] {
+        &self.builder.aliases
+    }
+}
+
+impl<T: Clone> SdfFontAsset<T> {
+    /// Reconstruct the requests this asset was built from, so it can be
+    /// rebuilt with different settings.  The asset does not keep a reference
+    /// to the font, so it must be provided again.  Every request uses `face`;
+    /// for assets built from several faces, see
+    /// [`requests_from_faces`](Self::requests_from_faces).
+    pub fn requests<'a>(
+        &'a self,
+        face: &'a Face<'a>,
+    ) -> impl 'a + Clone + Iterator<Item = GlyphRequest<'a, T>> {
+        self.metadata.iter().map(move |glyph| GlyphRequest {
+            user_data: glyph.user_data.clone(),
+            face,
+            key: glyph.key,
+            color: glyph.color,
+            transform: glyph.transform,
+            rotation: glyph.rotation,
+            priority: glyph.priority,
+            notdef: glyph.notdef,
         })
     }
+
+    /// Reconstruct the requests this asset was built from, taking each
+    /// glyph's face from `faces` by its [`FaceHandle`].
+    ///
+    /// # Panics
+    /// Panics if `faces` has fewer faces than the asset.
+    pub fn requests_from_faces<'a>(
+        &'a self,
+        faces: &'a [&'a Face<'a>],
+    ) -> impl 'a + Clone + Iterator<Item = GlyphRequest<'a, T>> {
+        self.metadata.iter().map(move |glyph| GlyphRequest {
+            user_data: glyph.user_data.clone(),
+            face: faces[glyph.face.index()],
+            key: glyph.key,
+            color: glyph.color,
+            transform: glyph.transform,
+            rotation: glyph.rotation,
+            priority: glyph.priority,
+            notdef: glyph.notdef,
+        })
+    }
+}
+
+/// Convert a texel position, moved by `offset` texels, to a texture
+/// coordinate.  The division is done in f64 so the result is rounded only
+/// once, to the f32 nearest the exact ratio, even for the largest atlases.
+fn tex_coord(texel: usize, offset: f64, size: u16) -> f32 {
+    ((texel as f64 + offset) / f64::from(size)) as f32
 }
 
 /// A request for a glyph to be rendered.
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub struct GlyphRequest<'a, T> {
     /// Some data you can use to associate a rendered Glyph to the submitted GlyphRequest.
     pub user_data: T,
@@ -191,29 +2097,341 @@ pub struct GlyphRequest<'a, T> {
     /// The font face to render the glyph from.
     pub face: &'a Face<'a>,
 
-    /// The codepoint of the glyph.
-    pub codepoint: char,
+    /// The character or glyph id of the glyph.
+    pub key: GlyphKey,
+
+    /// A default color to carry through to the glyph's metadata, as
+    /// non-premultiplied sRGB RGBA.
+    pub color: Option<[u8; 4]>,
+
+    /// An affine transform to apply to the outline, see
+    /// [`with_transform`](Self::with_transform).
+    pub transform: Option<[f32; 6]>,
+
+    /// Whether the glyph may be rotated when packed, overriding
+    /// [`FontAssetBuilder::allow_rotating_glyphs`], see
+    /// [`with_rotation`](Self::with_rotation).
+    pub rotation: Option<bool>,
+
+    /// How much the glyph matters when not every glyph fits, see
+    /// [`with_priority`](Self::with_priority).
+    pub priority: i32,
+
+    /// Whether to render the `.notdef` glyph if the face has no glyph for
+    /// the key, see [`MissingGlyphPolicy::NotDef`]
+    pub(crate) notdef: bool,
+}
+
+impl<'a, T> GlyphRequest<'a, T> {
+    /// Request a glyph from a font face by its codepoint.
+    pub fn new(user_data: T, face: &'a Face<'a>, codepoint: char) -> Self {
+        Self {
+            user_data,
+            face,
+            key: GlyphKey::Codepoint(codepoint),
+            color: None,
+            transform: None,
+            rotation: None,
+            priority: 0,
+            notdef: false,
+        }
+    }
+
+    /// Request a glyph from a font face by its id, for glyphs with no
+    /// codepoint of their own such as ligatures, alternates, and the output
+    /// of a text shaper.
+    pub fn from_glyph_id(user_data: T, face: &'a Face<'a>, glyph_id: GlyphId) -> Self {
+        Self {
+            user_data,
+            face,
+            key: GlyphKey::Id(glyph_id),
+            color: None,
+            transform: None,
+            rotation: None,
+            priority: 0,
+            notdef: false,
+        }
+    }
+
+    /// Allow or forbid rotating this glyph when it is packed, whatever the
+    /// builder allows for other glyphs.  For example, rotation can be
+    /// limited to large glyphs, or forbidden for glyphs drawn with shared
+    /// texture coordinate math.
+    pub fn with_rotation(self, allow: bool) -> Self {
+        Self {
+            rotation: Some(allow),
+            ..self
+        }
+    }
+
+    /// Set how much the glyph matters when not every glyph fits an atlas of
+    /// [fixed size](FontAssetBuilder::with_fixed_size): glyphs of the lowest
+    /// priority are left out first.  The default is zero.
+    pub fn with_priority(self, priority: i32) -> Self {
+        Self { priority, ..self }
+    }
+
+    /// The character the glyph was requested by, or `None` if it was
+    /// requested by glyph id.  This replaces the `codepoint` field of
+    /// earlier versions.
+    pub fn codepoint(&self) -> Option<char> {
+        self.key.codepoint()
+    }
+
+    /// The same request without its user data, to share between threads
+    /// whatever the type of the user data
+    pub(crate) fn without_user_data(&self) -> GlyphRequest<'a, ()> {
+        GlyphRequest {
+            user_data: (),
+            face: self.face,
+            key: self.key,
+            color: self.color,
+            transform: self.transform,
+            rotation: self.rotation,
+            priority: self.priority,
+            notdef: self.notdef,
+        }
+    }
+
+    /// Find the glyph in a face, falling back to `.notdef` if requested
+    pub(crate) fn glyph_id(&self, face: &Face<'_>) -> Option<GlyphId> {
+        let notdef = self.notdef.then_some(GlyphId(0));
+        self.key.glyph_id(face).or(notdef)
+    }
+
+    /// Attach a default color to the glyph, see [`palette_color`] to use the
+    /// color assigned by the font.
+    pub fn with_color(self, color: [u8; 4]) -> Self {
+        Self {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    /// Apply an affine transform `[a, b, c, d, e, f]` to the outline before
+    /// distances are computed, mapping each point to
+    /// `(a * x + c * y + e, b * x + d * y + f)`.  Coordinates are relative to
+    /// the font size, so a translation of 1.0 moves the glyph by one em.  The
+    /// glyph's bounding box in the metadata is that of the transformed
+    /// outline.
+    pub fn with_transform(self, transform: [f32; 6]) -> Self {
+        Self {
+            transform: Some(transform),
+            ..self
+        }
+    }
+}
+
+/// How a glyph is identified in a font face
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlyphKey {
+    /// A character, looked up in the font's character map
+    Codepoint(char),
+
+    /// A glyph id, used as is
+    Id(GlyphId),
+}
+
+impl GlyphKey {
+    /// The character, if the glyph was requested by one
+    pub fn codepoint(self) -> Option<char> {
+        match self {
+            Self::Codepoint(codepoint) => Some(codepoint),
+            Self::Id(_) => None,
+        }
+    }
+
+    /// Find the glyph in a face, if it has one
+    pub fn glyph_id(self, face: &Face<'_>) -> Option<GlyphId> {
+        match self {
+            Self::Codepoint(codepoint) => face.glyph_index(codepoint),
+            Self::Id(glyph_id) => (glyph_id.0 < face.number_of_glyphs()).then_some(glyph_id),
+        }
+    }
+}
+
+impl From<char> for GlyphKey {
+    fn from(codepoint: char) -> Self {
+        Self::Codepoint(codepoint)
+    }
+}
+
+impl From<GlyphId> for GlyphKey {
+    fn from(glyph_id: GlyphId) -> Self {
+        Self::Id(glyph_id)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Padding {
+    Ratio(f32, f32),
+    /// Ratios for the left, right, bottom, and top sides
+    Sides([f32; 4]),
+    Pixels(f32, f32),
+}
+
+impl Padding {
+    /// Padding ratios must be less than this; the margins on either side of
+    /// a glyph would otherwise cover a whole em between them.
+    const MAX_RATIO: f32 = 0.5;
+
+    /// Check that the padding describes a usable distance field
+    fn validate(self) -> Result<(), Error> {
+        let (sides, max) = match self {
+            Self::Ratio(x, y) => ([x, x, y, y], Self::MAX_RATIO),
+            Self::Sides(sides) => (sides, Self::MAX_RATIO),
+            Self::Pixels(x, y) => ([x, x, y, y], f32::INFINITY),
+        };
+        let in_range = |value: f32| (0.0..max).contains(&value);
+        if sides.into_iter().all(in_range) && sides.into_iter().any(|side| side > 0.0) {
+            Ok(())
+        } else {
+            Err(Error::InvalidPadding)
+        }
+    }
+
+    /// The padding on the left, right, bottom, and top sides as a ratio of
+    /// the font size
+    fn sides(self, font_size: f32) -> [f32; 4] {
+        match self {
+            Self::Ratio(x, y) => [x, x, y, y],
+            Self::Sides(sides) => sides,
+            Self::Pixels(x, y) => {
+                let largest = Self::MAX_RATIO - f32::EPSILON;
+                let (x, y) = ((x / font_size).min(largest), (y / font_size).min(largest));
+                [x, x, y, y]
+            }
+        }
+    }
+
+    /// The larger horizontal and vertical padding as a ratio of the font
+    /// size
+    fn ratio(self, font_size: f32) -> (f32, f32) {
+        let [left, right, bottom, top] = self.sides(font_size);
+        (left.max(right), bottom.max(top))
+    }
+
+    /// The range of the distance field as a ratio of the font size
+    fn spread(self, font_size: f32) -> f32 {
+        let (x, y) = self.ratio(font_size);
+        x.max(y)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 enum AssetSize {
     FontSize(f32),
     TextureSize(u16, u16),
+    Fixed(u16, u16, f32),
+    FixedWidth(u16, f32),
+}
+
+/// The size and placement function given to
+/// [`FontAssetBuilder::plan_with_layout`]
+struct CustomLayout<'l> {
+    width: u16,
+    height: u16,
+    /// The number of layers, times the pages in each
+    pages: u16,
+    font_size: f32,
+    place: PlaceFn<'l>,
+}
+
+type PlaceFn<'l> = Box<dyn 'l + FnOnce(&[PackerItem]) -> Option<Vec<PackerPlacement>>>;
+
+/// The predicate given to [`FontAssetBuilder::with_filter`]
+#[derive(Clone)]
+struct Filter(Arc<FilterFn>);
+
+type FilterFn = dyn Fn(&Face<'_>, Option<char>, GlyphId) -> bool + Send + Sync;
+
+impl std::fmt::Debug for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Filter")
+    }
 }
 
 /// Metadata for a glyph that was rendered in an asset.
-#[derive(Clone, Copy, Debug)]
+///
+/// Glyphs the font has but which have nothing to draw, such as spaces, are
+/// still included, so their [`advance`](Self::advance) is available.  Their
+/// bounding box is zero at every edge, and their texture coordinates form an
+/// empty rect; see [`is_empty`](Self::is_empty).
+///
+/// # Positioning
+///
+/// The bounding box, [`left`](Self::left), [`right`](Self::right),
+/// [`bottom`](Self::bottom), and [`top`](Self::top), is in ems relative to
+/// the pen position on the baseline, with y up.  To draw a glyph at a font
+/// size of `size` pixels with the pen at `(x, y)`, cover the quad from
+/// `(x + left * size, y + bottom * size)` to
+/// `(x + right * size, y + top * size)` with the glyph's tile.
+///
+/// Any edge may lie on either side of the pen position or baseline, and
+/// layout code should not assume otherwise:
+///
+/// - The box includes the padding around the outline, so `left` and
+///   `bottom` are usually a little negative even for glyphs such as `o`
+///   which sit on the baseline to the right of the pen.
+/// - Glyphs which descend below the baseline, such as `j`, `g`, and the
+///   tail of `Q`, have a `bottom` below zero by the depth of the descender.
+/// - Glyphs which reach back past the pen, such as the hook of `j` in many
+///   fonts and the swashes of script fonts, have a more negative `left`.
+/// - Glyphs which overhang the next glyph, such as italic `f`, have a
+///   `right` past their [`advance`](Self::advance).  The pen still moves by
+///   the advance, so such glyphs overlap their neighbors as the font
+///   intends.
+/// - Glyphs entirely above or below the baseline, such as accents and
+///   underscores, have a box which doesn't include it; see
+///   [`baseline_texels`](Self::baseline_texels).
+#[derive(Clone, Copy, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct Glyph<T> {
     /// The user_data from the GlyphRequest.
     pub user_data: T,
 
-    /// The codepoint that was rendered.
-    pub codepoint: char,
+    /// The character or glyph id that was rendered.
+    pub key: GlyphKey,
+
+    /// The id in its face of the glyph that was rendered, which
+    /// [`key`](Self::key) resolved to, or the `.notdef` glyph, id 0, under
+    /// [`MissingGlyphPolicy::NotDef`], for shaping and kerning lookups in the
+    /// face.
+    pub glyph_id: GlyphId,
+
+    /// The face the glyph was rendered from.
+    pub face: FaceHandle,
+
+    /// The layer of the image the glyph was rendered to, see
+    /// [`FontAssetBuilder::with_layers`].
+    pub layer: u16,
+
+    /// The channel of the image the glyph was rendered to, see
+    /// [`FontAssetBuilder::with_channel_packing`].  This is 0 otherwise.
+    pub channel: u8,
 
     /// Whether rotation was applied when this glyph was packed.
     pub rotated: bool,
 
+    /// The default color from the GlyphRequest.
+    pub color: Option<[u8; 4]>,
+
+    /// The transform from the GlyphRequest.
+    pub transform: Option<[f32; 6]>,
+
+    /// Whether the GlyphRequest allowed or forbade rotation, or `None` if
+    /// it left it to the builder, see
+    /// [`with_rotation`](GlyphRequest::with_rotation).
+    pub rotation: Option<bool>,
+
+    /// The priority from the GlyphRequest, see
+    /// [`with_priority`](GlyphRequest::with_priority).
+    pub priority: i32,
+
+    /// Whether the GlyphRequest fell back to `.notdef`, so the request can
+    /// be rebuilt as it was.
+    pub(crate) notdef: bool,
+
     /// The relative left edge of a bounding box from the glyph's 0 position
     /// that will position the resulting SDF so that the middle distance
     /// describes a character as specified by the font.
@@ -234,7 +2452,16 @@ pub struct Glyph<T> {
     /// describes a character as specified by the font.
     pub top: f32,
 
-    /// The left edge of the rendered glyph as a texture coordinate
+    /// The distance to move the pen after drawing the glyph, in the same
+    /// units as the bounding box.  The advance is not affected by the
+    /// request's transform.
+    pub advance: f32,
+
+    /// The left edge of the rendered glyph as a texture coordinate.  This is
+    /// [`x`](Self::x) divided by the width of the atlas, or half a texel
+    /// further in with [`SamplePosition::Corner`] or
+    /// [`inset_tex_coords`](FontAssetBuilder::inset_tex_coords); the other
+    /// texture coordinates are found from the tile the same way.
     pub tex_left: f32,
 
     /// The right edge of the rendered glyph as a texture coordinate
@@ -245,35 +2472,215 @@ pub struct Glyph<T> {
 
     /// The bottom edge of the rendered glyph as a texture coordinate
     pub tex_bottom: f32,
+
+    /// The left edge of the glyph's tile in the atlas, in texels
+    pub x: u16,
+
+    /// The bottom edge of the glyph's tile in its layer of the atlas, in
+    /// texels, or its top edge, measured from the top, with
+    /// [`UvOrigin::TopLeft`]
+    pub y: u16,
+
+    /// The width of the glyph's tile, in texels.  This covers the glyph's
+    /// height if it is [`rotated`](Self::rotated).
+    pub width: u16,
+
+    /// The height of the glyph's tile, in texels
+    pub height: u16,
+
+    /// Where the baseline lies in the glyph's tile, in texels from the
+    /// tile's bottom edge, or from its left edge if the glyph is
+    /// [`rotated`](Self::rotated).  With [`UvOrigin::TopLeft`], it is from
+    /// the tile's top edge instead of its bottom.  The baseline may lie outside the tile,
+    /// for glyphs entirely above or below it.
+    pub baseline_texels: f32,
+
+    /// The baseline as a texture coordinate: a `v` coordinate, like
+    /// [`tex_bottom`](Self::tex_bottom) and [`tex_top`](Self::tex_top), or a
+    /// `u` coordinate, like [`tex_left`](Self::tex_left) and
+    /// [`tex_right`](Self::tex_right), if the glyph is rotated
+    pub tex_baseline: f32,
+
+    /// Whether the glyph's distances came out broken, such as not a number,
+    /// and it was rendered again with a slower, more thorough search in
+    /// double precision.  This points to an outline the usual search
+    /// handles badly, so its tile is worth checking by eye.
+    pub retried: bool,
+}
+
+impl<T> Glyph<T> {
+    /// The character that was rendered, or `None` if the glyph was
+    /// requested by glyph id.  This replaces the `codepoint` field of
+    /// earlier versions.
+    pub fn codepoint(&self) -> Option<char> {
+        self.key.codepoint()
+    }
+
+    /// Whether the glyph has nothing to draw, such as a space.  Empty glyphs
+    /// have a zero bounding box and take no room in the atlas, so renderers
+    /// can skip them.
+    pub fn is_empty(&self) -> bool {
+        self.left == self.right || self.bottom == self.top
+    }
+
+    /// The glyph's edges as texture coordinates, as `[left, bottom, right,
+    /// top]`.  These are the edges of the tile in the image, so if the glyph
+    /// is [`rotated`](Self::rotated), the glyph's left edge is at the
+    /// tile's bottom; use [`uv_quad`](Self::uv_quad) to draw it.
+    pub fn tex_rect(&self) -> [f32; 4] {
+        [self.tex_left, self.tex_bottom, self.tex_right, self.tex_top]
+    }
+
+    /// The texture coordinates of the corners of the glyph's bounding box,
+    /// in the order `(left, bottom)`, `(right, bottom)`, `(right, top)`,
+    /// `(left, top)`, to draw the glyph as a quad with those corners.
+    ///
+    /// A [`rotated`](Self::rotated) tile is transposed: the glyph's x axis
+    /// runs up the image, and its y axis runs to the right.  The corners are
+    /// swapped to match, so renderers can use them the same way for every
+    /// glyph.
+    pub fn uv_quad(&self) -> [(f32, f32); 4] {
+        let [left, bottom, right, top] = self.tex_rect();
+        if self.rotated {
+            [(left, bottom), (left, top), (right, top), (right, bottom)]
+        } else {
+            [(left, bottom), (right, bottom), (right, top), (left, top)]
+        }
+    }
+
+    /// The map from the glyph's plane, the space of its bounding box, to
+    /// texture coordinates in its tile, to place points of the outline in
+    /// the atlas, or with [`TexTransform::inverse`], to find which point of
+    /// the glyph a texel shows.
+    pub fn tex_transform(&self) -> TexTransform {
+        TexTransform::new(self)
+    }
+}
+
+/// A requested glyph which was left out of an asset
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct SkippedGlyph<T> {
+    /// The user_data from the GlyphRequest.
+    pub user_data: T,
+
+    /// The character or glyph id that was requested.
+    pub key: GlyphKey,
+
+    /// Why the glyph was left out
+    pub reason: SkipReason,
+}
+
+/// Why a glyph was left out of an asset
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The font has no glyph for the key, see [`MissingGlyphPolicy::Skip`]
+    Missing,
+
+    /// The glyph didn't fit in an atlas of
+    /// [fixed size](FontAssetBuilder::with_fixed_size)
+    DidNotFit,
+
+    /// The glyph was left out by [`FontAssetBuilder::with_filter`]
+    Filtered,
 }
 
 /// Returns an iterator of the chars you would want to pass to
 /// [`build`](FontAssetBuilder::build) if you will be using the rendered font to
-/// display hexadecimal values.
+/// display hexadecimal values.  See [`Charset::hexdigits`].
 pub fn hexdigits() -> impl Clone + Iterator<Item = char> {
-    b"0123456789abcdefABCDEFxX".iter().copied().map(char::from)
+    Charset::hexdigits().into_iter()
 }
 
 /// Returns an iterator of the chars you would want to pass to
 /// [`build`](FontAssetBuilder::build) if you will be using the rendered font to
-/// display ascii text.
+/// display ascii text.  See [`Charset::ascii`].
 pub fn ascii() -> impl Clone + Iterator<Item = char> {
-    (b'!'..=b'~').map(char::from)
+    Charset::ascii().into_iter()
 }
 
 /// Returns an iterator of the chars you would want to pass to
 /// [`build`](FontAssetBuilder::build) if you will be using the rendered font to
-/// display ISO-8859-1 ("Latin 1") text.
+/// display ISO-8859-1 ("Latin 1") text.  See [`Charset::latin1`].
 pub fn latin1() -> impl Clone + Iterator<Item = char> {
-    ascii().chain((0xa1..=0xff).map(char::from))
+    Charset::latin1().into_iter()
 }
 
 /// Returns an iterator of the chars you would want to pass to
 /// [`build`](FontAssetBuilder::build) if you will be using the rendered font to
 /// display ISO-8859-1 ("Latin 1") text with three additional characters for
-/// French support.
+/// French support.  See [`Charset::latin1_french`].
 pub fn latin1_french() -> impl Clone + Iterator<Item = char> {
-    latin1().chain(['\u{0152}', '\u{0153}', '\u{0178}'])
+    Charset::latin1_french().into_iter()
+}
+
+/// Returns an iterator of the chars in the CJK Unified Ideographs block,
+/// the common Chinese, Japanese, and Korean ideographs.  This is over 20,000
+/// chars, so most atlases will want a smaller subset.  See [`Charset::cjk`].
+pub fn cjk() -> impl Clone + Iterator<Item = char> {
+    Charset::cjk().into_iter()
+}
+
+/// Returns the chars in `chars` which `face` has no glyph for, to check that
+/// a font covers a character set before building an atlas from it.
+pub fn missing_chars<'f>(
+    face: &'f Face<'_>,
+    chars: impl 'f + IntoIterator<Item = char>,
+) -> impl 'f + Iterator<Item = char> {
+    chars
+        .into_iter()
+        .filter(move |&ch| face.glyph_index(ch).is_none())
+}
+
+/// Whether a request is left out by the builder's filter.  Requests for
+/// glyphs the face lacks are kept, so measuring them reports the error.
+fn filtered_out<T>(filter: Option<&Filter>, request: &GlyphRequest<'_, T>) -> bool {
+    match (filter, request.glyph_id(request.face)) {
+        (Some(Filter(keep)), Some(glyph_id)) => {
+            !keep(request.face, request.key.codepoint(), glyph_id)
+        }
+        _ => false,
+    }
+}
+
+/// Leave out requests which repeat an earlier one, ignoring their user data
+fn unique_requests<'a, T, I>(glyphs: &I) -> impl 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>
+where
+    I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+{
+    let mut seen = std::collections::HashSet::new();
+    let repeated: Vec<bool> = glyphs
+        .clone()
+        .map(|request| {
+            let transform = request
+                .transform
+                .map(|transform| transform.map(f32::to_bits));
+            let identity = (
+                request.face as *const Face<'_>,
+                request.key,
+                request.color,
+                transform,
+            );
+            !seen.insert(identity)
+        })
+        .collect();
+    glyphs
+        .clone()
+        .zip(repeated)
+        .filter_map(|(request, repeated)| (!repeated).then_some(request))
+}
+
+/// A glyph placed in the atlas
+struct PackedGlyph<'a, T> {
+    request: GlyphRequest<'a, T>,
+    size: RasteredSize,
+    /// The texels the glyph is rendered to, not including the gutter between
+    /// glyphs
+    rect: crunch::Rect,
+    /// Whether the glyph is rendered a quarter turn from upright, so the
+    /// rect's width covers the glyph's height
+    rotated: bool,
 }
 
-type PackResult<'a, T> = Vec<crunch::PackedItem<Box<(GlyphRequest<'a, T>, RasteredSize)>>>;
+type PackResult<'a, T> = Vec<PackedGlyph<'a, T>>;
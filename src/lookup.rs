@@ -0,0 +1,95 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::ops::{Bound, RangeBounds};
+
+use crate::Glyph;
+
+/// Glyphs sorted by codepoint, for finding them without scanning the whole
+/// metadata, see [`SdfFontAsset::codepoint_index`](crate::SdfFontAsset::codepoint_index)
+#[derive(Clone, Debug)]
+pub struct CodepointIndex<'a, T> {
+    metadata: &'a [Glyph<T>],
+    /// Each codepoint with the index of its glyph, sorted by codepoint and
+    /// then by index, including aliases with the index of the glyph they
+    /// share
+    sorted: Vec<(char, usize)>,
+}
+
+impl<'a, T> CodepointIndex<'a, T> {
+    pub(crate) fn new(metadata: &'a [Glyph<T>], aliases: &[(char, char)]) -> Self {
+        let mut sorted: Vec<(char, usize)> = metadata
+            .iter()
+            .enumerate()
+            .filter_map(|(index, glyph)| Some((glyph.key.codepoint()?, index)))
+            .collect();
+        sorted.sort_unstable();
+        let glyphs = Self { metadata, sorted };
+        // aliases only stand in for codepoints without glyphs of their own
+        let mut aliased: Vec<(char, usize)> = aliases
+            .iter()
+            .filter(|&&(alias, _)| glyphs.get(alias).is_none())
+            .filter_map(|&(alias, target)| Some((alias, glyphs.index_of(target)?)))
+            .collect();
+        if aliased.is_empty() {
+            return glyphs;
+        }
+        aliased.extend(glyphs.sorted);
+        aliased.sort_unstable();
+        Self {
+            metadata,
+            sorted: aliased,
+        }
+    }
+
+    /// The glyph for a codepoint.  If several faces have a glyph for it, the
+    /// first in the metadata is returned.
+    pub fn get(&self, codepoint: char) -> Option<&'a Glyph<T>> {
+        Some(&self.metadata[self.index_of(codepoint)?])
+    }
+
+    /// The index in the metadata of the glyph for a codepoint
+    fn index_of(&self, codepoint: char) -> Option<usize> {
+        let start = self.sorted.partition_point(|&(known, _)| known < codepoint);
+        match self.sorted.get(start) {
+            Some(&(known, index)) if known == codepoint => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Every glyph for a codepoint in `range`, in order of codepoint.  An
+    /// alias in the range gives the glyph it shares, whose key is the
+    /// codepoint it points to.
+    /// Unicode blocks and most scripts are contiguous ranges, such as
+    /// `'\u{0370}'..='\u{03ff}'` for Greek, so this finds the glyphs a run
+    /// of text in one script may need.
+    pub fn range(
+        &self,
+        range: impl RangeBounds<char>,
+    ) -> impl '_ + DoubleEndedIterator<Item = &'a Glyph<T>> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => self.sorted.partition_point(|&(known, _)| known < start),
+            Bound::Excluded(&start) => self.sorted.partition_point(|&(known, _)| known <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => self.sorted.partition_point(|&(known, _)| known <= end),
+            Bound::Excluded(&end) => self.sorted.partition_point(|&(known, _)| known < end),
+            Bound::Unbounded => self.sorted.len(),
+        };
+        self.sorted[start..end.max(start)]
+            .iter()
+            .map(|&(_, index)| &self.metadata[index])
+    }
+
+    /// Each codepoint with the index of its glyph in the metadata, in order
+    /// of codepoint, with only the first glyph for each codepoint
+    pub(crate) fn first_of_each(&self) -> impl '_ + Iterator<Item = (char, usize)> {
+        let mut last = None;
+        self.sorted.iter().copied().filter(move |&(codepoint, _)| {
+            let first = last != Some(codepoint);
+            last = Some(codepoint);
+            first
+        })
+    }
+}
@@ -0,0 +1,88 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// A smaller copy of the atlas, see
+/// [`FontAssetBuilder::with_mipmaps`](crate::FontAssetBuilder::with_mipmaps)
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MipLevel {
+    /// The width of the level in pixels
+    pub width: u16,
+
+    /// The height of the level in pixels
+    pub height: u16,
+
+    /// The image data, with the same channels and layers as the full size
+    /// atlas
+    pub data: Vec<u8>,
+
+    /// The number of bytes from the start of one row of
+    /// [`data`](Self::data) to the next, see
+    /// [`SdfFontAsset::row_stride`](crate::SdfFontAsset::row_stride)
+    pub row_stride: usize,
+}
+
+/// Halve an image, rounding odd sizes down as graphics APIs do, by
+/// averaging each 2 by 2 block.  Texel values encode distance linearly, so
+/// this averages distances, and the outline stays at the same value.  The
+/// encoding keeps the spread of the full size atlas, so shaders decode every
+/// level the same way.
+fn half(width: u16, height: u16, channels: usize, data: &[u8]) -> MipLevel {
+    let (src_w, src_h) = (usize::from(width), usize::from(height));
+    let (dst_w, dst_h) = ((src_w / 2).max(1), (src_h / 2).max(1));
+    let mut out = Vec::with_capacity(dst_w * dst_h * channels);
+    for y in 0..dst_h {
+        let rows = [(2 * y).min(src_h - 1), (2 * y + 1).min(src_h - 1)];
+        for x in 0..dst_w {
+            let cols = [(2 * x).min(src_w - 1), (2 * x + 1).min(src_w - 1)];
+            for channel in 0..channels {
+                let sum: u32 = rows
+                    .iter()
+                    .flat_map(|&row| cols.iter().map(move |&col| (row, col)))
+                    .map(|(row, col)| u32::from(data[(row * src_w + col) * channels + channel]))
+                    .sum();
+                out.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+    MipLevel {
+        width: dst_w as u16,
+        height: dst_h as u16,
+        data: out,
+        row_stride: dst_w * channels,
+    }
+}
+
+/// Every level below the full size atlas, down to 1 by 1, with each layer
+/// reduced separately
+pub fn mip_chain(width: u16, height: u16, channels: u8, data: &[u8]) -> Vec<MipLevel> {
+    let layer_len = usize::from(width) * usize::from(height) * usize::from(channels);
+    let mut layers = data
+        .chunks_exact(layer_len)
+        .map(|layer| layer_chain(width, height, channels, layer));
+    let Some(mut levels) = layers.next() else {
+        return Vec::new();
+    };
+    for layer in layers {
+        for (level, layer) in levels.iter_mut().zip(layer) {
+            level.data.extend(layer.data);
+        }
+    }
+    levels
+}
+
+/// Every level below a single full size layer
+fn layer_chain(width: u16, height: u16, channels: u8, data: &[u8]) -> Vec<MipLevel> {
+    let channels = usize::from(channels);
+    let mut levels: Vec<MipLevel> = Vec::new();
+    let (mut width, mut height) = (width, height);
+    while width > 1 || height > 1 {
+        let level = match levels.last() {
+            Some(last) => half(width, height, channels, &last.data),
+            None => half(width, height, channels, data),
+        };
+        (width, height) = (level.width, level.height);
+        levels.push(level);
+    }
+    levels
+}
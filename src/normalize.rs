@@ -0,0 +1,82 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::raster::Buffer;
+
+/// Gradients flatter or steeper than this are left alone, since dividing by
+/// them would amplify noise more than it corrects the field.
+const GRADIENT_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+
+/// Only texels this close to the outline, in texels, are adjusted; this is
+/// where a shader's anti-aliasing happens, and farther out the gradient is
+/// dominated by ridges where the nearest outline changes.
+const BAND: f32 = 1.5;
+
+/// The largest change in slope, in texels per texel, across a texel for the
+/// field to be considered locally linear.
+const KINK: f32 = 0.25;
+
+/// Rescale the distances in one glyph's rect so the gradient magnitude is
+/// close to one texel per texel, by dividing each distance by the magnitude
+/// of its gradient.  This is one step of first-order redistancing; it fixes
+/// compressed gradients near thin features, where the nearest outline on
+/// either side pulls the field in different directions.
+///
+/// Only texels near the outline whose neighbors all lie within the range of
+/// the field are adjusted, since the gradient is meaningless where values
+/// are clamped.  The gradient is estimated with a Sobel filter to smooth
+/// over quantization.
+pub fn normalize_gradient(buffer: &mut Buffer<'_>, rect: &crunch::Rect, spread_px: f32) {
+    let width = rect.w;
+    let height = rect.h;
+    if width < 3 || height < 3 || spread_px <= 0.0 {
+        return;
+    }
+    let to_dist = |value: u8| (0.5 - f32::from(value) / f32::from(u8::MAX)) * 2.0 * spread_px;
+    let mut dist = Vec::with_capacity(width * height);
+    let mut clamped = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let value = buffer.get_pixel((rect.x + x, rect.y + y));
+            dist.push(to_dist(value));
+            clamped.push(value == 0 || value == u8::MAX);
+        }
+    }
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let i = y * width + x;
+            if dist[i].abs() > BAND {
+                continue;
+            }
+            let neighbors = [i - width - 1, i - width, i - width + 1, i - 1, i + 1]
+                .into_iter()
+                .chain([i + width - 1, i + width, i + width + 1]);
+            if neighbors.clone().any(|j| clamped[j]) {
+                continue;
+            }
+            let [bl, b, br, l, r, tl, t, tr] = {
+                let mut values = [0.0; 8];
+                for (value, j) in values.iter_mut().zip(neighbors) {
+                    *value = dist[j];
+                }
+                values
+            };
+            // dividing by the gradient is only valid where the field is close
+            // to linear, not across ridges where the nearest outline changes
+            let center = dist[i];
+            if (r - center - (center - l)).abs() > KINK || (t - center - (center - b)).abs() > KINK
+            {
+                continue;
+            }
+            let dx = ((br + 2.0 * r + tr) - (bl + 2.0 * l + tl)) / 8.0;
+            let dy = ((tl + 2.0 * t + tr) - (bl + 2.0 * b + br)) / 8.0;
+            let magnitude = (dx * dx + dy * dy).sqrt();
+            if !GRADIENT_RANGE.contains(&magnitude) {
+                continue;
+            }
+            let signed_dist = 0.5 - (dist[i] / magnitude) / (2.0 * spread_px);
+            let value = (f32::from(u8::MAX) * signed_dist.clamp(0.0, 1.0)) as u8;
+            buffer.set_pixel((rect.x + x, rect.y + y), value);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// Check that rects in pages of `width` by `height` stacked from the bottom
+/// up each lie within their page, after the gutter along its left and
+/// bottom edges, and that no two overlap, counting the gutter left of and
+/// below each.  Texels outside a glyph's rect are sampled when its edges
+/// are filtered, so any overlap would leak one glyph into another.
+///
+/// Builds run this in debug builds, after packing, to catch mistakes in the
+/// arithmetic which places rects and strips their gutters.
+///
+/// # Panics
+///
+/// Panics, naming the rects, if they break these rules.
+pub fn check_rects(width: u16, height: u16, gutter: u16, rects: &[crunch::Rect]) {
+    let (width, height, gutter) = (usize::from(width), usize::from(height), usize::from(gutter));
+    for rect in rects {
+        let y = rect.y % height;
+        assert!(
+            rect.x >= gutter && y >= gutter && rect.x + rect.w <= width && y + rect.h <= height,
+            "{rect:?} is outside its {width}x{height} page, or its gutter of {gutter} is"
+        );
+    }
+    // sweep from left to right, comparing each rect with those which start
+    // before it ends
+    let mut order: Vec<&crunch::Rect> = rects.iter().collect();
+    order.sort_unstable_by_key(|rect| rect.x);
+    for (index, a) in order.iter().enumerate() {
+        for b in order[index + 1..]
+            .iter()
+            .take_while(|b| b.x - gutter < a.x + a.w)
+        {
+            assert!(
+                b.y + b.h <= a.y - gutter || a.y + a.h <= b.y - gutter,
+                "{a:?} and {b:?} overlap, counting a gutter of {gutter}"
+            );
+        }
+    }
+}
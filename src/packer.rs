@@ -0,0 +1,95 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::fmt::Debug;
+
+/// A rectangle for a [`Packer`] to place
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PackerItem {
+    /// The width of the rectangle.  This includes the gutter to leave on its
+    /// right, so packers only need to keep items from overlapping.
+    pub width: u32,
+
+    /// The height of the rectangle, including the gutter to leave above it
+    pub height: u32,
+
+    /// Whether the rectangle may be placed a quarter turn from upright, so
+    /// its width and height are swapped
+    pub allow_rotation: bool,
+}
+
+/// Where a [`Packer`] placed an item
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackerPlacement {
+    /// The index of the item in the slice given to [`Packer::pack`]
+    pub item: usize,
+
+    /// The left edge of the item
+    pub x: u32,
+
+    /// The bottom edge of the item
+    pub y: u32,
+
+    /// Whether the item was turned, so it covers its height horizontally
+    /// and its width vertically
+    pub rotated: bool,
+}
+
+/// An algorithm for arranging glyphs in the atlas, see
+/// [`FontAssetBuilder::with_packer`](crate::FontAssetBuilder::with_packer).
+///
+/// The builder searches for the font or atlas size by packing the glyphs
+/// many times over, so packers should be fast, and must be deterministic for
+/// builds to be reproducible.
+pub trait Packer: Sync + Debug {
+    /// Place every item within a `width` by `height` area, with no two
+    /// overlapping, or return `None` if they don't fit.  Each item must be
+    /// placed exactly once, in any order; the asset's metadata lists glyphs
+    /// in the order they were requested either way.
+    ///
+    /// # Panics
+    ///
+    /// Building panics if the placements break these rules.
+    fn pack(&self, width: u32, height: u32, items: &[PackerItem]) -> Option<Vec<PackerPlacement>>;
+}
+
+/// The packer used by [`PackingStrategy::Tight`](crate::PackingStrategy::Tight),
+/// which fits glyphs together as closely as possible
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TightPacker;
+
+impl Packer for TightPacker {
+    fn pack(&self, width: u32, height: u32, items: &[PackerItem]) -> Option<Vec<PackerPlacement>> {
+        let crunch_items = items.iter().enumerate().map(|(index, item)| crunch::Item {
+            data: index,
+            w: item.width as usize,
+            h: item.height as usize,
+            rot: if item.allow_rotation {
+                crunch::Rotation::Allowed
+            } else {
+                crunch::Rotation::None
+            },
+        });
+        let into_rect = crunch::Rect {
+            x: 0,
+            y: 0,
+            w: width as usize,
+            h: height as usize,
+        };
+        let packed = crunch::Packer::with_items(crunch_items)
+            .pack(into_rect)
+            .ok()?;
+        let placements = packed
+            .into_iter()
+            .map(|packed| PackerPlacement {
+                item: packed.data,
+                x: packed.rect.x as u32,
+                y: packed.rect.y as u32,
+                // crunch doesn't report rotation, but it swaps the sides
+                rotated: packed.rect.w != items[packed.data].width as usize,
+            })
+            .collect();
+        Some(placements)
+    }
+}
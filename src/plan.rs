@@ -0,0 +1,537 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use ttf_parser::Face;
+
+use crate::{
+    face, raster, Error, FaceHandle, FontAssetBuilder, Glyph, GlyphKey, GlyphRequest, MipLevel,
+    PackResult, PackedGlyph, ReservedRect, SdfFontAsset, SkippedGlyph,
+};
+
+/// The layout of an asset, measured and packed but not yet rendered.  See
+/// [`FontAssetBuilder::plan`].
+pub struct AssetPlan<'a, T> {
+    pub(crate) builder: FontAssetBuilder,
+    pub(crate) width: u16,
+    pub(crate) height: u16,
+    /// The number of layers, times the pages packed into each
+    pub(crate) pages: u16,
+    pub(crate) font_size: f32,
+    pub(crate) packing: PackResult<'a, T>,
+    pub(crate) reserved: Vec<ReservedRect>,
+    pub(crate) skipped: Vec<SkippedGlyph<T>>,
+    pub(crate) faces: Vec<&'a Face<'a>>,
+}
+
+/// Where a glyph will be rendered in the atlas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Placement {
+    /// The left edge of the glyph, in texels
+    pub x: u16,
+
+    /// The bottom edge of the glyph, in texels
+    pub y: u16,
+
+    /// The layer of the image the glyph will be in, see
+    /// [`FontAssetBuilder::with_layers`]
+    pub layer: u16,
+
+    /// The channel of the image the glyph will be in, see
+    /// [`FontAssetBuilder::with_channel_packing`].  This is 0 otherwise.
+    pub channel: u8,
+
+    /// The width of the glyph as rendered in the atlas, in texels
+    pub width: u16,
+
+    /// The height of the glyph as rendered in the atlas, in texels
+    pub height: u16,
+
+    /// Whether the glyph will be rotated
+    pub rotated: bool,
+}
+
+/// One finished layer of an asset's image, see [`AssetPlan::raster_layers`]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LayerImage {
+    /// The layer of the image, counting from 0
+    pub layer: u16,
+
+    /// The layer's texels, laid out like a single layer of
+    /// [`SdfFontAsset::data`]
+    pub data: Vec<u8>,
+
+    /// The layer's mip levels, each laid out like a single layer of the
+    /// level in [`SdfFontAsset::mip_levels`]
+    pub mip_levels: Vec<MipLevel>,
+}
+
+/// Diagnostics collected while rendering an asset, see
+/// [`AssetPlan::raster_with_stats`]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct BuildStats {
+    /// The total time spent rendering glyphs
+    pub total: Duration,
+
+    /// The glyphs which took the longest to render, slowest first
+    pub slowest: Vec<GlyphTiming>,
+
+    /// Signs that a face is broken, which may explain glyphs which come out
+    /// too small or lines of text which overlap
+    pub warnings: Vec<FontWarning>,
+}
+
+/// A sign that a face is broken, see [`BuildStats::warnings`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FontWarning {
+    /// The face's ascender is at or below its descender, so its lines have
+    /// no height.  Glyphs are sized by the face's units per em, so they are
+    /// unaffected, but text laid out with the face's metrics may not be.
+    NoLineHeight(FaceHandle),
+
+    /// A glyph's outline reaches more than [`FAR_PAST_EM`] ems beyond the
+    /// em box, the square from the pen position to one em right of and
+    /// above it.  The face's units per em is likely too small for its
+    /// outlines, which shrinks every other glyph to fit this one.
+    FarPastEm {
+        /// The face of the glyph
+        face: FaceHandle,
+
+        /// The character or glyph id of the glyph
+        key: GlyphKey,
+
+        /// How far the outline reaches beyond the em box, in ems
+        reach: f32,
+    },
+}
+
+/// How far past the em box, in ems, a glyph's outline may reach before
+/// [`FontWarning::FarPastEm`] reports it.  Outlines of sound fonts, even
+/// tall stacked accents and wide swashes, stay well within this.
+pub const FAR_PAST_EM: f32 = 2.0;
+
+/// The time taken to render a single glyph
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct GlyphTiming {
+    /// The character or glyph id of the glyph
+    pub key: GlyphKey,
+
+    /// The number of lines and curves in the glyph's outline
+    pub edges: usize,
+
+    /// The time taken to render the glyph
+    pub duration: Duration,
+}
+
+impl<'a, T> AssetPlan<'a, T> {
+    /// The width of the image in pixels
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// The height of the image in pixels
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The number of layers in the image, each `width` by `height`
+    pub fn layers(&self) -> u16 {
+        self.pages / self.builder.pages_per_layer()
+    }
+
+    /// The font size glyphs will be rendered at, in pixels per em
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    /// The glyphs in the plan, in the order they were requested, with where
+    /// each will be rendered
+    pub fn glyphs(&self) -> impl Iterator<Item = (&GlyphRequest<'a, T>, Placement)> {
+        let pages_per_layer = usize::from(self.builder.pages_per_layer());
+        self.packing.iter().map(move |item| {
+            let page = item.rect.y / usize::from(self.height);
+            let placement = Placement {
+                x: item.rect.x as u16,
+                y: (item.rect.y % usize::from(self.height)) as u16,
+                layer: (page / pages_per_layer) as u16,
+                channel: (page % pages_per_layer) as u8,
+                width: item.rect.w as u16,
+                height: item.rect.h as u16,
+                rotated: item.rotated,
+            };
+            (&item.request, placement)
+        })
+    }
+
+    /// The distinct faces among the requests, in the order of their
+    /// [`FaceHandle`](crate::FaceHandle)s
+    pub fn faces(&self) -> &[&'a Face<'a>] {
+        &self.faces
+    }
+
+    /// Where the rects given to
+    /// [`with_reserved_rects`](FontAssetBuilder::with_reserved_rects) will
+    /// be, in the order they were given
+    pub fn reserved(&self) -> &[ReservedRect] {
+        &self.reserved
+    }
+
+    /// The requested glyphs which will be left out of the asset
+    pub fn skipped(&self) -> &[SkippedGlyph<T>] {
+        &self.skipped
+    }
+
+    /// Remove glyphs from the plan.  The remaining glyphs keep their
+    /// placement; to pack them more tightly, plan again with only the
+    /// glyphs to keep.
+    pub fn retain(&mut self, mut keep: impl FnMut(&GlyphRequest<'a, T>) -> bool) {
+        self.packing.retain(|item| keep(&item.request));
+    }
+
+    /// Render the glyphs, producing the asset.  This is the expensive part
+    /// of building an asset.  Every layer of the image is held until the
+    /// asset is returned, see [`raster_layers`](Self::raster_layers) to
+    /// render with less memory.
+    pub fn raster(self) -> Result<SdfFontAsset<T>, Error> {
+        self.raster_collected(|_| ())
+    }
+
+    /// Render the glyphs like [`raster`](Self::raster), timing each one.  The
+    /// `slowest` glyphs are reported, to help find decorative glyphs with
+    /// complex outlines which dominate build time.
+    pub fn raster_with_stats(self, slowest: usize) -> Result<(SdfFontAsset<T>, BuildStats), Error> {
+        let mut timings = Vec::new();
+        let start = Instant::now();
+        let warnings = self.font_warnings();
+        let asset = self.raster_collected(|timing| timings.push(timing))?;
+        let total = start.elapsed();
+        timings.sort_by_key(|timing: &GlyphTiming| std::cmp::Reverse(timing.duration));
+        timings.truncate(slowest);
+        let stats = BuildStats {
+            total,
+            slowest: timings,
+            warnings,
+        };
+        Ok((asset, stats))
+    }
+
+    /// Check the faces and glyphs of the plan for signs of broken fonts
+    fn font_warnings(&self) -> Vec<FontWarning> {
+        let variations = &self.builder.variations;
+        let mut warnings: Vec<FontWarning> = self
+            .faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| variations.apply(face).height() <= 0)
+            .map(|(index, _)| FontWarning::NoLineHeight(FaceHandle(index as u16)))
+            .collect();
+        for item in &self.packing {
+            let face = variations.apply(item.request.face);
+            let Some(bbox) = item
+                .request
+                .glyph_id(&face)
+                .and_then(|glyph_id| face.glyph_bounding_box(glyph_id))
+            else {
+                continue;
+            };
+            let units_per_em = f32::from(face.units_per_em());
+            let ems = |units: i16| f32::from(units) / units_per_em;
+            let reach = [
+                -ems(bbox.x_min),
+                ems(bbox.x_max) - 1.0,
+                -ems(bbox.y_min),
+                ems(bbox.y_max) - 1.0,
+            ]
+            .into_iter()
+            .fold(0.0, f32::max);
+            if reach > FAR_PAST_EM {
+                warnings.push(FontWarning::FarPastEm {
+                    face: face::handle_of(&self.faces, item.request.face),
+                    key: item.request.key,
+                    reach,
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Render the glyphs, collecting the layers into one image
+    fn raster_collected(self, record: impl FnMut(GlyphTiming)) -> Result<SdfFontAsset<T>, Error> {
+        // reserve the whole image up front, so growing it never holds two
+        // copies at once
+        let mut data = Vec::with_capacity(self.image_len());
+        let mut mip_levels: Vec<MipLevel> = Vec::new();
+        let mut asset = self.raster_timed(record, |layer| {
+            data.extend(layer.data);
+            if mip_levels.is_empty() {
+                mip_levels = layer.mip_levels;
+            } else {
+                for (level, layer) in mip_levels.iter_mut().zip(layer.mip_levels) {
+                    level.data.extend(layer.data);
+                }
+            }
+        })?;
+        asset.data = data;
+        asset.mip_levels = mip_levels;
+        Ok(asset)
+    }
+
+    /// Render the glyphs like [`raster`](Self::raster), but one layer at a
+    /// time, giving each layer to `each_layer` as soon as it is finished and
+    /// then dropping it, so only one layer of the image is held at once.
+    /// This is the low-memory path, see
+    /// [`build_layers`](FontAssetBuilder::build_layers).
+    ///
+    /// The returned asset has the metadata, but its
+    /// [`data`](SdfFontAsset::data) and
+    /// [`mip_levels`](SdfFontAsset::mip_levels) are empty.
+    pub fn raster_layers(
+        self,
+        each_layer: impl FnMut(LayerImage),
+    ) -> Result<SdfFontAsset<T>, Error> {
+        self.raster_timed(|_| (), each_layer)
+    }
+
+    /// The length of the finished image, not counting mip levels
+    fn image_len(&self) -> usize {
+        let builder = &self.builder;
+        let channels = (1 + builder.coverage_bytes()) * usize::from(builder.pages_per_layer());
+        let row_stride = builder.row_stride(self.width, channels as u8);
+        row_stride * usize::from(self.height) * usize::from(self.layers())
+    }
+
+    fn raster_timed(
+        mut self,
+        mut record: impl FnMut(GlyphTiming),
+        mut each_layer: impl FnMut(LayerImage),
+    ) -> Result<SdfFontAsset<T>, Error> {
+        let builder = self.builder.clone();
+        let config = builder.raster_config(self.font_size);
+        let width = self.width;
+        let pages_per_layer = builder.pages_per_layer();
+        let layer_height = usize::from(self.height) * usize::from(pages_per_layer);
+        let layer_len = usize::from(width) * layer_height;
+        let mut channels = 1;
+        // the glyphs rendered again, by their index in the packing, which
+        // the metadata follows
+        let mut retried = Vec::new();
+        // each layer's glyphs are taken out of the plan once it is rendered
+        // and kept only as metadata, so their requests and sizes are not
+        // held until the whole image is done
+        let mut packing: Vec<Option<PackedGlyph<'a, T>>> = std::mem::take(&mut self.packing)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut placed: Vec<Option<(crunch::Rect, Glyph<T>)>> = std::iter::repeat_with(|| None)
+            .take(packing.len())
+            .collect();
+        for layer in 0..self.layers() {
+            let bottom = usize::from(layer) * layer_height;
+            let mut buf = vec![0; layer_len];
+            let coverage_len = layer_len * builder.coverage_bytes();
+            let mut coverage = builder.coverage.then(|| vec![0; coverage_len]);
+            let mut glyphs = Vec::new();
+            let indices: Vec<usize> = (0..packing.len())
+                .filter(|&index| {
+                    packing[index]
+                        .as_ref()
+                        .is_some_and(|item| (bottom..bottom + layer_height).contains(&item.rect.y))
+                })
+                .collect();
+            let item_at = |index: usize| packing[index].as_ref().unwrap();
+            if builder.threads > 1 {
+                // render each glyph on its own, then copy them in the order
+                // of the packing, whichever thread finished first
+                let items: Vec<PackedGlyph<'a, ()>> = indices
+                    .iter()
+                    .map(|&index| {
+                        let item = item_at(index);
+                        PackedGlyph {
+                            request: item.request.without_user_data(),
+                            size: item.size,
+                            rect: crunch::Rect {
+                                x: 0,
+                                y: 0,
+                                ..item.rect
+                            },
+                            rotated: item.rotated,
+                        }
+                    })
+                    .collect();
+                let coverage_bytes = builder.coverage.then(|| builder.coverage_bytes());
+                let tiles = raster_tiles(&items, config, coverage_bytes, builder.threads)?;
+                for ((&index, item), tile) in indices.iter().zip(&items).zip(tiles) {
+                    let rect = item_at(index).rect;
+                    let rect = crunch::Rect {
+                        y: rect.y - bottom,
+                        ..rect
+                    };
+                    blit(&mut buf, width, 1, &rect, &tile.data);
+                    if let (Some(coverage), Some(tile)) = (&mut coverage, &tile.coverage) {
+                        blit(coverage, width, builder.coverage_bytes(), &rect, tile);
+                    }
+                    record(GlyphTiming {
+                        key: item.request.key,
+                        edges: tile.edges,
+                        duration: tile.duration,
+                    });
+                    if tile.retried {
+                        retried.push(index);
+                    }
+                    glyphs.push(rect);
+                }
+            } else {
+                for &index in &indices {
+                    let item = packing[index].as_mut().unwrap();
+                    // render into this layer's buffer, then put the glyph back
+                    // where the metadata expects it
+                    item.rect.y -= bottom;
+                    let start = Instant::now();
+                    let (edges, again) = raster::raster(
+                        raster::Buffer {
+                            data: &mut buf,
+                            width,
+                        },
+                        coverage.as_mut().map(|data| raster::Buffer { data, width }),
+                        config,
+                        item,
+                    )?;
+                    record(GlyphTiming {
+                        key: item.request.key,
+                        edges,
+                        duration: start.elapsed(),
+                    });
+                    if again {
+                        retried.push(index);
+                    }
+                    glyphs.push(item.rect);
+                    item.rect.y += bottom;
+                }
+            }
+            let reserved: Vec<crunch::Rect> = self
+                .reserved
+                .iter()
+                .filter(|rect| rect.layer == layer)
+                .map(|rect| {
+                    let rect = rect.as_rect(self.height, pages_per_layer);
+                    crunch::Rect {
+                        y: rect.y - bottom,
+                        ..rect
+                    }
+                })
+                .collect();
+            let (layer_channels, data, mip_levels) =
+                builder.finish_pages(&self, buf, coverage, &glyphs, &reserved);
+            channels = layer_channels;
+            each_layer(LayerImage {
+                layer,
+                data,
+                mip_levels,
+            });
+            for index in indices {
+                if let Some(item) = packing[index].take() {
+                    placed[index] = Some((item.rect, Glyph::placed(&self, item)));
+                }
+            }
+        }
+        let spread_px = builder.padding.spread(self.font_size) * self.font_size;
+        let channels = channels * pages_per_layer as u8;
+        let placed = placed.into_iter().flatten().collect();
+        let mut asset = SdfFontAsset::from_placed(self, placed, spread_px, builder.mode, channels);
+        for index in retried {
+            asset.metadata[index].retried = true;
+        }
+        Ok(asset)
+    }
+}
+
+/// A glyph rendered into a buffer of its own, see [`raster_tiles`]
+struct Tile {
+    data: Vec<u8>,
+    coverage: Option<Vec<u8>>,
+    edges: usize,
+    retried: bool,
+    duration: Duration,
+}
+
+/// Render glyphs placed at the origin on as many as `threads` threads, each
+/// into a tile the size of its rect, and return the tiles in the order of
+/// `items`.  Coverage is rendered too if `coverage_bytes` is given.
+fn raster_tiles(
+    items: &[PackedGlyph<'_, ()>],
+    config: raster::RasterConfig,
+    coverage_bytes: Option<usize>,
+    threads: usize,
+) -> Result<Vec<Tile>, Error> {
+    // threads take the next glyph when they finish one, so a few complex
+    // glyphs don't hold up the rest
+    let next = AtomicUsize::new(0);
+    let render = || -> Result<Vec<(usize, Tile)>, Error> {
+        let mut done = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(item) = items.get(index) else {
+                return Ok(done);
+            };
+            let len = item.rect.w * item.rect.h;
+            let width = item.rect.w as u16;
+            let mut data = vec![0; len];
+            let mut coverage = coverage_bytes.map(|bytes| vec![0; len * bytes]);
+            let start = Instant::now();
+            let (edges, retried) = raster::raster(
+                raster::Buffer {
+                    data: &mut data,
+                    width,
+                },
+                coverage.as_mut().map(|data| raster::Buffer { data, width }),
+                config,
+                item,
+            )?;
+            let tile = Tile {
+                data,
+                coverage,
+                edges,
+                retried,
+                duration: start.elapsed(),
+            };
+            done.push((index, tile));
+        }
+    };
+    let mut tiles = Vec::with_capacity(items.len());
+    std::thread::scope(|scope| -> Result<(), Error> {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| scope.spawn(render))
+            .collect();
+        for worker in workers {
+            let done = worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+            tiles.extend(done);
+        }
+        Ok(())
+    })?;
+    tiles.sort_unstable_by_key(|&(index, _)| index);
+    Ok(tiles.into_iter().map(|(_, tile)| tile).collect())
+}
+
+/// Copy a tile into a buffer `width` texels wide of `bytes` per texel, at
+/// the texels of `rect`
+fn blit(buffer: &mut [u8], width: u16, bytes: usize, rect: &crunch::Rect, tile: &[u8]) {
+    let row_len = rect.w * bytes;
+    if row_len == 0 {
+        return;
+    }
+    for (y, row) in tile.chunks_exact(row_len).enumerate() {
+        let start = ((rect.y + y) * usize::from(width) + rect.x) * bytes;
+        buffer[start..][..row_len].copy_from_slice(row);
+    }
+}
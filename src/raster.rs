@@ -1,13 +1,116 @@
 /* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
 /* Copyright © 2023 Violet Leonard */
 
-use ttf_parser::Face;
+use std::borrow::Cow;
+
+use ttf_parser::{Face, Tag};
 
 use crate::{
-    edge::{CubicCurve, EdgeBoundingBox, Line, QuadCurve, Segment},
-    GlyphRequest,
+    edge::{CubicCurve, Edge, EdgeBoundingBox, Line, QuadCurve, Segment},
+    edt::Mask,
+    DistanceMode, DistanceNorm, GlyphRequest, PackedGlyph, RasterBackend, SamplePosition,
 };
 
+/// Options which affect how each texel of a glyph is computed
+#[derive(Clone, Copy, Debug)]
+pub struct RasterConfig {
+    /// The padding on the left, right, bottom, and top sides as a ratio of
+    /// the font size
+    pub padding: [f32; 4],
+    pub backend: RasterBackend,
+    pub norm: DistanceNorm,
+    pub mode: DistanceMode,
+    /// The distance to offset the outline outward, as a ratio of the font
+    /// size
+    pub bold: f32,
+    /// The distance the outline may move when it is simplified, as a ratio
+    /// of the font size
+    pub simplify: f32,
+    pub variations: Variations,
+    /// Whether coverage is measured for each RGB subpixel, rather than for
+    /// each texel
+    pub subpixel: bool,
+    /// Whether to search for the nearest point on curves more thoroughly,
+    /// and in `f64`, for glyphs whose distances came out broken the first
+    /// time
+    pub careful: bool,
+}
+
+impl RasterConfig {
+    /// The range of the distance field as a ratio of the font size
+    pub fn spread(&self) -> f32 {
+        self.padding.into_iter().fold(0.0, f32::max)
+    }
+
+    /// The space on the left, right, bottom, and top of the glyph's
+    /// bounding box, including room for the outline to grow, as a ratio of
+    /// the font size
+    pub fn margin(&self) -> [f32; 4] {
+        let grow = self.bold.max(0.0);
+        self.padding.map(|side| side + grow)
+    }
+
+    /// Encode the signed distance from a point to the outline, as a ratio of
+    /// the font size and positive outside, as a texel value
+    pub fn encode(&self, dist: f32) -> u8 {
+        let spread = self.spread();
+        let value = match self.mode {
+            DistanceMode::Signed => 0.5 - ((dist - self.bold) / spread) * 0.5,
+            DistanceMode::Unsigned => 1.0 - (dist.abs() - self.bold) / spread,
+        };
+        (f32::from(u8::MAX) * value.clamp(0.0, 1.0)) as u8
+    }
+}
+
+/// The most variation axes ttf-parser can apply to a face
+const MAX_VARIATIONS: usize = 32;
+
+/// Coordinates on a variable font's axes, applied to each face before its
+/// glyphs are outlined
+#[derive(Clone, Copy, Debug)]
+pub struct Variations {
+    axes: [(Tag, f32); MAX_VARIATIONS],
+    len: usize,
+}
+
+impl Variations {
+    pub fn new(variations: &[(Tag, f32)]) -> Self {
+        let mut axes = [(Tag(0), 0.0); MAX_VARIATIONS];
+        let len = variations.len().min(MAX_VARIATIONS);
+        axes[..len].copy_from_slice(&variations[..len]);
+        Self { axes, len }
+    }
+
+    /// The face with these coordinates applied, copied only if needed
+    pub fn apply<'f, 'a>(&self, face: &'f Face<'a>) -> Cow<'f, Face<'a>> {
+        if self.len == 0 || !face.is_variable() {
+            return Cow::Borrowed(face);
+        }
+        let mut face = face.clone();
+        for &(axis, value) in &self.axes[..self.len] {
+            // axes the face lacks are ignored
+            let _ = face.set_variation(axis, value);
+        }
+        Cow::Owned(face)
+    }
+}
+
+impl Default for Variations {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl DistanceNorm {
+    pub(crate) fn length(self, dx: f32, dy: f32) -> f32 {
+        match self {
+            Self::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Self::Chebyshev => dx.abs().max(dy.abs()),
+            Self::Manhattan => dx.abs() + dy.abs(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct RasteredSize {
     /// The width of the destination buffer
@@ -25,25 +128,107 @@ pub struct RasteredSize {
     pub bottom: f32,
 }
 
-pub fn get_rastered_size(
-    padding_ratio: f32,
+impl RasteredSize {
+    /// The size of a glyph with nothing to draw, such as a space, which
+    /// takes no room in the atlas
+    pub const EMPTY: Self = Self {
+        pixel_width: 0,
+        pixel_height: 0,
+        left: 0.0,
+        right: 0.0,
+        top: 0.0,
+        bottom: 0.0,
+    };
+}
+
+/// Count the texels needed to sample both ends of a span of `size` and every
+/// pixel between, and the distance from the span's ends to the edges of the
+/// outermost texels
+fn corner_texels(size: f32, font_size: f32) -> (u16, f32) {
+    let largest = f32::from(u16::MAX - 2);
+    let least = if size > 0.0 { 1.0 } else { 0.0 };
+    let spans = (size * font_size).round().clamp(least, largest) as u16;
+    let cell = if spans == 0 {
+        1.0 / font_size
+    } else {
+        size / f32::from(spans)
+    };
+    (spans + 1, cell / 2.0)
+}
+
+/// Measure the box a glyph will be rendered into
+pub fn get_rastered_size<T>(
+    [margin_left, margin_right, margin_bottom, margin_top]: [f32; 4],
     font_size: f32,
-    face: &Face<'_>,
-    ch: char,
-) -> Result<RasteredSize, char> {
-    let face_height = f32::from(face.units_per_em());
-    let padding = padding_ratio;
-    let rel_from = |font_value: i16| f32::from(font_value) / face_height;
-    let glyph_id = face.glyph_index(ch).ok_or(ch)?;
-    let bbox = face.glyph_bounding_box(glyph_id).ok_or(ch)?;
-    let width = rel_from(bbox.width()) + (2.0 * padding);
-    let height = rel_from(bbox.height()) + (2.0 * padding);
-    let pixel_width = (width * font_size).round().clamp(0.0, u16::MAX.into()) as u16;
-    let pixel_height = (height * font_size).round().clamp(0.0, u16::MAX.into()) as u16;
-    let left = rel_from(bbox.x_min) - padding;
-    let right = rel_from(bbox.x_max) + padding;
-    let top = rel_from(bbox.y_max) + padding;
-    let bottom = rel_from(bbox.y_min) - padding;
+    variations: &Variations,
+    snap: Option<f32>,
+    sample: SamplePosition,
+    request: &GlyphRequest<'_, T>,
+) -> Result<RasteredSize, crate::Error> {
+    let face = &*variations.apply(request.face);
+    let glyph_id = request
+        .glyph_id(face)
+        .ok_or(crate::Error::MissingGlyph(request.key))?;
+    let bbox = face.glyph_bounding_box(glyph_id);
+    let (x_min, x_max, y_min, y_max) = match bbox {
+        Some(bbox) if request.transform.is_none() => {
+            if bbox.x_min > bbox.x_max || bbox.y_min > bbox.y_max {
+                return Err(crate::Error::BadOutline(glyph_id));
+            }
+            let units_per_em = f32::from(face.units_per_em());
+            let rel_from = |font_value: i16| f32::from(font_value) / units_per_em;
+            (
+                rel_from(bbox.x_min),
+                rel_from(bbox.x_max),
+                rel_from(bbox.y_min),
+                rel_from(bbox.y_max),
+            )
+        }
+        // measure the outline itself, for transformed glyphs and glyphs
+        // without outlines in the font's main tables
+        _ => match glyph_segments(request, 0.0, variations)?.bounds() {
+            Some(bounds) => bounds,
+            #[cfg(feature = "bitmap")]
+            None => match crate::bitmap::Bitmap::new(face, glyph_id) {
+                Some(bitmap) => bitmap.bounds(),
+                None => return Ok(RasteredSize::EMPTY),
+            },
+            #[cfg(not(feature = "bitmap"))]
+            None => return Ok(RasteredSize::EMPTY),
+        },
+    };
+    let mut left = x_min - margin_left;
+    let mut right = x_max + margin_right;
+    let mut top = y_max + margin_top;
+    let mut bottom = y_min - margin_bottom;
+    if let Some(size) = snap {
+        left = (left * size).floor() / size;
+        right = (right * size).ceil() / size;
+        bottom = (bottom * size).floor() / size;
+        top = (top * size).ceil() / size;
+    }
+    let width = right - left;
+    let height = top - bottom;
+    // leave room for the gutter added when packing
+    let largest = f32::from(u16::MAX - 1);
+    // a glyph thinner than half a pixel still gets a texel, rather than
+    // rounding away to nothing
+    let min_x = if width > 0.0 { 1.0 } else { 0.0 };
+    let min_y = if height > 0.0 { 1.0 } else { 0.0 };
+    let mut pixel_width = (width * font_size).round().clamp(min_x, largest) as u16;
+    let mut pixel_height = (height * font_size).round().clamp(min_y, largest) as u16;
+    if sample == SamplePosition::Corner {
+        // one more texel than spans, with the box grown by half a texel each
+        // way so texel centers fall on its edges
+        let (x_texels, half_x) = corner_texels(width, font_size);
+        let (y_texels, half_y) = corner_texels(height, font_size);
+        pixel_width = x_texels;
+        pixel_height = y_texels;
+        left -= half_x;
+        right += half_x;
+        bottom -= half_y;
+        top += half_y;
+    }
     Ok(RasteredSize {
         pixel_width,
         pixel_height,
@@ -55,80 +240,211 @@ pub fn get_rastered_size(
 }
 
 pub struct Segments {
-    face_height: f32,
-    segments: Vec<(crate::edge::Segment, EdgeBoundingBox)>,
+    units_per_em: f32,
+    transform: [f32; 6],
+    reversed: bool,
+    tolerance: f32,
+    /// The points of the line being extended by simplification
+    run: Vec<(f32, f32)>,
+    pub segments: Vec<(crate::edge::Segment, EdgeBoundingBox)>,
     curve_start: usize,
+    start_x: f32,
+    start_y: f32,
     cursor_x: f32,
     cursor_y: f32,
 }
 
 impl Segments {
-    fn new(face_height: f32) -> Self {
+    /// Collect edges scaled by `1 / units_per_em`, so they are measured in
+    /// ems.  ttf-parser only accepts units per em between 16 and 16384, so
+    /// this never divides by zero, unlike the face's ascender minus
+    /// descender, which broken fonts may report as zero or negative.
+    pub fn new(units_per_em: f32) -> Self {
         Self {
-            face_height,
+            units_per_em,
+            transform: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            reversed: false,
+            tolerance: 0.0,
+            run: Vec::new(),
             segments: Vec::new(),
             curve_start: usize::MAX,
+            start_x: 0.0,
+            start_y: 0.0,
             cursor_x: 0.0,
             cursor_y: 0.0,
         }
     }
+
+    /// Apply an affine transform to points after they are scaled to the font
+    /// size, see [`GlyphRequest::with_transform`].
+    pub fn with_transform(self, transform: [f32; 6]) -> Self {
+        Self { transform, ..self }
+    }
+
+    /// Simplify edges as they are added, moving the outline by at most
+    /// `tolerance`, see [`FontAssetBuilder::simplify_outlines`].
+    ///
+    /// [`FontAssetBuilder::simplify_outlines`]: crate::FontAssetBuilder::simplify_outlines
+    pub fn with_tolerance(self, tolerance: f32) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    /// Whether the contours wind opposite to the `glyf` convention, where
+    /// outer contours are clockwise
+    pub fn mirrored(&self) -> bool {
+        let [a, b, c, d, _, _] = self.transform;
+        (a * d - b * c < 0.0) != self.reversed
+    }
+
+    /// Detect outlines whose contours wind the opposite way, such as those
+    /// from `CFF` and `SVG ` glyphs, from the sign of their total area.
+    fn detect_orientation(&mut self) {
+        let area: f32 = self
+            .segments
+            .iter()
+            .filter(|(segment, _)| !matches!(segment, Segment::LoopPoint(_, _)))
+            .map(|(segment, _)| segment.area())
+            .sum();
+        let [a, b, c, d, _, _] = self.transform;
+        let flipped = a * d - b * c < 0.0;
+        self.reversed = (area > 0.0) != flipped;
+    }
+
+    /// The number of lines and curves in the outline
+    pub fn edge_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|(segment, _)| !matches!(segment, Segment::LoopPoint(_, _)))
+            .count()
+    }
+
+    /// The left, right, bottom, and top of a box containing every edge
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.segments
+            .iter()
+            .filter(|(segment, _)| !matches!(segment, Segment::LoopPoint(_, _)))
+            .map(|(_, bbox)| (bbox.left, bbox.right, bbox.bottom, bbox.top))
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1), a.2.min(b.2), a.3.max(b.3)))
+    }
+
+    /// Whether every point lies within the tolerance of the line from
+    /// `start` to `end`
+    fn near_line(&self, start: (f32, f32), end: (f32, f32), points: &[(f32, f32)]) -> bool {
+        let line = Line::new(start, end);
+        points.iter().all(|&point| {
+            let (x, y) = line.point(line.nearest_t(point));
+            (x - point.0).powi(2) + (y - point.1).powi(2) <= self.tolerance.powi(2)
+        })
+    }
+
+    fn push_line(&mut self, x: f32, y: f32) {
+        let cursor = (self.cursor_x, self.cursor_y);
+        let extend = self.tolerance > 0.0
+            && self.run.len() >= 2
+            && self.near_line(self.run[0], (x, y), &self.run[1..]);
+        if extend {
+            self.segments.pop();
+        } else {
+            self.run.clear();
+            self.run.push(cursor);
+        }
+        self.run.push((x, y));
+        let segment: Segment = Line::new(self.run[0], (x, y)).into();
+        let bbox = segment.bbox();
+        self.segments.push((segment, bbox));
+        self.cursor_x = x;
+        self.cursor_y = y;
+    }
+
+    fn push_quad(&mut self, control: (f32, f32), end: (f32, f32)) {
+        let start = (self.cursor_x, self.cursor_y);
+        if self.tolerance > 0.0 && self.near_line(start, end, &[control]) {
+            // the curve lies inside the triangle of its control points
+            return self.push_line(end.0, end.1);
+        }
+        self.run.clear();
+        let segment: Segment = QuadCurve::new(start, control, end).into();
+        let bbox = segment.bbox();
+        self.segments.push((segment, bbox));
+        (self.cursor_x, self.cursor_y) = end;
+    }
+
+    fn push_cubic(&mut self, control_s: (f32, f32), control_e: (f32, f32), end: (f32, f32)) {
+        let start = (self.cursor_x, self.cursor_y);
+        if self.tolerance > 0.0 {
+            if self.near_line(start, end, &[control_s, control_e]) {
+                return self.push_line(end.0, end.1);
+            }
+            if let [(control, end)] =
+                crate::edge::cubic_to_quads(start, control_s, control_e, end, self.tolerance)[..]
+            {
+                return self.push_quad(control, end);
+            }
+        }
+        self.run.clear();
+        let segment: Segment = CubicCurve::new(start, control_s, control_e, end).into();
+        let bbox = segment.bbox();
+        self.segments.push((segment, bbox));
+        (self.cursor_x, self.cursor_y) = end;
+    }
+
+    fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.transform;
+        let x = x / self.units_per_em;
+        let y = y / self.units_per_em;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
 }
 
 impl ttf_parser::OutlineBuilder for Segments {
     fn move_to(&mut self, x: f32, y: f32) {
-        self.cursor_x = x / self.face_height;
-        self.cursor_y = y / self.face_height;
+        (self.cursor_x, self.cursor_y) = self.map(x, y);
+        self.start_x = self.cursor_x;
+        self.start_y = self.cursor_y;
         let segment = Segment::LoopPoint(0.0, 0.0);
         let bbox = EdgeBoundingBox {
-            left: x,
-            right: x,
-            bottom: y,
-            top: y,
+            left: self.cursor_x,
+            right: self.cursor_x,
+            bottom: self.cursor_y,
+            top: self.cursor_y,
         };
         self.curve_start = self.segments.len();
         self.segments.push((segment, bbox));
+        self.run.clear();
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        let x = x / self.face_height;
-        let y = y / self.face_height;
-        let segment: Segment = Line::new((self.cursor_x, self.cursor_y), (x, y)).into();
-        let bbox = segment.bbox();
-        self.segments.push((segment, bbox));
-        self.cursor_x = x;
-        self.cursor_y = y;
+        let (x, y) = self.map(x, y);
+        self.push_line(x, y);
     }
 
     fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
-        let x1 = x1 / self.face_height;
-        let y1 = y1 / self.face_height;
-        let x = x / self.face_height;
-        let y = y / self.face_height;
-        let segment: Segment =
-            QuadCurve::new((self.cursor_x, self.cursor_y), (x1, y1), (x, y)).into();
-        let bbox = segment.bbox();
-        self.segments.push((segment, bbox));
-        self.cursor_x = x;
-        self.cursor_y = y;
+        let control = self.map(x1, y1);
+        let end = self.map(x, y);
+        self.push_quad(control, end);
     }
 
     fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
-        let x1 = x1 / self.face_height;
-        let y1 = y1 / self.face_height;
-        let x2 = x2 / self.face_height;
-        let y2 = y2 / self.face_height;
-        let x = x / self.face_height;
-        let y = y / self.face_height;
-        let segment: Segment =
-            CubicCurve::new((self.cursor_x, self.cursor_y), (x1, y1), (x2, y2), (x, y)).into();
-        let bbox = segment.bbox();
-        self.segments.push((segment, bbox));
-        self.cursor_x = x;
-        self.cursor_y = y;
+        let control_s = self.map(x1, y1);
+        let control_e = self.map(x2, y2);
+        let end = self.map(x, y);
+        self.push_cubic(control_s, control_e, end);
     }
 
     fn close(&mut self) {
-        let (end_dx, end_dy) = self.segments.last().unwrap().0.direction(1.0);
+        if (self.cursor_x, self.cursor_y) != (self.start_x, self.start_y)
+            && self.curve_start < self.segments.len()
+        {
+            // CFF outlines close contours implicitly
+            self.push_line(self.start_x, self.start_y);
+        }
+        if self.curve_start.saturating_add(1) >= self.segments.len() {
+            // a contour with no edges contributes nothing
+            self.segments.truncate(self.curve_start);
+            self.curve_start = usize::MAX;
+            return;
+        }
+        let (end_dx, end_dy) = self.segments[self.segments.len() - 1].0.direction(1.0);
         let (start_dx, start_dy) = self.segments[self.curve_start + 1].0.direction(0.0);
         self.segments[self.curve_start].0 = Segment::LoopPoint(end_dx, end_dy);
         let end_segment = Segment::LoopPoint(start_dx, start_dy);
@@ -139,6 +455,7 @@ impl ttf_parser::OutlineBuilder for Segments {
             bottom: self.cursor_y,
         };
         self.segments.push((end_segment, end_bbox));
+        self.curve_start = usize::MAX;
     }
 }
 
@@ -148,45 +465,257 @@ pub struct Buffer<'a> {
 }
 
 impl<'a> Buffer<'a> {
-    fn set_pixel(&mut self, (x, y): (usize, usize), value: u8) {
+    pub fn get_pixel(&self, (x, y): (usize, usize)) -> u8 {
+        let width = usize::from(self.width);
+        self.data[y * width + x]
+    }
+
+    pub fn set_pixel(&mut self, (x, y): (usize, usize), value: u8) {
         let width = usize::from(self.width);
         self.data[y * width + x] = value;
     }
+
+    /// Set a texel of a buffer with three bytes per texel, one for each
+    /// RGB subpixel
+    pub fn set_subpixels(&mut self, (x, y): (usize, usize), value: [u8; 3]) {
+        let width = usize::from(self.width);
+        let start = (y * width + x) * 3;
+        self.data[start..start + 3].copy_from_slice(&value);
+    }
+
+    /// Zero the texels of a rect
+    pub fn clear(&mut self, rect: &crunch::Rect) {
+        let width = usize::from(self.width);
+        for y in rect.y..rect.y + rect.h {
+            self.data[y * width + rect.x..][..rect.w].fill(0);
+        }
+    }
 }
 
+pub fn glyph_segments<T>(
+    request: &GlyphRequest<'_, T>,
+    tolerance: f32,
+    variations: &Variations,
+) -> Result<Segments, crate::Error> {
+    let face = &*variations.apply(request.face);
+    let glyph_id = request
+        .glyph_id(face)
+        .ok_or(crate::Error::MissingGlyph(request.key))?;
+    let mut segments = Segments::new(f32::from(face.units_per_em())).with_tolerance(tolerance);
+    if let Some(transform) = request.transform {
+        segments = segments.with_transform(transform);
+    }
+    let outlined = face.outline_glyph(glyph_id, &mut segments).is_some();
+    if !outlined && !segments.segments.is_empty() {
+        // the outline was cut short partway through
+        return Err(crate::Error::BadOutline(glyph_id));
+    }
+    #[cfg(feature = "svg")]
+    if !outlined {
+        crate::svg::outline_svg_glyph(face, glyph_id, &mut segments);
+    }
+    let finite = segments.bounds().is_none_or(|(left, right, bottom, top)| {
+        [left, right, bottom, top]
+            .iter()
+            .all(|edge| edge.is_finite())
+    });
+    if !finite {
+        return Err(crate::Error::BadOutline(glyph_id));
+    }
+    segments.detect_orientation();
+    Ok(segments)
+}
+
+/// Render one packed glyph, and its coverage if there is a buffer for it,
+/// returning the number of edges in its outline, and whether its distances
+/// came out broken and it was rendered again more carefully
 pub fn raster<T>(
     mut buffer: Buffer<'_>,
-    padding: f32,
-    item: &crunch::PackedItem<Box<(GlyphRequest<'_, T>, RasteredSize)>>,
-) -> Result<(), crate::Error> {
-    let (
-        GlyphRequest {
-            face, codepoint, ..
-        },
+    coverage: Option<Buffer<'_>>,
+    config: RasterConfig,
+    item: &PackedGlyph<'_, T>,
+) -> Result<(usize, bool), crate::Error> {
+    let (request, rastered_size) = (&item.request, &item.size);
+    let rotate = item.rotated;
+    let segments = glyph_segments(request, config.simplify, &config.variations)?;
+    if let Some(mut coverage) = coverage {
+        raster_coverage(&mut coverage, &segments, item, config.subpixel);
+    }
+    #[cfg(feature = "bitmap")]
+    if segments.edge_count() == 0 {
+        if let Some(bitmap) = crate::bitmap::Bitmap::from_request(request) {
+            bitmap.raster(&mut buffer, config, rastered_size, &item.rect, rotate);
+            return Ok((0, false));
+        }
+    }
+    let retried = raster_or_retry(
+        &mut buffer,
+        config,
+        &segments,
         rastered_size,
-    ) = &*item.data;
-    let rotate = (item.rect.w - 1) != rastered_size.pixel_width.into();
-    let glyph_id = face
-        .glyph_index(*codepoint)
-        .ok_or(crate::Error::MissingGlyph(*codepoint))?;
-    let mut segments = Segments::new(f32::from(face.units_per_em()));
-    face.outline_glyph(glyph_id, &mut segments);
-    for dest_y in 0..(item.rect.h - 1) {
-        let y = (dest_y as f32 + 0.5) / ((item.rect.h - 1) as f32);
-        let dest_y = dest_y + item.rect.y;
-        for dest_x in 0..(item.rect.w - 1) {
-            let x = (dest_x as f32 + 0.5) / ((item.rect.w - 1) as f32);
-            let dest_x = dest_x + item.rect.x;
+        &item.rect,
+        rotate,
+    );
+    Ok((segments.edge_count(), retried))
+}
+
+/// Render the distance field of an outline, and if any distance comes out
+/// broken, render it again with a slower, more thorough search in `f64`
+/// rather than keep texels which stand in for distances that were not numbers.
+/// Returns whether it was rendered again.
+pub fn raster_or_retry(
+    buffer: &mut Buffer<'_>,
+    config: RasterConfig,
+    segments: &Segments,
+    rastered_size: &RasteredSize,
+    rect: &crunch::Rect,
+    rotate: bool,
+) -> bool {
+    if raster_segments(buffer, config, segments, rastered_size, rect, rotate) {
+        return false;
+    }
+    buffer.clear(rect);
+    let config = RasterConfig {
+        careful: true,
+        ..config
+    };
+    raster_segments(buffer, config, segments, rastered_size, rect, rotate);
+    true
+}
+
+/// Render the fraction of each texel of a packed glyph which is inside it,
+/// as the glyph would be drawn at the atlas's font size, or of each RGB
+/// subpixel if `subpixel` is set
+pub fn raster_coverage<T>(
+    buffer: &mut Buffer<'_>,
+    segments: &Segments,
+    item: &PackedGlyph<'_, T>,
+    subpixel: bool,
+) {
+    let samples = crate::edt::COVERAGE_SAMPLES;
+    let across = if subpixel {
+        3 * crate::edt::SUBPIXEL_SAMPLES
+    } else {
+        samples
+    };
+    let width = usize::from(item.size.pixel_width) * across;
+    let height = usize::from(item.size.pixel_height) * samples;
+    if width == 0 || height == 0 {
+        return;
+    }
+    let mask = match () {
+        #[cfg(feature = "bitmap")]
+        () if segments.edge_count() == 0 => {
+            match crate::bitmap::Bitmap::from_request(&item.request) {
+                Some(bitmap) => bitmap.mask(&item.size, width, height),
+                None => Mask::from_segments(segments, &item.size, width, height),
+            }
+        }
+        () => Mask::from_segments(segments, &item.size, width, height),
+    };
+    if subpixel {
+        crate::edt::raster_subpixel_coverage(buffer, &mask, &item.rect, item.rotated);
+    } else {
+        crate::edt::raster_coverage(buffer, &mask, &item.rect, item.rotated);
+    }
+}
+
+/// Render the distance field of an outline, returning whether every
+/// distance computed was a finite number.  Texels whose distance was not
+/// are given the farthest value outside the outline.
+pub fn raster_segments(
+    buffer: &mut Buffer<'_>,
+    config: RasterConfig,
+    segments: &Segments,
+    rastered_size: &RasteredSize,
+    rect: &crunch::Rect,
+    rotate: bool,
+) -> bool {
+    match config.backend {
+        RasterBackend::Exact => raster_exact(buffer, config, segments, rastered_size, rect, rotate),
+        RasterBackend::Approximate { oversample } => {
+            crate::edt::raster(
+                buffer,
+                config,
+                segments,
+                rastered_size,
+                rect,
+                rotate,
+                oversample,
+            );
+            true
+        }
+    }
+}
+
+/// The direction of a segment at `t`.  Careful renders look just inside
+/// the segment where it has no direction, such as at the end of a curve
+/// whose control point sits on the end point.
+fn tangent(segment: &Segment, t: f32, careful: bool) -> (f32, f32) {
+    let (dx, dy) = segment.direction(t);
+    if careful && dx == 0.0 && dy == 0.0 {
+        let nudge = 1.0 / 1024.0;
+        segment.direction(if t < 0.5 { t + nudge } else { t - nudge })
+    } else {
+        (dx, dy)
+    }
+}
+
+fn raster_exact(
+    buffer: &mut Buffer<'_>,
+    config: RasterConfig,
+    segments: &Segments,
+    rastered_size: &RasteredSize,
+    rect: &crunch::Rect,
+    rotate: bool,
+) -> bool {
+    let [margin_left, margin_right, margin_bottom, margin_top] = config.margin();
+    let spread = config.spread();
+    // the farthest a point can be from the outline, in euclidean terms,
+    // while still inside the range of the distance field
+    let reach = (spread + config.bold).max(0.0);
+    let orientation = if segments.mirrored() { -1.0 } else { 1.0 };
+    let careful = config.careful;
+    let nearest_t = |segment: &Segment, point| {
+        if careful {
+            segment.careful_nearest_t(point)
+        } else {
+            segment.nearest_t(point)
+        }
+    };
+    // the length of a direction, which careful renders treat as one where
+    // it would be zero, so a direction with none adds nothing
+    let length = |dx: f32, dy: f32| {
+        let length = (dx.powi(2) + dy.powi(2)).sqrt();
+        if careful && length == 0.0 {
+            1.0
+        } else {
+            length
+        }
+    };
+    let mut finite = true;
+    let search_radius = match config.norm {
+        DistanceNorm::Chebyshev => reach * std::f32::consts::SQRT_2,
+        DistanceNorm::Euclidean | DistanceNorm::Manhattan => reach,
+    };
+    for dest_y in 0..rect.h {
+        let y = (dest_y as f32 + 0.5) / (rect.h as f32);
+        let dest_y = dest_y + rect.y;
+        for dest_x in 0..rect.w {
+            let x = (dest_x as f32 + 0.5) / (rect.w as f32);
+            let dest_x = dest_x + rect.x;
             let (x, y) = if rotate { (y, x) } else { (x, y) };
             let x = rastered_size.left + (x * (rastered_size.right - rastered_size.left));
             let y = rastered_size.bottom + (y * (rastered_size.top - rastered_size.bottom));
-            let outside = (x - rastered_size.left) < padding
-                || (rastered_size.right - x) < padding
-                || (y - rastered_size.bottom) < padding
-                || (rastered_size.top - y) < padding;
+            let outside = (x - rastered_size.left) < margin_left
+                || (rastered_size.right - x) < margin_right
+                || (y - rastered_size.bottom) < margin_bottom
+                || (rastered_size.top - y) < margin_top;
             let mut nearest = None;
-            let mut nearest_dist2 = if outside {
-                padding * padding
+            // unsigned distances fade out away from the outline on both sides
+            let capped = outside || config.mode == DistanceMode::Unsigned;
+            let mut nearest_dist2 = if capped {
+                search_radius * search_radius
             } else {
                 f32::INFINITY
             };
@@ -205,10 +734,7 @@ pub fn raster<T>(
                         }
                     }
                     _ => {
-                        let bbox_near_x = x.clamp(seg_bbox.left, seg_bbox.right);
-                        let bbox_near_y = y.clamp(seg_bbox.bottom, seg_bbox.top);
-                        let bbox_dist2 = (bbox_near_x - x).powi(2) + (bbox_near_y - y).powi(2);
-                        if bbox_dist2 > nearest_dist2 {
+                        if seg_bbox.dist2((x, y)) > nearest_dist2 {
                             continue;
                         }
                         // just check the end points for curves
@@ -232,13 +758,10 @@ pub fn raster<T>(
                 if matches!(segment, Segment::LoopPoint(_, _)) {
                     continue;
                 }
-                let bbox_near_x = x.clamp(seg_bbox.left, seg_bbox.right);
-                let bbox_near_y = y.clamp(seg_bbox.bottom, seg_bbox.top);
-                let bbox_dist2 = (bbox_near_x - x).powi(2) + (bbox_near_y - y).powi(2);
-                if bbox_dist2 > nearest_dist2 {
+                if seg_bbox.dist2((x, y)) > nearest_dist2 {
                     continue;
                 }
-                let t = segment.nearest_t((x, y));
+                let t = nearest_t(segment, (x, y));
                 let (px, py) = segment.point(t);
                 let dist2 = (px - x).powi(2) + (py - y).powi(2);
                 if dist2 < nearest_dist2 {
@@ -247,34 +770,118 @@ pub fn raster<T>(
                 }
             }
             if let Some((i, t, cx, cy)) = nearest {
-                let (dx, dy) = segments.segments[i].0.direction(t);
+                let (dx, dy) = tangent(&segments.segments[i].0, t, careful);
                 let (dx, dy) = if t == 0.0 {
                     let other_seg = if i == 0 {
                         segments.segments.len() - 1
                     } else {
                         i - 1
                     };
-                    let (odx, ody) = segments.segments[other_seg].0.direction(1.0);
-                    let dlen = (dx.powi(2) + dy.powi(2)).sqrt();
-                    let odlen = (odx.powi(2) + ody.powi(2)).sqrt();
+                    let (odx, ody) = tangent(&segments.segments[other_seg].0, 1.0, careful);
+                    let (dlen, odlen) = (length(dx, dy), length(odx, ody));
                     ((dx / dlen + odx / odlen), (dy / dlen + ody / odlen))
                 } else if t == 1.0 {
                     let other_seg = (i + 1) % segments.segments.len();
-                    let (odx, ody) = segments.segments[other_seg].0.direction(0.0);
-                    let dlen = (dx.powi(2) + dy.powi(2)).sqrt();
-                    let odlen = (odx.powi(2) + ody.powi(2)).sqrt();
+                    let (odx, ody) = tangent(&segments.segments[other_seg].0, 0.0, careful);
+                    let (dlen, odlen) = (length(dx, dy), length(odx, ody));
                     ((dx / dlen + odx / odlen), (dy / dlen + ody / odlen))
                 } else {
                     (dx, dy)
                 };
-                let curve_side = (dx * (y - cy) - dy * (x - cx)).signum();
+                let curve_side = (dx * (y - cy) - dy * (x - cx)).signum() * orientation;
                 //let inside = curve_side < 0.0;
-                let dist = nearest_dist2.sqrt() / padding;
-                let signed_dist = 0.5 - curve_side * (dist * 0.5);
-                let value = (f32::from(u8::MAX) * signed_dist.clamp(0.0, 1.0)) as u8;
-                buffer.set_pixel((dest_x, dest_y), value)
+                // other norms measure the offset to the nearest point in
+                // euclidean terms, which approximates them, see
+                // `DistanceNorm`
+                let dist = curve_side * config.norm.length(x - cx, y - cy);
+                let dist = if dist.is_finite() {
+                    dist
+                } else {
+                    finite = false;
+                    reach
+                };
+                buffer.set_pixel((dest_x, dest_y), config.encode(dist))
+            }
+        }
+    }
+    finite
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::OutlineBuilder;
+
+    use super::*;
+
+    /// The distance from a point to the square from 0.25 to 0.75 on each
+    /// axis, in a norm, negative inside
+    fn square_distance(norm: DistanceNorm, x: f32, y: f32) -> f32 {
+        let (dx, dy) = ((0.25 - x).max(x - 0.75), (0.25 - y).max(y - 0.75));
+        if dx < 0.0 && dy < 0.0 {
+            dx.max(dy)
+        } else {
+            norm.length(dx.max(0.0), dy.max(0.0))
+        }
+    }
+
+    #[test]
+    fn exact_norms_of_square() {
+        let mut segments = Segments::new(1.0);
+        // clockwise, as TrueType outlines are
+        segments.move_to(0.25, 0.25);
+        segments.line_to(0.25, 0.75);
+        segments.line_to(0.75, 0.75);
+        segments.line_to(0.75, 0.25);
+        segments.close();
+        let size = RasteredSize {
+            pixel_width: 32,
+            pixel_height: 32,
+            left: 0.0,
+            right: 1.0,
+            bottom: 0.0,
+            top: 1.0,
+        };
+        let rect = crunch::Rect {
+            x: 0,
+            y: 0,
+            w: 32,
+            h: 32,
+        };
+        for norm in [
+            DistanceNorm::Euclidean,
+            DistanceNorm::Chebyshev,
+            DistanceNorm::Manhattan,
+        ] {
+            let config = RasterConfig {
+                padding: [0.25; 4],
+                backend: RasterBackend::Exact,
+                norm,
+                mode: DistanceMode::Signed,
+                bold: 0.0,
+                simplify: 0.0,
+                variations: Variations::default(),
+                subpixel: false,
+                careful: false,
+            };
+            let mut data = vec![0; 32 * 32];
+            let mut buffer = Buffer {
+                data: &mut data,
+                width: 32,
+            };
+            assert!(raster_exact(
+                &mut buffer,
+                config,
+                &segments,
+                &size,
+                &rect,
+                false
+            ));
+            for (index, &value) in data.iter().enumerate() {
+                let x = (index % 32) as f32 / 32.0 + 1.0 / 64.0;
+                let y = (index / 32) as f32 / 32.0 + 1.0 / 64.0;
+                let expected = config.encode(square_distance(norm, x, y));
+                assert!(value.abs_diff(expected) <= 1, "{norm:?} at ({x}, {y})");
             }
         }
     }
-    Ok(())
 }
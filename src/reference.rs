@@ -0,0 +1,252 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+//! A tiny font, an asset built from it with fixed settings, and the
+//! metadata expected of that asset, for checking that code which decodes
+//! assets, such as a renderer's texture coordinate and rotation handling,
+//! gets known answers.
+//!
+//! The font has three glyphs, drawn with straight lines so they don't depend
+//! on how curves are flattened:
+//!
+//! - U+E000, an `L`, whose corner shows whether the tile is flipped
+//! - U+E001, a bar which reaches left of the pen and below the baseline
+//! - U+E002, a wide flat bar, which [`build`] packs rotated
+//!
+//! Each [`ReferenceGlyph`] lists [probes](ReferenceGlyph::probes): points
+//! in the glyph's em space, relative to the pen position, which are known to
+//! be inside or outside the outline.  Mapping a probe to texture coordinates
+//! with the glyph's metadata, as a renderer would, and sampling the image
+//! there should give a distance above 0.5 for points inside and below it
+//! for points outside.  [`tex_coords`] is the mapping, for reference.
+//!
+//! Values were produced by the exact backend, using `f32` math, so compare
+//! them with a small tolerance, such as `1e-4`.
+
+use ttf_parser::Face;
+
+use crate::{
+    synthetic, Error, FontAssetBuilder, Glyph, GlyphRequest, PackerItem, PackerPlacement,
+    SdfFontAsset,
+};
+
+/// The width and height of the [reference asset](build)
+pub const SIZE: (u16, u16) = (96, 48);
+
+/// The font size of the [reference asset](build), in pixels per em
+pub const FONT_SIZE: f32 = 32.0;
+
+/// The outlines of the reference glyphs, in font units of 1/1024 em, each
+/// listed clockwise
+const OUTLINES: [&[(i16, i16)]; 3] = [
+    &[
+        (128, 0),
+        (128, 768),
+        (320, 768),
+        (320, 192),
+        (640, 192),
+        (640, 0),
+    ],
+    &[(-192, -320), (-192, 512), (192, 512), (192, -320)],
+    &[(0, 256), (0, 384), (896, 384), (896, 256)],
+];
+
+/// Generate the reference font.  Every glyph has an advance of one em.
+pub fn font() -> Vec<u8> {
+    let mut glyf = Vec::new();
+    // glyph 0 is an empty .notdef
+    let mut loca = vec![0_u32, 0];
+    for outline in OUTLINES {
+        let points: Vec<(i16, i16, bool)> = outline.iter().map(|&(x, y)| (x, y, true)).collect();
+        synthetic::write_outline(&points, &[(points.len() - 1) as u16], &mut glyf);
+        loca.push(glyf.len() as u32);
+    }
+    synthetic::assemble(OUTLINES.len() as u16, glyf, loca)
+}
+
+/// The chars of the reference glyphs, in order
+pub fn chars() -> impl Clone + Iterator<Item = char> {
+    synthetic::codepoints(OUTLINES.len() as u16)
+}
+
+/// Build the reference asset from a face parsed from [`font`], with the
+/// default settings, at [`FONT_SIZE`] in an image of [`SIZE`].  The glyphs
+/// are placed side by side from the left in the order of [`chars`], by a
+/// fixed layout rather than a packer, with the last glyph rotated.  Each
+/// glyph's `user_data` is its char.
+pub fn build(face: &Face<'_>) -> Result<SdfFontAsset<char>, Error> {
+    let (width, height) = SIZE;
+    let glyphs = chars().map(|ch| GlyphRequest::new(ch, face, ch));
+    FontAssetBuilder::with_font_size(FONT_SIZE)
+        .plan_with_layout(width, height, FONT_SIZE, glyphs, side_by_side)?
+        .raster()
+}
+
+/// Place items side by side along the bottom, rotating the last
+fn side_by_side(items: &[PackerItem]) -> Option<Vec<PackerPlacement>> {
+    let mut x = 0;
+    let placements = items
+        .iter()
+        .enumerate()
+        .map(|(item, size)| {
+            let rotated = item + 1 == items.len();
+            let placement = PackerPlacement {
+                item,
+                x,
+                y: 0,
+                rotated,
+            };
+            x += if rotated { size.height } else { size.width };
+            placement
+        })
+        .collect();
+    Some(placements)
+}
+
+/// Map a point in a glyph's em space, relative to the pen position, to
+/// texture coordinates in the glyph's tile, following the glyph's
+/// [`rotated`](Glyph::rotated) flag.  A rotated tile is transposed: the
+/// glyph's x axis runs up the image, and its y axis runs to the right.
+/// This is the mapping [`Glyph::uv_quad`] gives at the corners of the
+/// glyph's bounding box.
+pub fn tex_coords<T>(glyph: &Glyph<T>, x: f32, y: f32) -> (f32, f32) {
+    let across = (x - glyph.left) / (glyph.right - glyph.left);
+    let up = (y - glyph.bottom) / (glyph.top - glyph.bottom);
+    let (u, v) = if glyph.rotated {
+        (up, across)
+    } else {
+        (across, up)
+    };
+    (
+        glyph.tex_left + u * (glyph.tex_right - glyph.tex_left),
+        glyph.tex_bottom + v * (glyph.tex_top - glyph.tex_bottom),
+    )
+}
+
+/// A point in a reference glyph's em space, relative to the pen position
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Probe {
+    /// The distance right of the pen position, in ems
+    pub x: f32,
+
+    /// The distance above the baseline, in ems
+    pub y: f32,
+
+    /// Whether the point is inside the outline
+    pub inside: bool,
+}
+
+/// The metadata expected of a glyph in the [reference asset](build)
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct ReferenceGlyph {
+    /// The char of the glyph
+    pub ch: char,
+
+    /// The expected [`Glyph::rotated`]
+    pub rotated: bool,
+
+    /// The expected [`Glyph::left`]
+    pub left: f32,
+
+    /// The expected [`Glyph::right`]
+    pub right: f32,
+
+    /// The expected [`Glyph::bottom`]
+    pub bottom: f32,
+
+    /// The expected [`Glyph::top`]
+    pub top: f32,
+
+    /// The expected [`Glyph::advance`]
+    pub advance: f32,
+
+    /// The expected [`Glyph::tex_left`]
+    pub tex_left: f32,
+
+    /// The expected [`Glyph::tex_right`]
+    pub tex_right: f32,
+
+    /// The expected [`Glyph::tex_bottom`]
+    pub tex_bottom: f32,
+
+    /// The expected [`Glyph::tex_top`]
+    pub tex_top: f32,
+
+    /// The expected [`Glyph::tex_baseline`]
+    pub tex_baseline: f32,
+
+    /// Points known to be inside or outside the glyph
+    pub probes: &'static [Probe],
+}
+
+const fn probe(x: f32, y: f32, inside: bool) -> Probe {
+    Probe { x, y, inside }
+}
+
+/// The metadata expected of each glyph in the [reference asset](build), in
+/// the order of [`chars`]
+pub const GLYPHS: [ReferenceGlyph; 3] = [
+    ReferenceGlyph {
+        ch: '\u{e000}',
+        rotated: false,
+        left: 0.025,
+        right: 0.725,
+        bottom: -0.1,
+        top: 0.85,
+        advance: 1.0,
+        tex_left: 0.010416667,
+        tex_right: 0.23958333,
+        tex_bottom: 0.020833334,
+        tex_top: 0.6458333,
+        tex_baseline: 0.086622804,
+        probes: &[
+            probe(0.21875, 0.5, true),
+            probe(0.5, 0.09375, true),
+            // the notch of the `L`, which a flipped tile would fill
+            probe(0.5, 0.5, false),
+            probe(0.0, 0.5, false),
+        ],
+    },
+    ReferenceGlyph {
+        ch: '\u{e001}',
+        rotated: false,
+        left: -0.2875,
+        right: 0.2875,
+        bottom: -0.4125,
+        top: 0.6,
+        advance: 1.0,
+        tex_left: 0.25,
+        tex_right: 0.4375,
+        tex_bottom: 0.020833334,
+        tex_top: 0.6875,
+        tex_baseline: 0.29243824,
+        probes: &[
+            probe(0.0, -0.15625, true),
+            probe(-0.125, 0.375, true),
+            probe(0.3125, 0.0, false),
+            probe(0.0, 0.625, false),
+        ],
+    },
+    ReferenceGlyph {
+        ch: '\u{e002}',
+        rotated: true,
+        left: -0.1,
+        right: 0.975,
+        bottom: 0.15,
+        top: 0.475,
+        advance: 1.0,
+        tex_left: 0.44791666,
+        tex_right: 0.5520833,
+        tex_bottom: 0.020833334,
+        tex_top: 0.7291667,
+        tex_baseline: 0.39983973,
+        probes: &[
+            probe(0.125, 0.3125, true),
+            probe(0.8125, 0.3125, true),
+            probe(0.4375, 0.5, false),
+            probe(0.4375, 0.125, false),
+        ],
+    },
+];
@@ -0,0 +1,71 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// Where space set aside with
+/// [`FontAssetBuilder::with_reserved_rects`](crate::FontAssetBuilder::with_reserved_rects)
+/// was placed in the atlas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReservedRect {
+    /// The key given with the rect's size
+    pub key: u32,
+
+    /// The layer of the image the rect is in, see
+    /// [`FontAssetBuilder::with_layers`](crate::FontAssetBuilder::with_layers)
+    pub layer: u16,
+
+    /// The channel of the image the rect is in, see
+    /// [`FontAssetBuilder::with_channel_packing`](crate::FontAssetBuilder::with_channel_packing).
+    /// This is 0 otherwise.
+    pub channel: u8,
+
+    /// The left edge of the rect, in texels
+    pub x: u16,
+
+    /// The bottom edge of the rect, in texels, or its top edge, measured
+    /// from the top, with
+    /// [`UvOrigin::TopLeft`](crate::UvOrigin::TopLeft)
+    pub y: u16,
+
+    /// The width of the rect, in texels
+    pub width: u16,
+
+    /// The height of the rect, in texels
+    pub height: u16,
+}
+
+impl ReservedRect {
+    /// The rect with its vertical position measured the other way, from the
+    /// opposite edge of a layer `layer_height` texels high
+    pub(crate) fn flipped(self, layer_height: u16) -> Self {
+        Self {
+            y: layer_height - self.y - self.height,
+            ..self
+        }
+    }
+
+    /// The rect in an image with its pages of `layer_height` stacked from
+    /// the bottom up, `pages_per_layer` to each layer
+    pub(crate) fn as_rect(&self, layer_height: u16, pages_per_layer: u16) -> crunch::Rect {
+        let page =
+            usize::from(self.layer) * usize::from(pages_per_layer) + usize::from(self.channel);
+        crunch::Rect {
+            x: self.x.into(),
+            y: page * usize::from(layer_height) + usize::from(self.y),
+            w: self.width.into(),
+            h: self.height.into(),
+        }
+    }
+}
+
+/// Clear the reserved rects, given by [`ReservedRect::as_rect`], of an image
+/// `width` texels wide with `channels` bytes per texel, which may have been
+/// filled by passes over the whole image
+pub fn clear(data: &mut [u8], width: u16, channels: u8, reserved: &[crunch::Rect]) {
+    let (width, channels) = (usize::from(width), usize::from(channels));
+    for rect in reserved {
+        for y in rect.y..rect.y + rect.h {
+            data[(y * width + rect.x) * channels..][..rect.w * channels].fill(0);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+/// Copy rows of `row_len` bytes into rows `stride` bytes apart, padding
+/// each with zeros, see
+/// [`FontAssetBuilder::with_row_alignment`](crate::FontAssetBuilder::with_row_alignment)
+pub fn pad(data: Vec<u8>, row_len: usize, stride: usize) -> Vec<u8> {
+    if stride == row_len || row_len == 0 {
+        return data;
+    }
+    let mut out = vec![0; data.len() / row_len * stride];
+    for (row, padded) in data.chunks_exact(row_len).zip(out.chunks_exact_mut(stride)) {
+        padded[..row_len].copy_from_slice(row);
+    }
+    out
+}
+
+/// Reverse the order of the rows, of `row_len` bytes, in each layer of
+/// `height` rows
+pub fn flip(data: &mut [u8], row_len: usize, height: usize) {
+    if row_len == 0 || height == 0 {
+        return;
+    }
+    for layer in data.chunks_exact_mut(row_len * height) {
+        for y in 0..height / 2 {
+            let (below, above) = layer.split_at_mut((height - 1 - y) * row_len);
+            below[y * row_len..][..row_len].swap_with_slice(&mut above[..row_len]);
+        }
+    }
+}
+
+/// Drop the padding from rows `stride` bytes apart, undoing [`pad`]
+pub fn unpad(data: Vec<u8>, row_len: usize, stride: usize) -> Vec<u8> {
+    if stride == row_len || row_len == 0 {
+        return data;
+    }
+    data.chunks_exact(stride)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect()
+}
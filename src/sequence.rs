@@ -0,0 +1,139 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::Face;
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const REGIONAL_INDICATORS: std::ops::RangeInclusive<char> = '\u{1F1E6}'..='\u{1F1FF}';
+const SKIN_TONE_MODIFIERS: std::ops::RangeInclusive<char> = '\u{1F3FB}'..='\u{1F3FF}';
+const TAGS: std::ops::RangeInclusive<char> = '\u{E0020}'..='\u{E007F}';
+
+/// How a sequence of codepoints, such as an emoji ZWJ sequence or a flag,
+/// can be displayed using individual glyphs from a face.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SequenceFallback {
+    /// The chars to request, in display order.  Each of these can be rendered
+    /// from the face.
+    pub chars: Vec<char>,
+
+    /// Codepoints from the sequence which the face cannot render, and which
+    /// have no fallback.
+    pub missing: Vec<char>,
+
+    /// Whether the result differs visibly from the intended sequence, because
+    /// parts of it were joined, substituted, or dropped.
+    pub degraded: bool,
+}
+
+/// Whether the face has a glyph for a char with something to draw: an
+/// outline, or an embedded bitmap or SVG image if the crate was built with
+/// the feature which renders it
+fn renderable(face: &Face<'_>, ch: char) -> bool {
+    let Some(id) = face.glyph_index(ch) else {
+        return false;
+    };
+    face.glyph_bounding_box(id).is_some()
+        || (cfg!(feature = "bitmap") && face.glyph_raster_image(id, u16::MAX).is_some())
+        || (cfg!(feature = "svg") && face.glyph_svg_image(id).is_some())
+}
+
+/// Break a sequence which a browser would display as a single glyph into
+/// chars which can be passed to [`build`](crate::FontAssetBuilder::build).
+///
+/// Since glyphs are rendered individually, ligatures are never formed; the
+/// components of a ZWJ sequence are returned separately.  Variation selectors
+/// and joiners are dropped, regional indicators without a glyph fall back to
+/// the corresponding capital letter, and skin tone modifiers and tag
+/// sequences are dropped if the face cannot render them.
+pub fn sequence_fallback(face: &Face<'_>, sequence: &str) -> SequenceFallback {
+    let mut chars = Vec::new();
+    let mut missing = Vec::new();
+    let mut degraded = false;
+    for ch in sequence.chars() {
+        if ch == ZERO_WIDTH_JOINER {
+            degraded = true;
+        } else if matches!(ch, '\u{FE00}'..='\u{FE0F}') {
+            // variation selectors only choose between presentations
+        } else if renderable(face, ch) {
+            chars.push(ch);
+        } else if REGIONAL_INDICATORS.contains(&ch) {
+            degraded = true;
+            let offset = u32::from(ch) - u32::from(*REGIONAL_INDICATORS.start());
+            match char::from_u32(u32::from('A') + offset) {
+                Some(letter) if renderable(face, letter) => chars.push(letter),
+                _ => missing.push(ch),
+            }
+        } else if SKIN_TONE_MODIFIERS.contains(&ch) || TAGS.contains(&ch) {
+            degraded = true;
+        } else {
+            degraded = true;
+            missing.push(ch);
+        }
+    }
+    SequenceFallback {
+        chars,
+        missing,
+        degraded,
+    }
+}
+
+#[cfg(all(test, feature = "bitmap"))]
+mod tests {
+    use ttf_parser::Face;
+
+    use super::sequence_fallback;
+    use crate::synthetic;
+
+    /// A font whose only glyph, at U+E000, is an 8 by 8 opaque square in the
+    /// `sbix` table, with no outline
+    fn bitmap_font() -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, 8, 8);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255; 8 * 8 * 4]).unwrap();
+        writer.finish().unwrap();
+        let mut sbix = Vec::new();
+        // version, flags, one strike, at the end of the header
+        sbix.extend(1_u16.to_be_bytes());
+        sbix.extend(1_u16.to_be_bytes());
+        sbix.extend(1_u32.to_be_bytes());
+        sbix.extend(12_u32.to_be_bytes());
+        // ppem, ppi, and the offset of the data of each of the 2 glyphs and
+        // of its end, the first being .notdef with no image
+        sbix.extend(8_u16.to_be_bytes());
+        sbix.extend(72_u16.to_be_bytes());
+        let start = 4 + 3 * 4;
+        let end = start + 8 + png.len() as u32;
+        for offset in [start, start, end] {
+            sbix.extend(offset.to_be_bytes());
+        }
+        // origin, graphic type, and image
+        sbix.extend(0_i16.to_be_bytes());
+        sbix.extend(0_i16.to_be_bytes());
+        sbix.extend(b"png ");
+        sbix.extend(png);
+        let mut tables = synthetic::tables(1, Vec::new(), vec![0, 0, 0]);
+        tables.push((b"sbix", sbix));
+        synthetic::sfnt(&tables)
+    }
+
+    #[test]
+    fn bitmap_glyphs_are_renderable() {
+        let data = bitmap_font();
+        let face = Face::parse(&data, 0).unwrap();
+        let id = face.glyph_index('\u{E000}').unwrap();
+        assert!(face.glyph_bounding_box(id).is_none());
+        let fallback = sequence_fallback(&face, "\u{E000}\u{FE0F}");
+        assert_eq!(fallback.chars, ['\u{E000}']);
+        assert!(fallback.missing.is_empty());
+        assert!(!fallback.degraded);
+        // chars without a glyph are still missing
+        let fallback = sequence_fallback(&face, "\u{E000}\u{200D}\u{E001}");
+        assert_eq!(fallback.chars, ['\u{E000}']);
+        assert_eq!(fallback.missing, ['\u{E001}']);
+        assert!(fallback.degraded);
+    }
+}
@@ -0,0 +1,98 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::{
+    DistanceMode, DistanceNorm, FontAssetBuilder, RasterBackend, SamplePosition, UvOrigin,
+};
+
+/// The settings an asset was built with which affect how it should be drawn,
+/// see [`SdfFontAsset::settings`](crate::SdfFontAsset::settings).  Code
+/// which loads a saved asset can set up its shader from these, without
+/// knowing how the asset was built.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct AssetSettings {
+    /// The space left around each glyph's outline in its tile, horizontally
+    /// and vertically, as a ratio of the font size.  Where the sides were
+    /// padded differently, this is the larger of each pair.
+    pub padding: (f32, f32),
+
+    /// The space left on the left, right, bottom, and top of each glyph's
+    /// outline in its tile, as a ratio of the font size, see
+    /// [`FontAssetBuilder::with_padding_sides`]
+    pub padding_sides: [f32; 4],
+
+    /// The distance from the outline, as a ratio of the font size, at which
+    /// the distance field reaches its minimum or maximum value.  This is the
+    /// larger of the paddings.
+    pub spread: f32,
+
+    /// Whether glyphs could be rotated when packed, unless a request said
+    /// otherwise; see [`Glyph::rotated`](crate::Glyph::rotated)
+    pub allow_rotate: bool,
+
+    /// What the value of each texel measures
+    pub mode: DistanceMode,
+
+    /// How distances were measured
+    pub norm: DistanceNorm,
+
+    /// Whether the gradient of each glyph's field was normalized, see
+    /// [`FontAssetBuilder::normalize_gradients`]
+    pub normalized_gradients: bool,
+
+    /// Where in each texel the distance was sampled, which decides how the
+    /// texture coordinates of a glyph relate to its tile
+    pub sample: SamplePosition,
+
+    /// Whether texture coordinates were inset half a texel from the edges
+    /// of each tile, see [`FontAssetBuilder::inset_tex_coords`]
+    pub inset_tex_coords: bool,
+
+    /// Which corner texture coordinates are measured from, and which row of
+    /// the image comes first, see [`FontAssetBuilder::with_uv_origin`]
+    pub uv_origin: UvOrigin,
+
+    /// How the distances were computed
+    pub backend: RasterBackend,
+
+    /// How far outlines were moved outward, as a ratio of the font size,
+    /// see [`FontAssetBuilder::with_embolden`]
+    pub embolden: f32,
+
+    /// Whether each distance is followed by the coverage of the texel, see
+    /// [`FontAssetBuilder::with_coverage_channel`]
+    pub coverage: bool,
+
+    /// Whether the coverage is of each RGB subpixel, see
+    /// [`FontAssetBuilder::with_subpixel_coverage`]
+    pub subpixel_coverage: bool,
+
+    /// Whether each channel of the image holds the distances of different
+    /// glyphs, see [`FontAssetBuilder::with_channel_packing`]
+    pub channel_packing: bool,
+}
+
+impl AssetSettings {
+    pub(crate) fn new(builder: &FontAssetBuilder, font_size: f32) -> Self {
+        Self {
+            padding: builder.padding.ratio(font_size),
+            padding_sides: builder.padding.sides(font_size),
+            spread: builder.padding.spread(font_size),
+            allow_rotate: builder.allow_rotate,
+            mode: builder.mode,
+            norm: builder.norm,
+            // the pass only applies to signed fields
+            normalized_gradients: builder.normalize_gradient
+                && builder.mode == DistanceMode::Signed,
+            sample: builder.sample,
+            inset_tex_coords: builder.inset_tex || builder.sample == SamplePosition::Corner,
+            uv_origin: builder.origin,
+            backend: builder.backend,
+            embolden: builder.bold,
+            coverage: builder.coverage,
+            subpixel_coverage: builder.coverage && builder.subpixel,
+            channel_packing: builder.channel_pack,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::cmp::Reverse;
+
+use crate::packer::{Packer, PackerItem, PackerPlacement};
+
+/// The packer used by [`PackingStrategy::Shelf`](crate::PackingStrategy::Shelf).
+///
+/// Items are packed into rows, tallest first, starting a new row whenever
+/// the current one is full.  Items which may be rotated are laid on their
+/// side when that makes them shorter.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShelfPacker;
+
+impl Packer for ShelfPacker {
+    fn pack(&self, width: u32, height: u32, items: &[PackerItem]) -> Option<Vec<PackerPlacement>> {
+        let mut items: Vec<(usize, u32, u32, bool)> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                if item.allow_rotation && item.height > item.width {
+                    (index, item.height, item.width, true)
+                } else {
+                    (index, item.width, item.height, false)
+                }
+            })
+            .collect();
+        items.sort_by_key(|&(_, _, h, _)| Reverse(h));
+        let mut packed = Vec::with_capacity(items.len());
+        let (mut x, mut y) = (0, 0);
+        let mut shelf_height = 0;
+        for (item, w, h, rotated) in items {
+            if u64::from(x) + u64::from(w) > u64::from(width) {
+                x = 0;
+                y += shelf_height;
+                shelf_height = 0;
+            }
+            if u64::from(x) + u64::from(w) > u64::from(width)
+                || u64::from(y) + u64::from(h) > u64::from(height)
+            {
+                return None;
+            }
+            packed.push(PackerPlacement {
+                item,
+                x,
+                y,
+                rotated,
+            });
+            x += w;
+            shelf_height = shelf_height.max(h);
+        }
+        Some(packed)
+    }
+}
@@ -0,0 +1,145 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::cmp::Reverse;
+
+use crate::{
+    AssetPlan, AssetSize, Error, FontAssetBuilder, GlyphRequest, PackedGlyph, SdfFontAsset,
+    SkipReason, SkippedGlyph,
+};
+
+impl FontAssetBuilder {
+    /// Build the glyphs into as many assets of the size given to
+    /// [`with_texture_size`](Self::with_texture_size) as it takes to render
+    /// them at `min_font_size` or larger, rather than making them all fit
+    /// in one at a font size too small to be legible.
+    ///
+    /// If they fit in one asset at `min_font_size` or larger, this builds
+    /// it as [`build`](Self::build) would.  Otherwise every asset is
+    /// rendered at `min_font_size`, and the glyphs are split between them
+    /// greedily by area: each asset is filled with as many of the remaining
+    /// glyphs as fit, largest first, and the rest move on to the next.
+    /// Each asset lists its glyphs in the order they were requested, and
+    /// the glyphs left out for other reasons are reported by the first.
+    ///
+    /// If a glyph doesn't fit in an asset on its own, this fails with
+    /// [`Error::PackingAtlasFailed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the builder was not made with
+    /// [`with_texture_size`](Self::with_texture_size).
+    pub fn build_split<'a, T, I>(
+        self,
+        min_font_size: f32,
+        glyphs: I,
+    ) -> Result<Vec<SdfFontAsset<T>>, Error>
+    where
+        T: Clone,
+        I: 'a + Clone + Iterator<Item = GlyphRequest<'a, T>>,
+    {
+        let AssetSize::TextureSize(width, height) = self.size else {
+            panic!("build_split needs a builder made with with_texture_size");
+        };
+        assert!(min_font_size > 0.0);
+        match self.clone().build(glyphs.clone()) {
+            Ok(asset) if asset.font_size >= min_font_size => return Ok(vec![asset]),
+            Ok(_) | Err(Error::PackingAtlasFailed) => {}
+            Err(err) => return Err(err),
+        }
+        let requests: Vec<GlyphRequest<'a, T>> = glyphs.collect();
+        let area = |request: &GlyphRequest<'_, T>| {
+            // glyphs which can't be measured are reported by packing them
+            self.measure(min_font_size, request).map_or(0, |size| {
+                u32::from(size.pixel_width) * u32::from(size.pixel_height)
+            })
+        };
+        let mut remaining: Vec<usize> = (0..requests.len()).collect();
+        remaining.sort_by_key(|&index| Reverse(area(&requests[index])));
+        let fixed = Self {
+            size: AssetSize::Fixed(width, height, min_font_size),
+            ..self
+        };
+        let mut assets = Vec::new();
+        loop {
+            // pack the remaining glyphs by their index, in order of area, so
+            // those left out can be found
+            let indexed: Vec<GlyphRequest<'a, usize>> = remaining
+                .iter()
+                .map(|&index| with_user_data(requests[index].clone(), index))
+                .collect();
+            let mut plan = fixed.pack(&indexed.into_iter(), None)?;
+            remaining = plan
+                .skipped
+                .iter()
+                .filter(|glyph| glyph.reason == SkipReason::DidNotFit)
+                .map(|glyph| glyph.user_data)
+                .collect();
+            if plan.packing.is_empty() && !remaining.is_empty() {
+                return Err(Error::PackingAtlasFailed);
+            }
+            plan.skipped
+                .retain(|glyph| glyph.reason != SkipReason::DidNotFit);
+            plan.skipped.sort_by_key(|glyph| glyph.user_data);
+            plan.packing.sort_by_key(|glyph| glyph.request.user_data);
+            assets.push(with_requests(plan, &requests).raster()?);
+            if remaining.is_empty() {
+                return Ok(assets);
+            }
+        }
+    }
+}
+
+/// Give a request other user data
+fn with_user_data<'a, T, U>(request: GlyphRequest<'a, T>, user_data: U) -> GlyphRequest<'a, U> {
+    GlyphRequest {
+        user_data,
+        face: request.face,
+        key: request.key,
+        color: request.color,
+        transform: request.transform,
+        rotation: request.rotation,
+        priority: request.priority,
+        notdef: request.notdef,
+    }
+}
+
+/// Put back the user data of the requests a plan's glyphs were indexed
+/// from
+fn with_requests<'a, T: Clone>(
+    plan: AssetPlan<'a, usize>,
+    requests: &[GlyphRequest<'a, T>],
+) -> AssetPlan<'a, T> {
+    let user_data = |index: usize| requests[index].user_data.clone();
+    AssetPlan {
+        builder: plan.builder,
+        width: plan.width,
+        height: plan.height,
+        pages: plan.pages,
+        font_size: plan.font_size,
+        packing: plan
+            .packing
+            .into_iter()
+            .map(|glyph| {
+                let index = glyph.request.user_data;
+                PackedGlyph {
+                    request: with_user_data(glyph.request, user_data(index)),
+                    size: glyph.size,
+                    rect: glyph.rect,
+                    rotated: glyph.rotated,
+                }
+            })
+            .collect(),
+        reserved: plan.reserved,
+        skipped: plan
+            .skipped
+            .into_iter()
+            .map(|glyph| SkippedGlyph {
+                user_data: user_data(glyph.user_data),
+                key: glyph.key,
+                reason: glyph.reason,
+            })
+            .collect(),
+        faces: plan.faces,
+    }
+}
@@ -0,0 +1,82 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+use usvg::{tiny_skia_path::PathSegment, NodeExt, NodeKind, TreeParsing};
+
+/// Feed the filled paths of a glyph's document in the `SVG ` table to an
+/// outline builder.  Returns false if the glyph has no usable document.
+///
+/// Glyph documents use font units with y pointing down, so points are
+/// flipped to match outlines from the `glyf` and `CFF` tables.  Strokes,
+/// images, and paint are ignored; only the union of the filled shapes is
+/// kept.
+pub fn outline_svg_glyph(
+    face: &Face<'_>,
+    glyph_id: GlyphId,
+    builder: &mut impl OutlineBuilder,
+) -> bool {
+    let Some(data) = face.glyph_svg_image(glyph_id) else {
+        return false;
+    };
+    let Ok(tree) = usvg::Tree::from_data(data, &usvg::Options::default()) else {
+        return false;
+    };
+    // a document may hold several glyphs, each identified by its glyph id
+    let root = tree
+        .node_by_id(&format!("glyph{}", glyph_id.0))
+        .unwrap_or_else(|| tree.root.clone());
+    let mut any = false;
+    for node in root.descendants() {
+        let NodeKind::Path(ref path) = *node.borrow() else {
+            continue;
+        };
+        if path.fill.is_none() {
+            continue;
+        }
+        let transform = node.abs_transform();
+        let map = |mut point: usvg::tiny_skia_path::Point| {
+            transform.map_point(&mut point);
+            (point.x, -point.y)
+        };
+        let mut open = false;
+        for segment in path.data.segments() {
+            match segment {
+                PathSegment::MoveTo(p) => {
+                    if open {
+                        builder.close();
+                    }
+                    let (x, y) = map(p);
+                    builder.move_to(x, y);
+                    open = true;
+                }
+                PathSegment::LineTo(p) => {
+                    let (x, y) = map(p);
+                    builder.line_to(x, y);
+                }
+                PathSegment::QuadTo(p1, p) => {
+                    let (x1, y1) = map(p1);
+                    let (x, y) = map(p);
+                    builder.quad_to(x1, y1, x, y);
+                }
+                PathSegment::CubicTo(p1, p2, p) => {
+                    let (x1, y1) = map(p1);
+                    let (x2, y2) = map(p2);
+                    let (x, y) = map(p);
+                    builder.curve_to(x1, y1, x2, y2, x, y);
+                }
+                PathSegment::Close => {
+                    if open {
+                        builder.close();
+                    }
+                    open = false;
+                }
+            }
+        }
+        if open {
+            builder.close();
+        }
+        any = true;
+    }
+    any
+}
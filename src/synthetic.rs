@@ -0,0 +1,395 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+//! Generate fonts full of random outlines, for benchmarking and fuzzing
+//! without depending on redistributable font files.
+//!
+//! The generated data is a minimal TrueType font which can be parsed with
+//! [`Face::parse`](ttf_parser::Face::parse) and used with
+//! [`FontAssetBuilder`](crate::FontAssetBuilder) like any other font.  Since
+//! the `glyf` table only supports quadratic curves, outlines consist of lines
+//! and quadratic curves.
+
+pub(crate) const UNITS_PER_EM: i16 = 1024;
+
+/// The private use areas which glyphs are mapped from, in order, as the
+/// first and last codepoint of each.  The area in the basic plane holds the
+/// first 6400 glyphs, and the rest continue in plane 15.
+const PRIVATE_USE: [(u32, u32); 2] = [(0xE000, 0xF8FF), (0xF_0000, 0xF_FFFD)];
+
+/// Settings for generating a synthetic font
+#[derive(Clone, Copy, Debug)]
+pub struct SyntheticFont {
+    glyphs: u16,
+    contours: u16,
+    points: u16,
+    curve_ratio: f32,
+    seed: u64,
+}
+
+impl SyntheticFont {
+    /// Generate a font containing the specified number of glyphs.  By default
+    /// each glyph has 2 contours of 8 points, with half the edges curved.
+    pub fn new(glyphs: u16) -> Self {
+        assert!(glyphs < u16::MAX);
+        Self {
+            glyphs,
+            contours: 2,
+            points: 8,
+            curve_ratio: 0.5,
+            seed: 0x5EED,
+        }
+    }
+
+    /// Set the number of contours in each glyph.
+    pub fn with_contours(self, contours: u16) -> Self {
+        Self { contours, ..self }
+    }
+
+    /// Set the number of on-curve points in each contour.
+    pub fn with_points_per_contour(self, points: u16) -> Self {
+        assert!(points >= 3);
+        Self { points, ..self }
+    }
+
+    /// Set the fraction of edges, from 0 to 1, which are quadratic curves
+    /// rather than straight lines.
+    pub fn with_curve_ratio(self, curve_ratio: f32) -> Self {
+        Self {
+            curve_ratio: curve_ratio.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Set the seed of the random number generator.  The same settings and
+    /// seed always generate the same font.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Returns an iterator of the chars which are mapped to glyphs in the
+    /// generated font.  These are assigned from the private use area of the
+    /// basic plane, then from that of plane 15.
+    pub fn codepoints(&self) -> impl Clone + Iterator<Item = char> {
+        codepoints(self.glyphs)
+    }
+
+    /// Generate the font data.
+    pub fn build(&self) -> Vec<u8> {
+        let mut rng = Rng(self.seed.max(1));
+        let mut glyf = Vec::new();
+        let mut loca = vec![0_u32];
+        // glyph 0 is an empty .notdef
+        loca.push(0);
+        for _ in 0..self.glyphs {
+            self.write_glyph(&mut rng, &mut glyf);
+            loca.push(glyf.len() as u32);
+        }
+        assemble(self.glyphs, glyf, loca)
+    }
+
+    fn write_glyph(&self, rng: &mut Rng, out: &mut Vec<u8>) {
+        let mut points: Vec<(i16, i16, bool)> = Vec::new();
+        let mut end_points = Vec::new();
+        for _ in 0..self.contours {
+            let center_x = rng.range(0.25, 0.75);
+            let center_y = rng.range(0.25, 0.75);
+            let radius = rng.range(0.1, 0.25);
+            let mut angles: Vec<f32> = (0..self.points)
+                .map(|_| rng.range(0.0, std::f32::consts::TAU))
+                .collect();
+            angles.sort_by(|a, b| b.total_cmp(a));
+            let vertices: Vec<(f32, f32)> = angles
+                .iter()
+                .map(|angle| {
+                    let r = radius * rng.range(0.5, 1.0);
+                    (center_x + r * angle.cos(), center_y + r * angle.sin())
+                })
+                .collect();
+            for (i, &(x, y)) in vertices.iter().enumerate() {
+                points.push((to_units(x), to_units(y), true));
+                if rng.next_f32() < self.curve_ratio {
+                    let (nx, ny) = vertices[(i + 1) % vertices.len()];
+                    let bulge = rng.range(-0.1, 0.1);
+                    let cx = (x + nx) / 2.0 + bulge * (ny - y) / radius;
+                    let cy = (y + ny) / 2.0 - bulge * (nx - x) / radius;
+                    points.push((to_units(cx), to_units(cy), false));
+                }
+            }
+            end_points.push((points.len() - 1) as u16);
+        }
+        write_outline(&points, &end_points, out);
+    }
+}
+
+/// The chars mapped to the glyphs after `.notdef`, in order, in a font of
+/// `glyphs` glyphs from [`assemble`]
+pub(crate) fn codepoints(glyphs: u16) -> impl Clone + Iterator<Item = char> {
+    groups(glyphs)
+        .flat_map(|(first, last, _)| first..=last)
+        .filter_map(char::from_u32)
+}
+
+/// The runs of codepoints mapped to `glyphs` glyphs, as the first and last
+/// codepoint of each and the glyph id of the first
+fn groups(glyphs: u16) -> impl Clone + Iterator<Item = (u32, u32, u32)> {
+    let mut left = u32::from(glyphs);
+    // glyph 0 is .notdef
+    let mut glyph_id = 1;
+    PRIVATE_USE.into_iter().filter_map(move |(first, last)| {
+        let count = left.min(last - first + 1);
+        if count == 0 {
+            return None;
+        }
+        let group = (first, first + count - 1, glyph_id);
+        left -= count;
+        glyph_id += count;
+        Some(group)
+    })
+}
+
+/// Build a font from its `glyf` and `loca` tables, whose glyphs after
+/// `.notdef` are mapped to the [`codepoints`]
+pub(crate) fn assemble(glyphs: u16, glyf: Vec<u8>, loca: Vec<u32>) -> Vec<u8> {
+    sfnt(&tables(glyphs, glyf, loca))
+}
+
+/// The tables of the font [`assemble`] builds, in order of their tags, to
+/// which more can be added before they are put together by [`sfnt`]
+pub(crate) fn tables(
+    glyphs: u16,
+    glyf: Vec<u8>,
+    loca: Vec<u32>,
+) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+    let num_glyphs = glyphs + 1;
+    vec![
+        (b"cmap", cmap(glyphs)),
+        (b"glyf", glyf),
+        (b"head", head()),
+        (b"hhea", hhea(num_glyphs)),
+        (b"hmtx", hmtx(num_glyphs)),
+        (
+            b"loca",
+            loca.into_iter().flat_map(u32::to_be_bytes).collect(),
+        ),
+        (b"maxp", maxp(num_glyphs)),
+    ]
+}
+
+/// Write a simple glyph to the `glyf` table from its points, each with
+/// whether it is on the curve, and the index of the last point of each
+/// contour
+pub(crate) fn write_outline(points: &[(i16, i16, bool)], end_points: &[u16], out: &mut Vec<u8>) {
+    if points.is_empty() {
+        return;
+    }
+    let x_min = points.iter().map(|p| p.0).min().unwrap_or(0);
+    let x_max = points.iter().map(|p| p.0).max().unwrap_or(0);
+    let y_min = points.iter().map(|p| p.1).min().unwrap_or(0);
+    let y_max = points.iter().map(|p| p.1).max().unwrap_or(0);
+    for value in [end_points.len() as i16, x_min, y_min, x_max, y_max] {
+        out.extend(value.to_be_bytes());
+    }
+    for end in end_points {
+        out.extend(end.to_be_bytes());
+    }
+    // no instructions
+    out.extend(0_u16.to_be_bytes());
+    out.extend(points.iter().map(|p| u8::from(p.2)));
+    let mut prev = 0;
+    for &(x, _, _) in points {
+        out.extend((x - prev).to_be_bytes());
+        prev = x;
+    }
+    prev = 0;
+    for &(_, y, _) in points {
+        out.extend((y - prev).to_be_bytes());
+        prev = y;
+    }
+    while out.len() % 4 != 0 {
+        out.push(0);
+    }
+}
+
+fn to_units(value: f32) -> i16 {
+    (value * f32::from(UNITS_PER_EM)).round() as i16
+}
+
+/// A xorshift64* random number generator
+struct Rng(u64);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let value = self.0.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        ((value >> 40) as f32) / ((1_u64 << 24) as f32)
+    }
+
+    fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + self.next_f32() * (high - low)
+    }
+}
+
+/// Put tables together into a font file, with the table directory in the
+/// order they are given
+pub(crate) fn sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range = 16 << entry_selector;
+    let range_shift = num_tables * 16 - search_range;
+    let mut out = Vec::new();
+    out.extend(0x0001_0000_u32.to_be_bytes());
+    for value in [num_tables, search_range, entry_selector, range_shift] {
+        out.extend(value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in tables {
+        let checksum = data
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_be_bytes(word)
+            })
+            .fold(0_u32, u32::wrapping_add);
+        out.extend(tag.iter());
+        out.extend(checksum.to_be_bytes());
+        out.extend((offset as u32).to_be_bytes());
+        out.extend((data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in tables {
+        out.extend(data);
+        while out.len() % 4 != 0 {
+            out.push(0);
+        }
+    }
+    out
+}
+
+fn cmap(glyphs: u16) -> Vec<u8> {
+    let mut out = Vec::new();
+    // version, number of subtables
+    out.extend(0_u16.to_be_bytes());
+    out.extend(1_u16.to_be_bytes());
+    // windows platform, unicode full repertoire encoding
+    out.extend(3_u16.to_be_bytes());
+    out.extend(10_u16.to_be_bytes());
+    out.extend(12_u32.to_be_bytes());
+    // format 12 subtable with a group for each private use area, and none
+    // at all for a font with no glyphs to map
+    let groups: Vec<_> = groups(glyphs).collect();
+    out.extend(12_u16.to_be_bytes());
+    out.extend(0_u16.to_be_bytes());
+    out.extend((16 + 12 * groups.len() as u32).to_be_bytes());
+    out.extend(0_u32.to_be_bytes());
+    out.extend((groups.len() as u32).to_be_bytes());
+    for (first, last, glyph_id) in groups {
+        for value in [first, last, glyph_id] {
+            out.extend(value.to_be_bytes());
+        }
+    }
+    out
+}
+
+fn head() -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(0x0001_0000_u32.to_be_bytes());
+    out.extend(0x0001_0000_u32.to_be_bytes());
+    // checksum adjustment, magic number
+    out.extend(0_u32.to_be_bytes());
+    out.extend(0x5F0F_3CF5_u32.to_be_bytes());
+    // flags, units per em
+    out.extend(0_u16.to_be_bytes());
+    out.extend((UNITS_PER_EM as u16).to_be_bytes());
+    // created, modified
+    out.extend([0; 16]);
+    // bounding box
+    for value in [0, 0, UNITS_PER_EM, UNITS_PER_EM] {
+        out.extend(value.to_be_bytes());
+    }
+    // mac style, lowest rec ppem, font direction hint
+    out.extend(0_u16.to_be_bytes());
+    out.extend(8_u16.to_be_bytes());
+    out.extend(2_i16.to_be_bytes());
+    // long loca offsets, glyph data format
+    out.extend(1_i16.to_be_bytes());
+    out.extend(0_i16.to_be_bytes());
+    out
+}
+
+fn hhea(num_glyphs: u16) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(0x0001_0000_u32.to_be_bytes());
+    // ascender, descender, line gap
+    for value in [UNITS_PER_EM, 0, 0] {
+        out.extend(value.to_be_bytes());
+    }
+    // advance width max, min lsb, min rsb, x max extent
+    for value in [UNITS_PER_EM, 0, 0, UNITS_PER_EM] {
+        out.extend(value.to_be_bytes());
+    }
+    // caret slope rise, run, offset
+    for value in [1_i16, 0, 0] {
+        out.extend(value.to_be_bytes());
+    }
+    // reserved, metric data format
+    out.extend([0; 10]);
+    out.extend(num_glyphs.to_be_bytes());
+    out
+}
+
+fn hmtx(num_glyphs: u16) -> Vec<u8> {
+    (0..num_glyphs)
+        .flat_map(|_| {
+            let mut metric = [0; 4];
+            metric[..2].copy_from_slice(&UNITS_PER_EM.to_be_bytes());
+            metric
+        })
+        .collect()
+}
+
+fn maxp(num_glyphs: u16) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(0x0000_5000_u32.to_be_bytes());
+    out.extend(num_glyphs.to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::{Face, GlyphId};
+
+    use super::SyntheticFont;
+
+    #[test]
+    fn codepoints_continue_in_plane_15() {
+        let font = SyntheticFont::new(7000)
+            .with_contours(1)
+            .with_points_per_contour(3);
+        let data = font.build();
+        let face = Face::parse(&data, 0).unwrap();
+        let codepoints: Vec<char> = font.codepoints().collect();
+        assert_eq!(codepoints.len(), 7000);
+        assert_eq!(codepoints[6399], '\u{F8FF}');
+        assert_eq!(codepoints[6400], '\u{F0000}');
+        assert_eq!(codepoints[6999], '\u{F0257}');
+        for (index, &codepoint) in codepoints.iter().enumerate() {
+            let glyph_id = GlyphId(index as u16 + 1);
+            assert_eq!(face.glyph_index(codepoint), Some(glyph_id));
+        }
+        assert_eq!(face.glyph_index('\u{F900}'), None);
+        assert_eq!(face.glyph_index('\u{F0258}'), None);
+    }
+
+    #[test]
+    fn no_glyphs_maps_nothing() {
+        let font = SyntheticFont::new(0);
+        let data = font.build();
+        let face = Face::parse(&data, 0).unwrap();
+        assert_eq!(font.codepoints().count(), 0);
+        assert_eq!(face.glyph_index('\u{E000}'), None);
+    }
+}
@@ -0,0 +1,106 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use crate::Glyph;
+
+/// An affine map from a glyph's plane to where it was rendered in the atlas,
+/// see [`Glyph::tex_transform`].  The glyph's plane is the space of its
+/// bounding box: ems, relative to the pen position, with y up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TexTransform {
+    /// The map as `[a, b, c, d, e, f]`, taking each point to
+    /// `(a * x + c * y + e, b * x + d * y + f)`, as
+    /// [`GlyphRequest::with_transform`](crate::GlyphRequest::with_transform)
+    /// takes them
+    pub matrix: [f32; 6],
+}
+
+impl TexTransform {
+    /// The map to texture coordinates, which follows the glyph's padding,
+    /// rotation, and sample position as its texture coordinates do
+    pub(crate) fn new<T>(glyph: &Glyph<T>) -> Self {
+        // empty glyphs have a zero box, and map to the corner of their tile
+        let scale = |tex: f32, plane: f32| if plane == 0.0 { 0.0 } else { tex / plane };
+        let (tex_width, tex_height) = (
+            glyph.tex_right - glyph.tex_left,
+            glyph.tex_top - glyph.tex_bottom,
+        );
+        let (width, height) = (glyph.right - glyph.left, glyph.top - glyph.bottom);
+        let matrix = if glyph.rotated {
+            // the glyph's x axis runs up the image, and its y axis right
+            let (across, up) = (scale(tex_height, width), scale(tex_width, height));
+            [
+                0.0,
+                across,
+                up,
+                0.0,
+                glyph.tex_left - glyph.bottom * up,
+                glyph.tex_bottom - glyph.left * across,
+            ]
+        } else {
+            let (across, up) = (scale(tex_width, width), scale(tex_height, height));
+            [
+                across,
+                0.0,
+                0.0,
+                up,
+                glyph.tex_left - glyph.left * across,
+                glyph.tex_bottom - glyph.bottom * up,
+            ]
+        };
+        Self { matrix }
+    }
+
+    /// Map a point
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.matrix;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Map to texels of a layer of an atlas `width` by `height` instead,
+    /// from its bottom left corner.  Texel centers are at half texels.
+    pub fn in_texels(self, width: u16, height: u16) -> Self {
+        let (width, height) = (f32::from(width), f32::from(height));
+        let [a, b, c, d, e, f] = self.matrix;
+        Self {
+            matrix: [
+                a * width,
+                b * height,
+                c * width,
+                d * height,
+                e * width,
+                f * height,
+            ],
+        }
+    }
+
+    /// Map with the image's rows in the opposite order, such as for an
+    /// atlas saved as a PNG, which is stored top row first, when it was
+    /// built with the default [`UvOrigin`](crate::UvOrigin).  The rows of
+    /// [`SdfFontAsset::data`](crate::SdfFontAsset::data) need no flip when
+    /// uploaded to a GPU as they are.
+    ///
+    /// This flips texture coordinates, so call it before
+    /// [`in_texels`](Self::in_texels).
+    pub fn flip_v(self) -> Self {
+        let [a, b, c, d, e, f] = self.matrix;
+        Self {
+            matrix: [a, -b, c, -d, e, 1.0 - f],
+        }
+    }
+
+    /// The map back from the atlas to the glyph's plane, or `None` for
+    /// empty glyphs, whose box has no area
+    pub fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d, e, f] = self.matrix;
+        let det = a * d - b * c;
+        if det == 0.0 {
+            return None;
+        }
+        let (a, b, c, d) = (d / det, -b / det, -c / det, a / det);
+        Some(Self {
+            matrix: [a, b, c, d, -(a * e + c * f), -(b * e + d * f)],
+        })
+    }
+}
@@ -0,0 +1,116 @@
+/* SPDX-License-Identifier: (Apache-2.0 OR MIT OR Zlib) */
+/* Copyright © 2023 Violet Leonard */
+
+use std::cmp::Ordering;
+
+use crate::{Glyph, SdfFontAsset};
+
+/// The metadata of one glyph as plain data with a fixed layout, see
+/// [`SdfFontAsset::glyph_records`].  Records are 44 bytes with no padding,
+/// and hold no pointers, so a table of them can be written out as Rust
+/// source or raw bytes and kept in flash or ROM.  For the same reason every
+/// field is public and the record can be built with a struct literal.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlyphRecord {
+    /// See [`Glyph::left`]
+    pub left: f32,
+    /// See [`Glyph::right`]
+    pub right: f32,
+    /// See [`Glyph::bottom`]
+    pub bottom: f32,
+    /// See [`Glyph::top`]
+    pub top: f32,
+    /// See [`Glyph::advance`]
+    pub advance: f32,
+    /// See [`Glyph::tex_left`]
+    pub tex_left: f32,
+    /// See [`Glyph::tex_right`]
+    pub tex_right: f32,
+    /// See [`Glyph::tex_bottom`]
+    pub tex_bottom: f32,
+    /// See [`Glyph::tex_top`]
+    pub tex_top: f32,
+    /// See [`Glyph::tex_baseline`]
+    pub tex_baseline: f32,
+    /// See [`Glyph::layer`]
+    pub layer: u16,
+    /// See [`Glyph::channel`]
+    pub channel: u8,
+    /// 1 if the glyph is [`rotated`](Glyph::rotated), otherwise 0
+    pub rotated: u8,
+}
+
+impl GlyphRecord {
+    /// The record of a glyph's metadata
+    pub fn new<T>(glyph: &Glyph<T>) -> Self {
+        Self {
+            left: glyph.left,
+            right: glyph.right,
+            bottom: glyph.bottom,
+            top: glyph.top,
+            advance: glyph.advance,
+            tex_left: glyph.tex_left,
+            tex_right: glyph.tex_right,
+            tex_bottom: glyph.tex_bottom,
+            tex_top: glyph.tex_top,
+            tex_baseline: glyph.tex_baseline,
+            layer: glyph.layer,
+            channel: glyph.channel,
+            rotated: u8::from(glyph.rotated),
+        }
+    }
+}
+
+/// A borrowed view of glyph records and a codepoint table, for finding
+/// glyphs without allocating, such as from tables kept in flash on a
+/// microcontroller.  The view only reads the slices it is given, so it can
+/// be built in a `const` from `static` tables.
+#[derive(Clone, Copy, Debug)]
+pub struct MetadataView<'a> {
+    records: &'a [GlyphRecord],
+    /// Pairs of codepoint and index into `records`, sorted by codepoint
+    index: &'a [u32],
+}
+
+impl<'a> MetadataView<'a> {
+    /// A view of `records`, in the order of
+    /// [`glyph_records`](SdfFontAsset::glyph_records), found by the pairs
+    /// of `index`, as made by
+    /// [`codepoint_table`](SdfFontAsset::codepoint_table)
+    pub const fn new(records: &'a [GlyphRecord], index: &'a [u32]) -> Self {
+        Self { records, index }
+    }
+
+    /// The record for a codepoint, or `None` if the index has no entry for
+    /// it, or its entry is past the end of the records
+    pub fn get(&self, codepoint: char) -> Option<&'a GlyphRecord> {
+        let codepoint = u32::from(codepoint);
+        // binary search the pairs, which slices can't do by themselves
+        let (mut low, mut high) = (0, self.index.len() / 2);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.index[middle * 2].cmp(&codepoint) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return self.records.get(self.index[middle * 2 + 1] as usize),
+            }
+        }
+        None
+    }
+
+    /// Every record, in the order of the metadata they were made from
+    pub fn records(&self) -> &'a [GlyphRecord] {
+        self.records
+    }
+}
+
+impl<T> SdfFontAsset<T> {
+    /// The [`metadata`](Self::metadata) as plain records, in the same
+    /// order, to store alongside the
+    /// [`codepoint_table`](Self::codepoint_table) and read back through a
+    /// [`MetadataView`]
+    pub fn glyph_records(&self) -> Vec<GlyphRecord> {
+        self.metadata.iter().map(GlyphRecord::new).collect()
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use blurry::FontAssetBuilder;
+
+use common::requests;
+
+#[test]
+fn aliases_share_glyphs() {
+    let face = common::roboto();
+    let aliases = [
+        ('\u{a0}', ' '),
+        ('x', 'a'),
+        ('\u{2010}', '-'),
+        ('\u{ff41}', 'a'),
+    ];
+    let asset = FontAssetBuilder::with_font_size(16.0)
+        .with_aliases(&aliases)
+        .build(requests(&face, " abx"))
+        .unwrap();
+    assert_eq!(asset.aliases(), aliases);
+    // aliases render no tile of their own
+    assert_eq!(asset.metadata.len(), 4);
+
+    let index = asset.codepoint_index();
+    let key = |c| index.get(c).map(|glyph| glyph.user_data);
+    assert_eq!(key('\u{a0}'), Some(' '));
+    assert_eq!(key('\u{ff41}'), Some('a'));
+    // a codepoint with a glyph of its own ignores its alias
+    assert_eq!(key('x'), Some('x'));
+    // an alias of a codepoint without a glyph finds nothing
+    assert_eq!(key('\u{2010}'), None);
+
+    // the range gives the glyph each alias shares, in order of the alias
+    let found: String = index.range('a'..).map(|glyph| glyph.user_data).collect();
+    assert_eq!(found, "abx a");
+
+    // the table pairs aliases with the index of the glyph they share
+    let table = asset.codepoint_table();
+    let pairs: Vec<(u32, u32)> = table.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+    let position = |c| {
+        asset
+            .metadata
+            .iter()
+            .position(|glyph| glyph.user_data == c)
+            .unwrap() as u32
+    };
+    assert_eq!(
+        pairs,
+        [
+            (u32::from(' '), position(' ')),
+            (u32::from('a'), position('a')),
+            (u32::from('b'), position('b')),
+            (u32::from('x'), position('x')),
+            (0xa0, position(' ')),
+            (0xff41, position('a')),
+        ]
+    );
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use blurry::{FontAssetBuilder, PackerPlacement, SdfFontAsset};
+use common::{requests, tile};
+
+/// Build `first` then append `second`, and check every glyph matches the
+/// same glyph built from scratch along with the others
+fn append_matches_scratch(
+    builder: FontAssetBuilder,
+    first: &str,
+    second: &str,
+) -> SdfFontAsset<char> {
+    let face = common::roboto();
+    let mut asset = builder.clone().build(requests(&face, first)).unwrap();
+    let before: Vec<Vec<u8>> = asset
+        .metadata
+        .iter()
+        .map(|glyph| tile(&asset, glyph))
+        .collect();
+    let added = asset.append(requests(&face, second)).unwrap();
+    let keys: String = added.iter().map(|glyph| glyph.user_data).collect();
+    assert_eq!(keys, second);
+
+    let all = format!("{first}{second}");
+    let scratch = builder.build(requests(&face, &all)).unwrap();
+    assert_eq!(asset.metadata.len(), scratch.metadata.len());
+    for (index, glyph) in asset.metadata.iter().enumerate() {
+        let expected = scratch
+            .metadata
+            .iter()
+            .find(|other| other.user_data == glyph.user_data)
+            .unwrap();
+        assert_eq!(
+            (
+                glyph.width,
+                glyph.height,
+                glyph.left,
+                glyph.top,
+                glyph.advance
+            ),
+            (
+                expected.width,
+                expected.height,
+                expected.left,
+                expected.top,
+                expected.advance
+            ),
+        );
+        let texels = tile(&asset, glyph);
+        assert_eq!(texels, tile(&scratch, expected), "{:?}", glyph.user_data);
+        // the glyphs already there are left as they were
+        if let Some(before) = before.get(index) {
+            assert_eq!(&texels, before);
+        }
+    }
+    asset
+}
+
+#[test]
+fn appends_into_free_space() {
+    append_matches_scratch(
+        FontAssetBuilder::with_fixed_size(128, 128, 24.0),
+        "abc",
+        "xyz",
+    );
+}
+
+#[test]
+fn appends_to_other_channels() {
+    let builder = FontAssetBuilder::with_fixed_size(40, 40, 24.0).with_channel_packing();
+    let asset = append_matches_scratch(builder, "ab", "wxyz");
+    assert!(asset.metadata[2..].iter().any(|glyph| glyph.channel > 0));
+}
+
+#[test]
+fn appends_to_other_layers() {
+    let builder = FontAssetBuilder::with_fixed_size(40, 40, 24.0).with_layers(4);
+    let asset = append_matches_scratch(builder, "ab", "wxyz");
+    assert!(asset.metadata[2..].iter().any(|glyph| glyph.layer > 0));
+}
+
+#[test]
+fn appends_to_other_layers_and_channels() {
+    let builder = FontAssetBuilder::with_fixed_size(32, 32, 24.0)
+        .with_layers(3)
+        .with_channel_packing();
+    let asset = append_matches_scratch(builder, "ab", "defghijk");
+    assert!(asset.metadata[2..]
+        .iter()
+        .any(|glyph| glyph.layer > 0 && glyph.channel > 0));
+}
+
+#[test]
+fn finishes_the_whole_image_again() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_fixed_size(64, 64, 16.0)
+        .with_edge_bleed()
+        .with_mipmaps();
+    let mut asset = builder.clone().build(requests(&face, "ab")).unwrap();
+    asset.append(requests(&face, "yz")).unwrap();
+
+    // lay the glyphs out where the appended asset has them, so the images
+    // match texel for texel, bleed and mip levels included
+    let gutter = u32::from(asset.gutter);
+    let places: Vec<PackerPlacement> = asset
+        .metadata
+        .iter()
+        .enumerate()
+        .map(|(item, glyph)| PackerPlacement {
+            item,
+            x: u32::from(glyph.x) - gutter,
+            y: u32::from(glyph.y) - gutter,
+            rotated: false,
+        })
+        .collect();
+    let scratch = builder
+        .plan_with_layout(64, 64, 16.0, requests(&face, "abyz"), |_| Some(places))
+        .unwrap()
+        .raster()
+        .unwrap();
+    assert_eq!(asset.metadata, scratch.metadata);
+    assert_eq!(asset.data, scratch.data);
+    assert_eq!(asset.mip_levels.len(), scratch.mip_levels.len());
+    for (level, expected) in asset.mip_levels.iter().zip(&scratch.mip_levels) {
+        assert_eq!(level.data, expected.data);
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use blurry::{BandMask, FontAssetBuilder};
+
+use common::{requests, tile};
+
+/// Whether every texel of `inner` is in `outer`
+fn within(inner: &BandMask, outer: &BandMask) -> bool {
+    (0..inner.height)
+        .flat_map(|y| (0..inner.width).map(move |x| (x, y)))
+        .all(|(x, y)| !inner.contains(x, y) || outer.contains(x, y))
+}
+
+#[test]
+fn band_follows_the_outline() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(32.0)
+        .build(requests(&face, "O"))
+        .unwrap();
+    let glyph = &asset.metadata[0];
+    let texels = tile(&asset, glyph);
+    let narrow = asset.band_mask(glyph, 1.0);
+    let wide = asset.band_mask(glyph, 2.0);
+    let all = asset.band_mask(glyph, asset.spread_px);
+    let area = usize::from(glyph.width) * usize::from(glyph.height);
+    assert_eq!((narrow.width, narrow.height), (glyph.width, glyph.height));
+    assert!(0 < narrow.count() && narrow.count() < wide.count() && wide.count() < area);
+    assert!(within(&narrow, &wide));
+    // every texel is within the spread of the outline
+    assert_eq!(all.count(), area);
+
+    for (index, &value) in texels.iter().enumerate() {
+        let (x, y) = (
+            index % usize::from(glyph.width),
+            index / usize::from(glyph.width),
+        );
+        let in_band = narrow.contains(x as u16, y as u16);
+        // texels on the outline are in the band, and those at the farthest
+        // values, such as the tile's corners and the middle of the bowl, not
+        if value.abs_diff(128) <= 2 {
+            assert!(in_band, "{x}, {y}");
+        }
+        if value == 0 || value == u8::MAX {
+            assert!(!in_band, "{x}, {y}");
+        }
+    }
+    assert!(!narrow.contains(glyph.width, 0));
+}
+
+#[test]
+fn rects_cover_the_band() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(32.0)
+        .build(requests(&face, "Og"))
+        .unwrap();
+    for glyph in &asset.metadata {
+        let band = asset.band_mask(glyph, 1.5);
+        let mut covered = vec![0; usize::from(band.width) * usize::from(band.height)];
+        for (x, y, width, height) in band.rects() {
+            for y in y..y + height {
+                for x in x..x + width {
+                    assert!(band.contains(x, y));
+                    covered[usize::from(y) * usize::from(band.width) + usize::from(x)] += 1;
+                }
+            }
+        }
+        // each texel of the band is in exactly one rect
+        assert_eq!(
+            covered.iter().filter(|&&count| count == 1).count(),
+            band.count()
+        );
+        assert!(covered.iter().all(|&count| count <= 1));
+        assert_eq!(band.bits().len(), covered.len().div_ceil(8));
+    }
+}
+
+#[test]
+fn channel_packed_bands_match() {
+    let face = common::roboto();
+    let chars = "abcdefgh";
+    let plain = FontAssetBuilder::with_fixed_size(64, 64, 16.0)
+        .build(requests(&face, chars))
+        .unwrap();
+    let packed = FontAssetBuilder::with_fixed_size(32, 32, 16.0)
+        .with_channel_packing()
+        .build(requests(&face, chars))
+        .unwrap();
+    assert!(packed.metadata.iter().any(|glyph| glyph.channel > 0));
+    for (glyph, expected) in packed.metadata.iter().zip(&plain.metadata) {
+        assert_eq!(packed.band_mask(glyph, 1.0), plain.band_mask(expected, 1.0));
+    }
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest};
+
+#[test]
+fn negative_bearings() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(32.0)
+        .build("jgpy".chars().map(|c| GlyphRequest::new(c, &face, c)))
+        .unwrap();
+    let units = f32::from(face.units_per_em());
+    for glyph in &asset.metadata {
+        let c = glyph.codepoint().unwrap();
+        let outline = face.glyph_bounding_box(glyph.glyph_id).unwrap();
+        // every one of these descends below the baseline
+        assert!(outline.y_min < 0, "{c}");
+        assert!(glyph.bottom < 0.0, "{c}");
+        assert!(glyph.top > 0.0, "{c}");
+        // the quad, padding and all, holds the whole outline
+        assert!(glyph.left <= f32::from(outline.x_min) / units, "{c}");
+        assert!(glyph.right >= f32::from(outline.x_max) / units, "{c}");
+        assert!(glyph.bottom <= f32::from(outline.y_min) / units, "{c}");
+        assert!(glyph.top >= f32::from(outline.y_max) / units, "{c}");
+    }
+    // the tail of the j reaches left of the pen position
+    let j = &asset.metadata[0];
+    assert!(face.glyph_bounding_box(j.glyph_id).unwrap().x_min < 0);
+    assert!(j.left < 0.0);
+}
@@ -0,0 +1,32 @@
+use blurry::capabilities;
+
+/// The names of the optional features in the manifest
+fn manifest_features() -> Vec<&'static str> {
+    include_str!("../Cargo.toml")
+        .lines()
+        .skip_while(|line| *line != "[features]")
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split('=').next().unwrap().trim())
+        .collect()
+}
+
+#[test]
+fn reports_every_feature() {
+    // the debug output lists each field with its value
+    let reported = format!("{:?}", capabilities());
+    for feature in manifest_features() {
+        let enabled = match feature {
+            "bitmap" => cfg!(feature = "bitmap"),
+            "effects" => cfg!(feature = "effects"),
+            "fuzzing" => cfg!(feature = "fuzzing"),
+            "gpu" => cfg!(feature = "gpu"),
+            "reference" => cfg!(feature = "reference"),
+            "svg" => cfg!(feature = "svg"),
+            _ => panic!("feature {feature} is not checked"),
+        };
+        let field = format!("{feature}: {enabled}");
+        assert!(reported.contains(&field), "{reported} lacks {field}");
+    }
+}
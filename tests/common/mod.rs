@@ -0,0 +1,43 @@
+//! Fixtures shared by the integration tests.  Each test file uses some of
+//! them, so the rest would be reported as dead code in that file.
+#![allow(dead_code)]
+
+use blurry::{Glyph, GlyphRequest, SdfFontAsset};
+use ttf_parser::Face;
+
+/// The font the tests build from
+pub const ROBOTO: &[u8] = include_bytes!("../../examples/roboto/Roboto-Regular.ttf");
+
+/// Parse [`ROBOTO`]
+pub fn roboto() -> Face<'static> {
+    Face::parse(ROBOTO, 0).unwrap()
+}
+
+/// A request for each of `chars`, with the char as its user data
+pub fn requests<'a>(
+    face: &'a Face<'a>,
+    chars: &'a str,
+) -> impl 'a + Clone + Iterator<Item = GlyphRequest<'a, char>> {
+    chars.chars().map(move |c| GlyphRequest::new(c, face, c))
+}
+
+/// The bytes of a glyph's tile, row by row in the order of the data.  The
+/// only four channel images the tests build are channel packed, so for
+/// those this is the glyph's own channel of each texel.
+pub fn tile<T>(asset: &SdfFontAsset<T>, glyph: &Glyph<T>) -> Vec<u8> {
+    let channels = usize::from(asset.channels);
+    let (bytes, offset) = if asset.channels == 4 {
+        (1, usize::from(glyph.channel))
+    } else {
+        (channels, 0)
+    };
+    let first_row = usize::from(glyph.layer) * usize::from(asset.height) + usize::from(glyph.y);
+    let mut texels = Vec::new();
+    for row in first_row..first_row + usize::from(glyph.height) {
+        for x in glyph.x..glyph.x + glyph.width {
+            let start = row * asset.row_stride + usize::from(x) * channels + offset;
+            texels.extend_from_slice(&asset.data[start..start + bytes]);
+        }
+    }
+    texels
+}
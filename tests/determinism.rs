@@ -0,0 +1,27 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest, SdfFontAsset};
+use ttf_parser::Face;
+
+fn build(face: &Face<'_>, threads: usize) -> SdfFontAsset<char> {
+    FontAssetBuilder::with_texture_size(256, 256)
+        .allow_rotating_glyphs()
+        .with_kerning()
+        .with_threads(threads)
+        .build((' '..='~').map(|c| GlyphRequest::new(c, face, c)))
+        .unwrap()
+}
+
+#[test]
+fn builds_are_identical() {
+    let face = common::roboto();
+    let first = build(&face, 1);
+    assert!(!first.kerning.is_empty());
+    for threads in [1, 1, 4, 4] {
+        let asset = build(&face, threads);
+        assert_eq!(asset.font_size, first.font_size);
+        assert_eq!(asset.data, first.data);
+        assert_eq!(asset.metadata, first.metadata);
+        assert_eq!(asset.kerning, first.kerning);
+    }
+}
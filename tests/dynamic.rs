@@ -0,0 +1,70 @@
+mod common;
+
+use blurry::{DynamicSdfAtlas, Error, FontAssetBuilder, GlyphKey, GlyphRequest};
+use ttf_parser::Face;
+
+/// A request for `O` in a color of its own, so every request renders the
+/// same tile but is a different glyph of the atlas
+fn colored_o<'a>(face: &'a Face<'a>, shade: u8) -> GlyphRequest<'a, u8> {
+    GlyphRequest::new(shade, face, 'O').with_color([shade, 0, 0, 255])
+}
+
+#[test]
+fn evicts_least_recently_used() {
+    let face = common::roboto();
+    let mut atlas =
+        DynamicSdfAtlas::new(FontAssetBuilder::with_font_size(16.0), 64, 64, 16.0).unwrap();
+    // fill the atlas, until asking for another glyph evicts one
+    let mut tiles = Vec::new();
+    loop {
+        let shade = tiles.len() as u8;
+        let glyph = *atlas.glyph(colored_o(&face, shade)).unwrap();
+        if atlas.asset().metadata.len() <= tiles.len() {
+            break;
+        }
+        tiles.push((glyph.x, glyph.y));
+    }
+    let full = tiles.len();
+    assert!(full >= 2, "only {full} glyphs fit");
+    // the first glyph asked for was the one evicted, and the new glyph
+    // took its tile
+    let newest = full as u8;
+    let metadata = &atlas.asset().metadata;
+    assert!(metadata.iter().all(|glyph| glyph.user_data != 0));
+    let glyph = metadata
+        .iter()
+        .find(|glyph| glyph.user_data == newest)
+        .unwrap();
+    assert_eq!((glyph.x, glyph.y), tiles[0]);
+
+    // use the glyph which is now least recent, so the next is evicted
+    let glyph = *atlas.glyph(colored_o(&face, 1)).unwrap();
+    assert_eq!((glyph.x, glyph.y), tiles[1]);
+    let glyph = *atlas.glyph(colored_o(&face, newest + 1)).unwrap();
+    assert_eq!((glyph.x, glyph.y), tiles[2 % full]);
+    assert_eq!(atlas.asset().metadata.len(), full);
+
+    // every glyph still in the atlas is found where it was rendered
+    for shade in [1, newest, newest + 1] {
+        let before = atlas.asset().metadata.len();
+        let glyph = *atlas.glyph(colored_o(&face, shade)).unwrap();
+        assert_eq!(glyph.user_data, shade);
+        assert_eq!(atlas.asset().metadata.len(), before);
+    }
+}
+
+#[test]
+fn filtered_glyph_is_not_missing() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_font_size(16.0).with_filter(|_, c, _| c != Some('x'));
+    let mut atlas = DynamicSdfAtlas::new(builder, 64, 64, 16.0).unwrap();
+    let result = atlas.glyph(GlyphRequest::new((), &face, 'x'));
+    assert!(matches!(
+        result,
+        Err(Error::FilteredGlyph(GlyphKey::Codepoint('x')))
+    ));
+    let result = atlas.glyph(GlyphRequest::new((), &face, '\u{E000}'));
+    assert!(matches!(result, Err(Error::MissingGlyph(_))));
+    assert!(atlas.asset().skipped.is_empty());
+    assert!(atlas.glyph(GlyphRequest::new((), &face, 'y')).is_ok());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use blurry::FontAssetBuilder;
+
+use common::{requests, tile};
+
+#[test]
+fn bleeds_the_nearest_texel_outward() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_fixed_size(64, 64, 24.0);
+    let plain = builder.clone().build(requests(&face, "W")).unwrap();
+    let bled = builder
+        .with_edge_bleed()
+        .build(requests(&face, "W"))
+        .unwrap();
+    let glyph = &bled.metadata[0];
+    assert_eq!(glyph, &plain.metadata[0]);
+    assert_eq!(tile(&bled, glyph), tile(&plain, &plain.metadata[0]));
+    assert_ne!(bled.data, plain.data);
+
+    // each texel outside the tile copies one of the tile's texels fewest
+    // steps away, counting diagonal steps as one
+    let texel = |x: u16, y: u16| bled.data[usize::from(y) * bled.row_stride + usize::from(x)];
+    let tile_texels: Vec<(u16, u16)> = (glyph.y..glyph.y + glyph.height)
+        .flat_map(|y| (glyph.x..glyph.x + glyph.width).map(move |x| (x, y)))
+        .collect();
+    let steps = |(x, y): (u16, u16), (tx, ty): (u16, u16)| x.abs_diff(tx).max(y.abs_diff(ty));
+    for y in 0..bled.height {
+        for x in 0..bled.width {
+            let nearest = tile_texels
+                .iter()
+                .map(|&tile| steps((x, y), tile))
+                .min()
+                .unwrap();
+            let found = tile_texels
+                .iter()
+                .filter(|&&tile| steps((x, y), tile) == nearest)
+                .any(|&(tx, ty)| texel(tx, ty) == texel(x, y));
+            assert!(found, "{x}, {y}");
+        }
+    }
+}
+
+#[test]
+fn leaves_the_background_empty_without_bleed() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(64, 64, 24.0)
+        .build(requests(&face, "W"))
+        .unwrap();
+    let glyph = &asset.metadata[0];
+    let inside = |x: usize, y: usize| {
+        (usize::from(glyph.x)..usize::from(glyph.x + glyph.width)).contains(&x)
+            && (usize::from(glyph.y)..usize::from(glyph.y + glyph.height)).contains(&y)
+    };
+    for (index, &value) in asset.data.iter().enumerate() {
+        let (x, y) = (index % asset.row_stride, index / asset.row_stride);
+        if !inside(x, y) {
+            assert_eq!(value, 0, "{x}, {y}");
+        }
+    }
+}
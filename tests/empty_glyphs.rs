@@ -0,0 +1,44 @@
+mod common;
+
+use blurry::{FontAssetBuilder, Glyph, GlyphRequest};
+use ttf_parser::Face;
+
+fn assert_space(face: &Face<'_>, glyph: &Glyph<char>) {
+    assert!(glyph.is_empty());
+    assert_eq!([glyph.left, glyph.right, glyph.bottom, glyph.top], [0.0; 4]);
+    assert_eq!(glyph.tex_left, glyph.tex_right);
+    assert_eq!(glyph.tex_bottom, glyph.tex_top);
+    assert_eq!((glyph.width, glyph.height), (0, 0));
+    let id = face.glyph_index(' ').unwrap();
+    let advance = f32::from(face.glyph_hor_advance(id).unwrap());
+    assert!(glyph.advance > 0.0);
+    assert_eq!(glyph.advance, advance / f32::from(face.units_per_em()));
+}
+
+#[test]
+fn space_among_other_glyphs() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_texture_size(128, 128)
+        .build("a b".chars().map(|c| GlyphRequest::new(c, &face, c)))
+        .unwrap();
+    assert_eq!(asset.metadata.len(), 3);
+    assert_space(&face, &asset.metadata[1]);
+    assert!(!asset.metadata[0].is_empty());
+    assert!(!asset.metadata[2].is_empty());
+}
+
+#[test]
+fn only_spaces() {
+    let face = common::roboto();
+    for builder in [
+        FontAssetBuilder::with_texture_size(64, 64),
+        FontAssetBuilder::with_font_size(24.0),
+    ] {
+        let asset = builder
+            .build(std::iter::once(GlyphRequest::new(' ', &face, ' ')))
+            .unwrap();
+        assert_eq!(asset.metadata.len(), 1);
+        assert_space(&face, &asset.metadata[0]);
+        assert!(asset.font_size > 0.0);
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use blurry::{FontAssetBuilder, FontWarning, GlyphKey, GlyphRequest, FAR_PAST_EM};
+use ttf_parser::Face;
+
+/// Roboto with a table's big-endian `u16` at `offset` replaced
+fn patched(tag: &[u8; 4], offset: usize, value: u16) -> Vec<u8> {
+    let mut font = common::ROBOTO.to_vec();
+    let tables = usize::from(u16::from_be_bytes([font[4], font[5]]));
+    let record = (0..tables)
+        .map(|index| 12 + index * 16)
+        .find(|&record| &font[record..record + 4] == tag)
+        .unwrap();
+    let start = u32::from_be_bytes(font[record + 8..record + 12].try_into().unwrap());
+    let at = start as usize + offset;
+    font[at..at + 2].copy_from_slice(&value.to_be_bytes());
+    font
+}
+
+fn warnings(font: &[u8]) -> Vec<FontWarning> {
+    let face = Face::parse(font, 0).unwrap();
+    // small, as glyphs of broken fonts are many ems across
+    let (_, stats) = FontAssetBuilder::with_font_size(2.0)
+        .plan("Ab".chars().map(|c| GlyphRequest::new(c, &face, c)))
+        .unwrap()
+        .raster_with_stats(0)
+        .unwrap();
+    stats.warnings
+}
+
+#[test]
+fn sound_font_has_no_warnings() {
+    assert!(warnings(common::ROBOTO).is_empty());
+}
+
+#[test]
+fn units_per_em_too_small() {
+    // the smallest units per em ttf-parser accepts, for outlines drawn in
+    // thousands of units
+    let warnings = warnings(&patched(b"head", 18, 16));
+    assert_eq!(warnings.len(), 2);
+    for (warning, c) in warnings.iter().zip("Ab".chars()) {
+        match *warning {
+            FontWarning::FarPastEm { key, reach, .. } => {
+                assert_eq!(key, GlyphKey::Codepoint(c));
+                assert!(reach > FAR_PAST_EM);
+            }
+            _ => panic!("{warning:?}"),
+        }
+    }
+}
+
+#[test]
+fn no_line_height() {
+    // a descender as far above the baseline as the ascender
+    let ascender = common::roboto().ascender() as u16;
+    let warnings = warnings(&patched(b"hhea", 6, ascender));
+    assert!(matches!(warnings[..], [FontWarning::NoLineHeight(face)] if face.index() == 0));
+}
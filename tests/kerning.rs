@@ -0,0 +1,26 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest};
+
+#[test]
+fn kerns_known_pairs() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(16.0)
+        .with_kerning()
+        .build("AVTo".chars().map(|c| GlyphRequest::new(c, &face, c)))
+        .unwrap();
+    let index = |c| {
+        asset
+            .metadata
+            .iter()
+            .position(|glyph| glyph.user_data == c)
+            .unwrap()
+    };
+    let (a, v, t, o) = (index('A'), index('V'), index('T'), index('o'));
+    assert!(asset.pair_kerning(a, v) < 0.0);
+    assert!(asset.pair_kerning(v, a) < 0.0);
+    assert!(asset.pair_kerning(t, o) < 0.0);
+    assert_eq!(asset.pair_kerning(o, t), 0.0);
+    // the adjustment is in ems, so a fraction of the advance
+    assert!(asset.pair_kerning(a, v) > -0.25);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest};
+
+#[test]
+fn build_layers_matches_build() {
+    let face = common::roboto();
+    let requests = || ('A'..='Z').map(|c| GlyphRequest::new(c, &face, c));
+    let builder = FontAssetBuilder::with_fixed_size(64, 64, 24.0)
+        .with_layers(8)
+        .with_mipmaps();
+    let whole = builder.clone().build(requests()).unwrap();
+    assert!(whole.layers > 1);
+
+    let mut data = Vec::new();
+    let mut mips = vec![Vec::new(); whole.mip_levels.len()];
+    let mut layers = Vec::new();
+    let streamed = builder
+        .build_layers(requests(), |layer| {
+            layers.push(layer.layer);
+            data.extend(layer.data);
+            for (mip, level) in mips.iter_mut().zip(layer.mip_levels) {
+                mip.extend(level.data);
+            }
+        })
+        .unwrap();
+    assert_eq!(layers, (0..whole.layers).collect::<Vec<_>>());
+    assert_eq!(data, whole.data);
+    for (mip, level) in mips.iter().zip(&whole.mip_levels) {
+        assert_eq!(*mip, level.data);
+    }
+    assert!(streamed.data.is_empty());
+    assert_eq!(streamed.metadata.len(), whole.metadata.len());
+    for (streamed, whole) in streamed.metadata.iter().zip(&whole.metadata) {
+        assert_eq!(streamed.user_data, whole.user_data);
+        assert_eq!(
+            (streamed.layer, streamed.x, streamed.y),
+            (whole.layer, whole.x, whole.y)
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn too_many_layers() {
+    let _ = FontAssetBuilder::with_fixed_size(64, 64, 24.0).with_layers(u16::MAX / 4 + 1);
+}
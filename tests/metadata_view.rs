@@ -0,0 +1,54 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRecord, GlyphRequest, MetadataView};
+
+use common::requests;
+
+/// A view of no glyphs, built in a `const` as tables in flash would be
+const EMPTY: MetadataView<'static> = MetadataView::new(&[], &[]);
+
+#[test]
+fn records_have_a_fixed_size() {
+    assert_eq!(std::mem::size_of::<GlyphRecord>(), 44);
+}
+
+#[test]
+fn view_finds_records_by_codepoint() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(16.0)
+        .with_aliases(&[('\u{a0}', ' ')])
+        .build(requests(&face, "zay ").chain([GlyphRequest::from_glyph_id(
+            '#',
+            &face,
+            face.glyph_index('#').unwrap(),
+        )]))
+        .unwrap();
+    let records = asset.glyph_records();
+    let table = asset.codepoint_table();
+    let view = MetadataView::new(&records, &table);
+    assert_eq!(view.records().len(), asset.metadata.len());
+    for (glyph, record) in asset.metadata.iter().zip(view.records()) {
+        assert_eq!(*record, GlyphRecord::new(glyph));
+        if let Some(c) = glyph.codepoint() {
+            assert_eq!(view.get(c), Some(record));
+        }
+    }
+    assert_eq!(view.get('\u{a0}'), view.get(' '));
+    // glyphs requested by id have no codepoint to be found by
+    assert_eq!(view.get('#'), None);
+    assert_eq!(view.get('b'), None);
+
+    // entries past the end of the records find nothing
+    let first = asset
+        .metadata
+        .iter()
+        .position(|glyph| glyph.user_data == 'z')
+        .unwrap();
+    let view = MetadataView::new(&records[..=first], &table);
+    assert!(view.get('z').is_some());
+    assert!(asset.metadata[first + 1..]
+        .iter()
+        .filter_map(|glyph| glyph.codepoint())
+        .all(|c| view.get(c).is_none()));
+    assert_eq!(EMPTY.get('a'), None);
+}
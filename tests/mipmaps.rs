@@ -0,0 +1,64 @@
+mod common;
+
+use blurry::{FontAssetBuilder, MipLevel};
+
+use common::requests;
+
+/// Each texel of a level is the rounded average of the block of four it
+/// covers in the level above
+fn assert_halved(width: u16, height: u16, data: &[u8], level: &MipLevel) {
+    assert_eq!((level.width, level.height), (width / 2, height / 2));
+    let (width, level_width) = (usize::from(width), usize::from(level.width));
+    assert_eq!(level.row_stride, level_width);
+    assert_eq!(level.data.len(), level_width * usize::from(level.height));
+    for (index, &value) in level.data.iter().enumerate() {
+        let (x, y) = (index % level_width * 2, index / level_width * 2);
+        let sum: u32 = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+            .into_iter()
+            .map(|(x, y)| u32::from(data[y * width + x]))
+            .sum();
+        assert_eq!(u32::from(value), (sum + 2) / 4);
+    }
+}
+
+#[test]
+fn halves_each_level() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(64, 64, 16.0)
+        .with_mipmaps()
+        .build(requests(&face, "abcdef"))
+        .unwrap();
+    let sizes: Vec<u16> = asset.mip_levels.iter().map(|level| level.width).collect();
+    assert_eq!(sizes, [32, 16, 8, 4, 2, 1]);
+    let (mut width, mut height, mut data) = (asset.width, asset.height, &asset.data);
+    for level in &asset.mip_levels {
+        assert_halved(width, height, data, level);
+        (width, height, data) = (level.width, level.height, &level.data);
+    }
+}
+
+#[test]
+fn no_levels_without_mipmaps() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(64, 64, 16.0)
+        .build(requests(&face, "abcdef"))
+        .unwrap();
+    assert!(asset.mip_levels.is_empty());
+}
+
+#[test]
+fn safe_levels_follow_the_gutter() {
+    let face = common::roboto();
+    let max_level = |gutter| {
+        FontAssetBuilder::with_fixed_size(64, 64, 16.0)
+            .with_gutter(gutter)
+            .with_mipmaps()
+            .build(requests(&face, "ab"))
+            .unwrap()
+            .max_mip_level()
+    };
+    assert_eq!(max_level(0), 0);
+    assert_eq!(max_level(1), 0);
+    assert_eq!(max_level(3), 1);
+    assert_eq!(max_level(7), 2);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use blurry::{DistanceMode, FontAssetBuilder, RasterBackend, SdfFontAsset};
+
+use common::{requests, tile};
+
+/// How far the gradient of the field strays from one texel per texel, on
+/// average, near the outlines, where shaders anti-alias
+fn gradient_error(asset: &SdfFontAsset<char>) -> f32 {
+    let to_dist = |value: u8| (0.5 - f32::from(value) / 255.0) * 2.0 * asset.spread_px;
+    let (mut error, mut count) = (0.0, 0);
+    for glyph in &asset.metadata {
+        let dist: Vec<f32> = tile(asset, glyph).into_iter().map(to_dist).collect();
+        let width = usize::from(glyph.width);
+        for y in 1..usize::from(glyph.height) - 1 {
+            for x in 1..width - 1 {
+                let i = y * width + x;
+                let around = [i - 1, i + 1, i - width, i + width];
+                if dist[i].abs() > 1.0 || around.iter().any(|&j| dist[j].abs() >= asset.spread_px) {
+                    continue;
+                }
+                let dx = (dist[i + 1] - dist[i - 1]) / 2.0;
+                let dy = (dist[i + width] - dist[i - width]) / 2.0;
+                error += (dx.hypot(dy) - 1.0).abs();
+                count += 1;
+            }
+        }
+    }
+    error / count as f32
+}
+
+#[test]
+fn brings_gradients_closer_to_one() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_font_size(64.0)
+        .with_raster_backend(RasterBackend::Approximate { oversample: 4 });
+    let chars = "aeimosw";
+    let plain = builder.clone().build(requests(&face, chars)).unwrap();
+    let normalized = builder
+        .normalize_gradients()
+        .build(requests(&face, chars))
+        .unwrap();
+    let (before, after) = (gradient_error(&plain), gradient_error(&normalized));
+    assert!(
+        after < 0.9 * before,
+        "{after} is not much less than {before}"
+    );
+}
+
+#[test]
+fn leaves_unsigned_fields_alone() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_font_size(32.0)
+        .with_raster_backend(RasterBackend::Approximate { oversample: 4 })
+        .with_distance_mode(DistanceMode::Unsigned);
+    let plain = builder.clone().build(requests(&face, "aeo")).unwrap();
+    let normalized = builder
+        .normalize_gradients()
+        .build(requests(&face, "aeo"))
+        .unwrap();
+    assert_eq!(plain.data, normalized.data);
+}
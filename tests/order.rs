@@ -0,0 +1,41 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest, SkipReason};
+
+#[test]
+fn metadata_follows_request_order() {
+    let face = common::roboto();
+    // reverse alphabetical, with priorities which rank the glyphs in yet
+    // another order, so no order but the requests' would line up
+    let requests: Vec<_> = ('A'..='Z')
+        .chain('a'..='z')
+        .rev()
+        .enumerate()
+        .map(|(index, c)| GlyphRequest::new(index, &face, c).with_priority((index as i32 * 7) % 11))
+        .collect();
+    for threads in [1, 4] {
+        let asset = FontAssetBuilder::with_fixed_size(128, 128, 24.0)
+            .with_threads(threads)
+            .build(requests.iter().copied())
+            .unwrap();
+        assert!(!asset.skipped.is_empty());
+        assert!(asset
+            .skipped
+            .iter()
+            .all(|skipped| skipped.reason == SkipReason::DidNotFit));
+        let kept: Vec<_> = requests
+            .iter()
+            .filter(|request| {
+                !asset
+                    .skipped
+                    .iter()
+                    .any(|skipped| skipped.user_data == request.user_data)
+            })
+            .collect();
+        assert_eq!(kept.len(), asset.metadata.len());
+        for (request, glyph) in kept.iter().zip(&asset.metadata) {
+            assert_eq!(request.user_data, glyph.user_data);
+            assert_eq!(request.key, glyph.key);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use blurry::{FontAssetBuilder, Glyph, GlyphRequest, PackingStrategy};
+
+use common::requests;
+
+/// Whether two glyphs' tiles share a texel
+fn overlap(a: &Glyph<char>, b: &Glyph<char>) -> bool {
+    a.layer == b.layer
+        && a.x < b.x + b.width
+        && b.x < a.x + a.width
+        && a.y < b.y + b.height
+        && b.y < a.y + a.height
+}
+
+#[test]
+fn grid_places_glyphs_by_index() {
+    let face = common::roboto();
+    let chars = "abcdefghijklMW";
+    let asset = FontAssetBuilder::with_fixed_size(128, 128, 16.0)
+        .with_gutter(2)
+        .with_packing(PackingStrategy::Grid)
+        .build(requests(&face, chars))
+        .unwrap();
+    let gutter = asset.gutter;
+    // each cell is the size of the widest and tallest tile, and its gutter
+    let cell_width = asset
+        .metadata
+        .iter()
+        .map(|glyph| glyph.width)
+        .max()
+        .unwrap()
+        + gutter;
+    let cell_height = asset
+        .metadata
+        .iter()
+        .map(|glyph| glyph.height)
+        .max()
+        .unwrap()
+        + gutter;
+    let columns = (asset.width - gutter) / cell_width;
+    assert!(columns > 1 && usize::from(columns) < chars.len());
+    for (index, glyph) in asset.metadata.iter().enumerate() {
+        let index = index as u16;
+        assert!(!glyph.rotated);
+        assert_eq!(
+            (glyph.x, glyph.y),
+            (
+                gutter + index % columns * cell_width,
+                gutter + index / columns * cell_height
+            )
+        );
+    }
+}
+
+#[test]
+fn shelf_places_taller_glyphs_lower() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(128, 128, 16.0)
+        .with_packing(PackingStrategy::Shelf)
+        .build(requests(&face, "abcdefghijklmnopqrstuvwxyz.,"))
+        .unwrap();
+    assert!(asset.skipped.is_empty());
+    for (index, glyph) in asset.metadata.iter().enumerate() {
+        assert!(glyph.x + glyph.width <= asset.width);
+        assert!(glyph.y + glyph.height <= asset.height);
+        for other in &asset.metadata[index + 1..] {
+            assert!(!overlap(glyph, other));
+            // shelves fill from the bottom, tallest glyphs first
+            if glyph.height > other.height {
+                assert!(glyph.y <= other.y);
+            } else if other.height > glyph.height {
+                assert!(other.y <= glyph.y);
+            }
+        }
+    }
+}
+
+#[test]
+fn shelf_lays_tall_glyphs_on_their_side() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(128, 128, 32.0)
+        .with_packing(PackingStrategy::Shelf)
+        .build(
+            [
+                GlyphRequest::new('l', &face, 'l').with_rotation(true),
+                GlyphRequest::new('|', &face, '|'),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+    let (l, bar) = (&asset.metadata[0], &asset.metadata[1]);
+    assert!(l.rotated && l.width > l.height);
+    assert!(!bar.rotated && bar.height > bar.width);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "reference")]
+
+use blurry::reference::{self, GLYPHS};
+use ttf_parser::Face;
+
+fn assert_close(actual: f32, expected: f32, what: &str, ch: char) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "{ch:?} {what}: {actual} != {expected}"
+    );
+}
+
+#[test]
+fn build_gives_reference_glyphs() {
+    let font = reference::font();
+    let face = Face::parse(&font, 0).unwrap();
+    let asset = reference::build(&face).unwrap();
+    assert_eq!((asset.width, asset.height), reference::SIZE);
+    assert_eq!(asset.font_size, reference::FONT_SIZE);
+    assert_eq!(asset.metadata.len(), GLYPHS.len());
+    for (glyph, expected) in asset.metadata.iter().zip(&GLYPHS) {
+        let ch = expected.ch;
+        assert_eq!(glyph.user_data, ch);
+        assert_eq!(glyph.codepoint(), Some(ch));
+        assert_eq!(glyph.rotated, expected.rotated, "{ch:?} rotated");
+        assert_close(glyph.left, expected.left, "left", ch);
+        assert_close(glyph.right, expected.right, "right", ch);
+        assert_close(glyph.bottom, expected.bottom, "bottom", ch);
+        assert_close(glyph.top, expected.top, "top", ch);
+        assert_close(glyph.advance, expected.advance, "advance", ch);
+        assert_close(glyph.tex_left, expected.tex_left, "tex_left", ch);
+        assert_close(glyph.tex_right, expected.tex_right, "tex_right", ch);
+        assert_close(glyph.tex_bottom, expected.tex_bottom, "tex_bottom", ch);
+        assert_close(glyph.tex_top, expected.tex_top, "tex_top", ch);
+        assert_close(
+            glyph.tex_baseline,
+            expected.tex_baseline,
+            "tex_baseline",
+            ch,
+        );
+    }
+}
+
+#[test]
+fn probes_sample_inside_and_outside() {
+    let font = reference::font();
+    let face = Face::parse(&font, 0).unwrap();
+    let asset = reference::build(&face).unwrap();
+    let (width, height) = (usize::from(asset.width), usize::from(asset.height));
+    for (glyph, expected) in asset.metadata.iter().zip(&GLYPHS) {
+        for probe in expected.probes {
+            let (u, v) = reference::tex_coords(glyph, probe.x, probe.y);
+            // the nearest texel, with rows from the bottom up
+            let x = ((u * width as f32) as usize).min(width - 1);
+            let y = ((v * height as f32) as usize).min(height - 1);
+            let value = asset.data[y * asset.row_stride + x];
+            assert_eq!(value > 127, probe.inside, "{:?} {probe:?}", expected.ch);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest, MissingGlyphPolicy};
+
+#[test]
+fn rebuilds_the_same_requests() {
+    let face = common::roboto();
+    let requests = [
+        GlyphRequest::new(0, &face, 'a'),
+        GlyphRequest::new(1, &face, 'b')
+            .with_rotation(false)
+            .with_priority(3),
+        GlyphRequest::new(2, &face, 'c').with_rotation(true),
+        // a private use char Roboto has no glyph for
+        GlyphRequest::new(3, &face, '\u{E000}'),
+    ];
+    let builder = FontAssetBuilder::with_font_size(16.0);
+    let asset = builder
+        .clone()
+        .with_missing_glyphs(MissingGlyphPolicy::NotDef)
+        .build(requests.into_iter())
+        .unwrap();
+    let rebuilt: Vec<_> = asset.requests(&face).collect();
+    for (request, original) in rebuilt.iter().zip(&requests) {
+        assert_eq!(request.user_data, original.user_data);
+        assert_eq!(request.key, original.key);
+        assert_eq!(request.rotation, original.rotation);
+        assert_eq!(request.priority, original.priority);
+    }
+    // the missing glyph still falls back to .notdef without the policy
+    let again = builder.build(rebuilt.into_iter()).unwrap();
+    assert_eq!(again.metadata, asset.metadata);
+    let faces = [&face];
+    let from_faces = asset.requests_from_faces(&faces);
+    assert!(from_faces
+        .zip(asset.requests(&face))
+        .all(|(a, b)| (a.rotation, a.priority) == (b.rotation, b.priority)));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest, SdfFontAsset};
+use ttf_parser::Face;
+
+fn build(face: &Face<'_>, font_size: f32) -> SdfFontAsset<char> {
+    FontAssetBuilder::with_font_size(font_size)
+        .build(('!'..='~').map(|c| GlyphRequest::new(c, face, c)))
+        .unwrap()
+}
+
+#[test]
+fn builds_share_a_face_across_threads() {
+    let face = common::roboto();
+    let sizes = [12.0, 16.0, 24.0, 32.0];
+    let expected: Vec<_> = sizes.iter().map(|&size| build(&face, size)).collect();
+    let built: Vec<_> = std::thread::scope(|scope| {
+        let face = &face;
+        let workers: Vec<_> = sizes
+            .iter()
+            .map(|&size| scope.spawn(move || build(face, size)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    for (asset, expected) in built.iter().zip(&expected) {
+        assert_eq!(
+            (asset.width, asset.height),
+            (expected.width, expected.height)
+        );
+        assert_eq!(asset.data, expected.data);
+        assert_eq!(asset.metadata, expected.metadata);
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use blurry::{Error, FontAssetBuilder, Glyph};
+use common::{requests, tile};
+
+const CHARS: &str = "abcdefghijklmnopqrstuvwxyzMW";
+
+fn area(glyph: &Glyph<char>) -> u32 {
+    u32::from(glyph.width) * u32::from(glyph.height)
+}
+
+#[test]
+fn fits_in_one_asset() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_texture_size(256, 256);
+    let assets = builder
+        .clone()
+        .build_split(8.0, requests(&face, CHARS))
+        .unwrap();
+    let whole = builder.build(requests(&face, CHARS)).unwrap();
+    assert_eq!(assets.len(), 1);
+    assert!(whole.font_size >= 8.0);
+    assert_eq!(assets[0].metadata, whole.metadata);
+    assert_eq!(assets[0].data, whole.data);
+}
+
+#[test]
+fn splits_greedily_by_area() {
+    let face = common::roboto();
+    let (size, font_size) = (64, 24.0);
+    let assets = FontAssetBuilder::with_texture_size(size, size)
+        .build_split(font_size, requests(&face, CHARS))
+        .unwrap();
+    assert!(assets.len() > 2, "only {} assets", assets.len());
+
+    // every glyph is in exactly one asset, in the order requested
+    let mut found: Vec<char> = Vec::new();
+    for asset in &assets {
+        assert_eq!(asset.font_size, font_size);
+        assert!(asset.skipped.is_empty());
+        let chars: Vec<char> = asset.metadata.iter().map(|glyph| glyph.user_data).collect();
+        let mut sorted = chars.clone();
+        sorted.sort_by_key(|&c| CHARS.find(c));
+        assert_eq!(chars, sorted);
+        found.extend(chars);
+    }
+    found.sort_unstable();
+    let mut expected: Vec<char> = CHARS.chars().collect();
+    expected.sort_unstable();
+    assert_eq!(found, expected);
+
+    let fixed = FontAssetBuilder::with_fixed_size(size, size, font_size);
+    for (index, asset) in assets.iter().enumerate() {
+        // each glyph renders as it would in an asset of its own
+        let chars: String = asset.metadata.iter().map(|glyph| glyph.user_data).collect();
+        let alone = fixed.clone().build(requests(&face, &chars)).unwrap();
+        assert!(alone.skipped.is_empty());
+        for (glyph, expected) in asset.metadata.iter().zip(&alone.metadata) {
+            assert_eq!(tile(asset, glyph), tile(&alone, expected));
+        }
+
+        // the largest glyphs are placed first, and each asset is filled
+        // until the largest glyph left over would not fit
+        let Some(next) = assets.get(index + 1) else {
+            continue;
+        };
+        let smallest = asset.metadata.iter().map(area).min().unwrap();
+        let largest = next
+            .metadata
+            .iter()
+            .max_by_key(|glyph| area(glyph))
+            .unwrap();
+        assert!(smallest >= area(largest));
+        let more = format!("{chars}{}", largest.user_data);
+        let overfull = fixed.clone().build(requests(&face, &more)).unwrap();
+        assert_eq!(overfull.skipped.len(), 1);
+    }
+}
+
+#[test]
+fn fails_if_a_glyph_fits_nowhere() {
+    let face = common::roboto();
+    let result =
+        FontAssetBuilder::with_texture_size(32, 32).build_split(64.0, requests(&face, "aW"));
+    assert!(matches!(result, Err(Error::PackingAtlasFailed)));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use blurry::{FontAssetBuilder, Glyph, GlyphRequest, PackingStrategy};
+
+fn assert_near((x, y): (f32, f32), (ex, ey): (f32, f32)) {
+    assert!(
+        (x - ex).abs() < 1e-5 && (y - ey).abs() < 1e-5,
+        "({x}, {y}) is not ({ex}, {ey})"
+    );
+}
+
+/// The corners of the glyph's box map to the corners of its quad, and back
+fn assert_maps_corners(glyph: &Glyph<char>) {
+    let transform = glyph.tex_transform();
+    let inverse = transform.inverse().unwrap();
+    // the quad runs counterclockwise around the glyph from its bottom left
+    let corners = [
+        (glyph.left, glyph.bottom),
+        (glyph.right, glyph.bottom),
+        (glyph.right, glyph.top),
+        (glyph.left, glyph.top),
+    ];
+    for (corner, uv) in corners.into_iter().zip(glyph.uv_quad()) {
+        assert_near(transform.apply(corner.0, corner.1), uv);
+        assert_near(inverse.apply(uv.0, uv.1), corner);
+    }
+}
+
+#[test]
+fn maps_rotated_glyphs() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_fixed_size(128, 128, 32.0)
+        .with_packing(PackingStrategy::Shelf)
+        .build(
+            [
+                GlyphRequest::new('l', &face, 'l').with_rotation(true),
+                GlyphRequest::new('T', &face, 'T').with_rotation(false),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+    let (l, t) = (&asset.metadata[0], &asset.metadata[1]);
+    assert!(l.rotated && !t.rotated);
+    assert_maps_corners(l);
+    assert_maps_corners(t);
+
+    // the glyph's x axis runs up the image, and its y axis to the right,
+    // so the top of the `l` is at the tile's right in texels
+    let texels = l.tex_transform().in_texels(asset.width, asset.height);
+    let (x, _) = texels.apply(l.left, l.top);
+    let (_, y) = texels.apply(l.right, l.bottom);
+    assert!((x - f32::from(l.x + l.width)).abs() <= 0.5);
+    assert!((y - f32::from(l.y + l.height)).abs() <= 0.5);
+}
+
+#[test]
+fn empty_glyphs_have_no_inverse() {
+    let face = common::roboto();
+    let asset = FontAssetBuilder::with_font_size(16.0)
+        .build(common::requests(&face, " "))
+        .unwrap();
+    assert!(asset.metadata[0].tex_transform().inverse().is_none());
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use blurry::{FontAssetBuilder, GlyphRequest, SamplePosition};
+
+/// Build glyphs squashed to a sliver with no padding across it, so their
+/// tiles are as few texels across as the sample position allows
+fn build_thin(wide: bool, sample: SamplePosition, rotate: bool) {
+    let face = common::roboto();
+    let (transform, padding) = if wide {
+        ([1.0, 0.0, 0.0, 0.001, 0.0, 0.0], (0.1, 0.0))
+    } else {
+        ([0.001, 0.0, 0.0, 1.0, 0.0, 0.0], (0.0, 0.1))
+    };
+    let mut builder = FontAssetBuilder::with_font_size(16.0)
+        .with_padding_ratios(padding.0, padding.1)
+        .with_sample_position(sample);
+    if rotate {
+        builder = builder.allow_rotating_glyphs();
+    }
+    let requests = "lW|".chars();
+    let asset = builder
+        .build(requests.map(|c| GlyphRequest::new(c, &face, c).with_transform(transform)))
+        .unwrap();
+    assert_eq!(asset.metadata.len(), 3);
+    for glyph in &asset.metadata {
+        let across = if wide == glyph.rotated {
+            glyph.width
+        } else {
+            glyph.height
+        };
+        let expected = match sample {
+            SamplePosition::Center => 1,
+            _ => 2,
+        };
+        assert_eq!(across, expected, "{glyph:?}");
+        let [left, bottom, right, top] = glyph.tex_rect();
+        for uv in [left, bottom, right, top] {
+            assert!((0.0..=1.0).contains(&uv), "{glyph:?}");
+        }
+        assert!(left < right && bottom < top, "{glyph:?}");
+    }
+}
+
+#[test]
+fn one_texel_wide() {
+    for sample in [SamplePosition::Center, SamplePosition::Corner] {
+        for rotate in [false, true] {
+            build_thin(false, sample, rotate);
+        }
+    }
+}
+
+#[test]
+fn one_texel_tall() {
+    for sample in [SamplePosition::Center, SamplePosition::Corner] {
+        for rotate in [false, true] {
+            build_thin(true, sample, rotate);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use blurry::FontAssetBuilder;
+
+use common::{requests, tile};
+
+#[test]
+fn trims_the_image_to_its_glyphs() {
+    let face = common::roboto();
+    let builder = FontAssetBuilder::with_fixed_size(256, 256, 16.0);
+    let full = builder.clone().build(requests(&face, "abc")).unwrap();
+    let trimmed = builder
+        .trim_to_content(4)
+        .build(requests(&face, "abc"))
+        .unwrap();
+    assert!(trimmed.width < 256 && trimmed.height < 256);
+    assert_eq!((trimmed.width % 4, trimmed.height % 4), (0, 0));
+    assert_eq!(
+        trimmed.data.len(),
+        usize::from(trimmed.height) * trimmed.row_stride
+    );
+    // only the texture coordinates change, and the tiles are as they were
+    for (glyph, expected) in trimmed.metadata.iter().zip(&full.metadata) {
+        assert_eq!((glyph.x, glyph.y), (expected.x, expected.y));
+        assert!(glyph.x + glyph.width <= trimmed.width);
+        assert!(glyph.y + glyph.height <= trimmed.height);
+        assert!(glyph.tex_right <= 1.0 && glyph.tex_top <= 1.0);
+        assert_eq!(tile(&trimmed, glyph), tile(&full, expected));
+    }
+}
+
+#[test]
+fn trims_empty_borders_of_tiles() {
+    let face = common::roboto();
+    // thinning the outline leaves rows and columns of the padded box past
+    // the reach of the field
+    let builder = FontAssetBuilder::with_font_size(32.0).with_embolden(-0.05);
+    let full = builder.clone().build(requests(&face, "o")).unwrap();
+    let trimmed = builder
+        .trim_glyph_borders()
+        .build(requests(&face, "o"))
+        .unwrap();
+    let (full_glyph, glyph) = (&full.metadata[0], &trimmed.metadata[0]);
+    assert!(glyph.width < full_glyph.width);
+    assert!(glyph.height < full_glyph.height);
+    assert!(glyph.left > full_glyph.left && glyph.right < full_glyph.right);
+    assert!(glyph.bottom > full_glyph.bottom && glyph.top < full_glyph.top);
+    assert_eq!(glyph.advance, full_glyph.advance);
+
+    // the texels kept are those of the full tile, offset by the rows and
+    // columns trimmed from its left and bottom
+    let texel = (full_glyph.right - full_glyph.left) / f32::from(full_glyph.width);
+    let dx = ((glyph.left - full_glyph.left) / texel).round() as usize;
+    let dy = ((glyph.bottom - full_glyph.bottom) / texel).round() as usize;
+    let (full_tile, tile) = (tile(&full, full_glyph), tile(&trimmed, glyph));
+    let full_width = usize::from(full_glyph.width);
+    for (y, row) in tile.chunks(usize::from(glyph.width)).enumerate() {
+        let start = (y + dy) * full_width + dx;
+        assert_eq!(row, &full_tile[start..start + row.len()]);
+    }
+    // and every texel trimmed away held the farthest value
+    let kept = |x: usize, y: usize| {
+        (dx..dx + usize::from(glyph.width)).contains(&x)
+            && (dy..dy + usize::from(glyph.height)).contains(&y)
+    };
+    for (index, &value) in full_tile.iter().enumerate() {
+        if !kept(index % full_width, index / full_width) {
+            assert_eq!(value, 0);
+        }
+    }
+}